@@ -0,0 +1,78 @@
+//! Compares the batch mapping of `from_row_refs`, which looks the columns up
+//! by name once, with mapping every row on its own.
+//!
+//! Run with `cargo bench --bench from_rows --features derive,test-util`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::types::Type;
+use tokio_postgres::Row;
+
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "wide")]
+pub struct Wide {
+    pub c00: i64,
+    pub c01: i64,
+    pub c02: i64,
+    pub c03: i64,
+    pub c04: String,
+    pub c05: String,
+    pub c06: String,
+    pub c07: String,
+    pub c08: Option<i32>,
+    pub c09: Option<i32>,
+    pub c10: Option<i32>,
+    pub c11: Option<i32>,
+    pub c12: bool,
+    pub c13: bool,
+    pub c14: f64,
+    pub c15: f64,
+}
+
+fn wide_row(id: i64) -> Row {
+    let mut builder = MockRowBuilder::new();
+
+    for column in &["c00", "c01", "c02", "c03"] {
+        builder = builder.column(column, Type::INT8, id);
+    }
+    for column in &["c04", "c05", "c06", "c07"] {
+        builder = builder.column(column, Type::TEXT, format!("value {}", id));
+    }
+    for column in &["c08", "c09", "c10", "c11"] {
+        builder = builder.column(column, Type::INT4, Some(id as i32));
+    }
+    for column in &["c12", "c13"] {
+        builder = builder.column(column, Type::BOOL, id % 2 == 0);
+    }
+    for column in &["c14", "c15"] {
+        builder = builder.column(column, Type::FLOAT8, id as f64);
+    }
+
+    builder.build()
+}
+
+fn from_rows(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_row_refs");
+
+    for &len in &[10, 100, 1_000] {
+        let rows = (0..len).map(wide_row).collect::<Vec<_>>();
+
+        group.bench_with_input(BenchmarkId::new("batch", len), &rows, |b, rows| {
+            b.iter(|| Wide::from_row_refs(rows).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("per_row", len), &rows, |b, rows| {
+            b.iter(|| {
+                rows.iter()
+                    .map(Wide::from_row_ref)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, from_rows);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Compares mapping batches of wide rows in parallel with `from_rows_par` to
+//! mapping them sequentially with `from_rows`, to find the batch size from
+//! which the parallel mapping pays off on a given machine.
+//!
+//! Run with `cargo bench --bench from_rows_par --features derive,rayon,test-util`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::types::Type;
+use tokio_postgres::Row;
+
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "wide")]
+pub struct Wide {
+    pub c00: i64,
+    pub c01: i64,
+    pub c02: String,
+    pub c03: String,
+    pub c04: String,
+    pub c05: String,
+    pub c06: Option<i32>,
+    pub c07: Option<i32>,
+    pub c08: f64,
+    pub c09: f64,
+}
+
+fn wide_rows(id: i64, count: usize) -> Vec<Row> {
+    let mut builder = MockRowBuilder::new();
+
+    for column in &["c00", "c01"] {
+        builder = builder.column(column, Type::INT8, id);
+    }
+    for column in &["c02", "c03", "c04", "c05"] {
+        builder = builder.column(column, Type::TEXT, "x".repeat(64));
+    }
+    for column in &["c06", "c07"] {
+        builder = builder.column(column, Type::INT4, Some(id as i32));
+    }
+    for column in &["c08", "c09"] {
+        builder = builder.column(column, Type::FLOAT8, id as f64);
+    }
+
+    builder.build_many(count)
+}
+
+fn from_rows_par(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_rows");
+
+    for &len in &[100, 1_000, 5_000] {
+        // The mapping consumes the rows and `Row` isn't `Clone`, so every
+        // iteration needs fresh rows.
+        let mut build = || wide_rows(1, len);
+
+        group.bench_function(BenchmarkId::new("sequential", len), |b| {
+            b.iter_batched(&mut build, Wide::from_rows, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("parallel", len), |b| {
+            b.iter_batched(&mut build, Wide::from_rows_par, BatchSize::LargeInput)
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, from_rows_par);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+//! Serves users mapped with `PostgresMapper` from an actix-web handler.
+//!
+//! Run with `cargo run --example actix --features actix,derive`, with a
+//! Postgres server reachable through `DATABASE_URL`.
+
+use actix_web::{web, App, HttpServer};
+use serde::Serialize;
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::{Client, NoTls};
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "users")]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+async fn get_user(
+    client: web::Data<Client>,
+    id: web::Path<i64>,
+) -> Result<web::Json<User>, tokio_pg_mapper::Error> {
+    let stmt = format!(
+        "SELECT {} FROM {} WHERE id = $1",
+        User::sql_fields(),
+        User::sql_table()
+    );
+    let row = client.query_one(stmt.as_str(), &[&id.into_inner()]).await?;
+
+    Ok(web::Json(User::from_row(row)?))
+}
+
+fn main() -> std::io::Result<()> {
+    actix_web::rt::System::new().block_on(async {
+        let url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "host=localhost user=postgres".to_string());
+        let (client, connection) = tokio_postgres::connect(&url, NoTls)
+            .await
+            .map_err(std::io::Error::other)?;
+        actix_web::rt::spawn(connection);
+
+        let client = web::Data::new(client);
+
+        HttpServer::new(move || {
+            App::new()
+                .app_data(client.clone())
+                .route("/users/{id}", web::get().to(get_user))
+        })
+        .bind(("127.0.0.1", 8080))?
+        .run()
+        .await
+    })
+}
@@ -0,0 +1,89 @@
+//! Loads orders with their items in a single query, mapping `array_agg` of
+//! the items table's row type into a `Vec<Item>` field.
+//!
+//! Run with `cargo run --example array_agg --features derive`, with a
+//! Postgres server reachable through `DATABASE_URL` and the tables:
+//!
+//! ```sql
+//! CREATE TABLE orders (id bigint PRIMARY KEY, customer text NOT NULL);
+//! CREATE TABLE items (
+//!     id bigint PRIMARY KEY,
+//!     order_id bigint NOT NULL REFERENCES orders,
+//!     sku text NOT NULL,
+//!     note text
+//! );
+//! ```
+
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::NoTls;
+
+/// Every table has a composite type of the same name for its rows, whose
+/// attributes are the table's columns in order, so the fields must be
+/// declared in that order too.
+#[derive(Debug, PostgresMapper)]
+#[pg_mapper(table = "items", composite = "items")]
+pub struct Item {
+    pub id: i64,
+    pub order_id: i64,
+    pub sku: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, PostgresMapper)]
+#[pg_mapper(table = "orders")]
+pub struct Order {
+    pub id: i64,
+    pub customer: String,
+    pub items: Vec<Item>,
+}
+
+/// The same order, keeping arrays which may contain NULL elements as is.
+#[derive(Debug, PostgresMapper)]
+#[pg_mapper(table = "orders")]
+pub struct SparseOrder {
+    pub id: i64,
+    pub customer: String,
+    pub items: Option<Vec<Option<Item>>>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "host=localhost user=postgres".to_string());
+    let (client, connection) = tokio_postgres::connect(&url, NoTls).await?;
+    tokio::spawn(connection);
+
+    // `array_agg` returns NULL rather than an empty array when there's
+    // nothing to aggregate, and the `FILTER` keeps the NULL row of an order
+    // without items out of the array, so `COALESCE` is needed for `Vec<Item>`.
+    let rows = client
+        .query(
+            "SELECT o.id, o.customer,
+                 COALESCE(array_agg(i ORDER BY i.id) FILTER (WHERE i.id IS NOT NULL), '{}')
+                     AS items
+             FROM orders o
+             LEFT JOIN items i ON i.order_id = o.id
+             GROUP BY o.id
+             ORDER BY o.id",
+            &[],
+        )
+        .await?;
+    let orders = Order::from_row_refs(&rows)?;
+    println!("{:?}", orders);
+
+    // Without them, `Option<Vec<Option<Item>>>` maps whatever is returned.
+    let rows = client
+        .query(
+            "SELECT o.id, o.customer, array_agg(i ORDER BY i.id) AS items
+             FROM orders o
+             LEFT JOIN items i ON i.order_id = o.id
+             GROUP BY o.id
+             ORDER BY o.id",
+            &[],
+        )
+        .await?;
+    let orders = SparseOrder::from_row_refs(&rows)?;
+    println!("{:?}", orders);
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! Maps users with `PostgresMapper` through a deadpool-postgres `Pool`.
+//!
+//! Run with `cargo run --example deadpool --features deadpool,derive`, with a
+//! Postgres server reachable through `DATABASE_URL`.
+
+use deadpool_postgres::{Config, Runtime};
+use tokio_pg_mapper::deadpool::PoolExt;
+use tokio_pg_mapper::PostgresMapper;
+use tokio_postgres::NoTls;
+
+#[derive(Debug, PostgresMapper)]
+#[pg_mapper(table = "users")]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = Config::new();
+    config.url = Some(
+        std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "host=localhost user=postgres".to_string()),
+    );
+    let pool = config.create_pool(Some(Runtime::Tokio1), NoTls)?;
+
+    let users: Vec<User> = pool
+        .query_as("SELECT id, name, email FROM users ORDER BY id", &[])
+        .await?;
+    println!("{:?}", users);
+
+    let user: Option<User> = pool
+        .query_opt_as("SELECT id, name, email FROM users WHERE id = $1", &[&1i64])
+        .await?;
+    println!("{:?}", user);
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Maps users from the payloads of `LISTEN`/`NOTIFY` notifications.
+//!
+//! Run with `cargo run --example notification --features json,derive`, with a
+//! Postgres server reachable through `DATABASE_URL`, then notify with
+//! `SELECT pg_notify('users_changed', row_to_json(users)::text) FROM users`.
+
+use futures_util::stream::{self, StreamExt};
+use tokio_pg_mapper::{FromNotificationPayload, PostgresMapper};
+use tokio_postgres::{AsyncMessage, NoTls};
+
+#[derive(Debug, PostgresMapper)]
+#[pg_mapper(table = "users", notification)]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "host=localhost user=postgres".to_string());
+    let (client, mut connection) = tokio_postgres::connect(&url, NoTls).await?;
+
+    // Notifications are delivered by the connection, so drive it by hand and
+    // forward them to the main task.
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+
+        while let Some(message) = messages.next().await {
+            match message {
+                Ok(AsyncMessage::Notification(notification)) => {
+                    let _ = tx.send(notification);
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    eprintln!("connection error: {}", err);
+                    break;
+                }
+            }
+        }
+    });
+
+    client.batch_execute("LISTEN users_changed").await?;
+
+    while let Some(notification) = rx.recv().await {
+        match User::from_notification(&notification) {
+            Ok(user) => println!("changed: {:?}", user),
+            Err(err) => eprintln!("invalid payload: {}", err),
+        }
+    }
+
+    Ok(())
+}
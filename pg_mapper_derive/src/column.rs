@@ -0,0 +1,436 @@
+//! The single model of the mapped columns, from which every generated column
+//! list and SQL string is built, so that they agree on names and quoting.
+
+use syn::{DataStruct, Expr, Field, GenericArgument, Ident, Lifetime, PathArguments, Type};
+
+use crate::{is_string, option_inner_type, parse_field_attrs};
+
+/// A mapped column, with the attributes of its field.
+pub struct ColumnSpec<'a> {
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+    /// The name of the column, as looked up in rows.
+    pub name: String,
+    pub primary_key: bool,
+    pub readonly: bool,
+    pub unique: bool,
+    /// Whether the column holds the version of the row, checked and
+    /// incremented by updates.
+    pub version: bool,
+    /// Whether the column is set on soft deleted rows.
+    pub soft_delete: bool,
+    /// The `pg_type` hint of the field.
+    pub pg_type: Option<String>,
+    /// What NULL values are mapped to, for `default_on_null` fields.
+    pub null_default: Option<NullDefault>,
+    /// Whether empty strings are mapped to `None`.
+    pub empty_as_none: bool,
+    /// The whitespace trimmed from strings.
+    pub trim: Option<Trim>,
+    /// The unit of the epoch integer stored for a timestamp field.
+    pub epoch: Option<Epoch>,
+    /// Whether the column holds a JSON document deserialized into the field.
+    pub json_text: bool,
+}
+
+/// The whitespace trimmed from the strings of a `trim` field.
+#[derive(Clone, Copy)]
+pub enum Trim {
+    /// Trailing whitespace, for `#[pg_mapper(trim)]` or
+    /// `#[pg_mapper(trim = "end")]`.
+    End,
+    /// Leading and trailing whitespace, for `#[pg_mapper(trim = "both")]`.
+    Both,
+}
+
+/// The unit of the epoch integer of an `epoch` field.
+#[derive(Clone, Copy)]
+pub enum Epoch {
+    /// Seconds, for `#[pg_mapper(epoch = "seconds")]`.
+    Seconds,
+    /// Milliseconds, for `#[pg_mapper(epoch = "millis")]`.
+    Millis,
+    /// Microseconds, for `#[pg_mapper(epoch = "micros")]`.
+    Micros,
+}
+
+impl Epoch {
+    fn micros_per_unit(self) -> i64 {
+        match self {
+            Epoch::Seconds => 1_000_000,
+            Epoch::Millis => 1_000,
+            Epoch::Micros => 1,
+        }
+    }
+}
+
+/// The value of a `default_on_null` field whose column is NULL.
+pub enum NullDefault {
+    /// `Default::default()`, for `#[pg_mapper(default_on_null)]`.
+    Default,
+    /// The result of a function, for
+    /// `#[pg_mapper(default_on_null = "path::to::function")]`.
+    Function(syn::Path),
+}
+
+impl<'a> ColumnSpec<'a> {
+    pub fn new(field: &'a Field) -> Self {
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("Expected structfield identifier");
+        let attrs = parse_field_attrs(field);
+
+        if attrs.empty_as_none
+            && option_inner_type(&field.ty).is_none()
+            && attrs.null_default.is_none()
+        {
+            panic!("empty_as_none requires an Option or default_on_null field");
+        }
+
+        if attrs.version && attrs.primary_key {
+            panic!("a version field can't be part of the primary key");
+        }
+
+        if attrs.soft_delete && attrs.primary_key {
+            panic!("a soft_delete field can't be part of the primary key");
+        }
+
+        Self {
+            ident,
+            ty: &field.ty,
+            name: attrs.rename.unwrap_or_else(|| ident.to_string()),
+            primary_key: attrs.primary_key,
+            readonly: attrs.readonly,
+            unique: attrs.unique,
+            version: attrs.version,
+            soft_delete: attrs.soft_delete,
+            pg_type: attrs.pg_type,
+            null_default: attrs.null_default,
+            empty_as_none: attrs.empty_as_none,
+            trim: attrs.trim,
+            epoch: attrs.epoch,
+            json_text: attrs.json_text,
+        }
+    }
+
+    /// Get the name of the field, as reported in errors.
+    pub fn field_name(&self) -> String {
+        self.ident.to_string()
+    }
+
+    /// Get the variant of the column in the enum generated for
+    /// `#[pg_mapper(query)]`, the field name in upper camel case.
+    pub fn variant(&self) -> Ident {
+        let field_name = self.field_name();
+        let variant = field_name
+            .trim_start_matches("r#")
+            .split('_')
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let (first, rest) = word.split_at(1);
+                first.to_ascii_uppercase() + rest
+            })
+            .collect::<String>();
+
+        Ident::new(&variant, self.ident.span())
+    }
+
+    /// Whether the field accepts NULL values: it is an `Option`, or NULL
+    /// values are replaced by a default.
+    pub fn nullable(&self) -> bool {
+        option_inner_type(self.ty).is_some() || self.null_default.is_some()
+    }
+
+    /// Get the type fetched from rows: the field type, with `i64` in place of
+    /// the timestamps of `epoch` fields and the [`substitute`] of other
+    /// fields, wrapped in an `Option` for `default_on_null` fields.
+    ///
+    /// [`substitute`]: #method.substitute
+    pub fn fetched_type(&self) -> Type {
+        let inner = option_inner_type(self.ty);
+        let fetched = match self.epoch {
+            Some(_) => Some(syn::parse_quote!(i64)),
+            None => self.substitute().map(|(fetched, _)| fetched),
+        };
+        let ty = match fetched {
+            Some(fetched) if inner.is_some() => syn::parse_quote!(Option<#fetched>),
+            Some(fetched) => fetched,
+            None => self.ty.clone(),
+        };
+
+        match self.null_default {
+            Some(_) => syn::parse_quote!(Option<#ty>),
+            None => ty,
+        }
+    }
+
+    /// Get the field value from a value of the [`fetched_type`], in turn
+    /// trimming the strings of `trim` fields, mapping empty strings to `None`
+    /// for `empty_as_none` fields, converting substitutes into the field
+    /// type, converting the epoch integers of `epoch` fields, reporting
+    /// errors for the given mapped type, and replacing NULL values with the
+    /// default of `default_on_null` fields.
+    ///
+    /// [`fetched_type`]: #method.fetched_type
+    pub fn field_value(&self, fetched: Expr, type_name: &str) -> Expr {
+        let fetched = self.trimmed(fetched);
+        // Other types are never empty in rows, and simple query mappings
+        // parse blank texts as NULL.
+        let text = option_inner_type(self.ty).is_some_and(is_text);
+        let fetched = if self.empty_as_none && text {
+            syn::parse_quote!(tokio_pg_mapper::empty_as_none(#fetched))
+        } else {
+            fetched
+        };
+        let fetched = self.convert_substitute(fetched);
+        let fetched = self.convert_epoch(fetched, type_name);
+
+        match self.null_default {
+            Some(NullDefault::Default) => {
+                syn::parse_quote!(#fetched.unwrap_or_default())
+            }
+            Some(NullDefault::Function(ref function)) => {
+                syn::parse_quote!(#fetched.unwrap_or_else(#function))
+            }
+            None => fetched,
+        }
+    }
+
+    /// Get the type fetched in place of the field type, ignoring an outer
+    /// `Option`, and the function converting it into the field type: a
+    /// `JsonText` for `json_text` fields, or the [`type_substitute`] of types
+    /// without an owning `FromSql` implementation.
+    ///
+    /// [`type_substitute`]: fn.type_substitute.html
+    pub fn substitute(&self) -> Option<(Type, syn::Path)> {
+        let ty = option_inner_type(self.ty).unwrap_or(self.ty);
+
+        if self.json_text {
+            Some((
+                syn::parse_quote!(tokio_pg_mapper::JsonText<#ty>),
+                syn::parse_quote!(tokio_pg_mapper::JsonText::into_inner),
+            ))
+        } else {
+            type_substitute(ty)
+        }
+    }
+
+    /// Converts a fetched substitute, or an `Option` of one, into the field
+    /// type.
+    pub fn convert_substitute(&self, fetched: Expr) -> Expr {
+        let inner = option_inner_type(self.ty);
+        let convert = match self.substitute() {
+            Some((_, convert)) => convert,
+            None => return fetched,
+        };
+
+        if inner.is_some() || self.null_default.is_some() {
+            syn::parse_quote!(#fetched.map(#convert))
+        } else {
+            syn::parse_quote!(#convert(#fetched))
+        }
+    }
+
+    /// Converts a fetched epoch integer, or an `Option` of one, into the
+    /// timestamp of an `epoch` field.
+    pub fn convert_epoch(&self, fetched: Expr, type_name: &str) -> Expr {
+        let micros = match self.epoch {
+            Some(epoch) => epoch.micros_per_unit(),
+            None => return fetched,
+        };
+        let name = &self.name;
+
+        if option_inner_type(self.ty).is_some() || self.null_default.is_some() {
+            syn::parse_quote! {
+                #fetched
+                    .map(|value| tokio_pg_mapper::from_epoch(value, #micros, #name, #type_name))
+                    .transpose()?
+            }
+        } else {
+            syn::parse_quote! {
+                tokio_pg_mapper::from_epoch(#fetched, #micros, #name, #type_name)?
+            }
+        }
+    }
+
+    /// Get a reference to the field of `self` as a query parameter, written
+    /// as an epoch integer for `epoch` fields.
+    pub fn param(&self) -> Expr {
+        let ident = self.ident;
+
+        match self.epoch {
+            Some(epoch) => {
+                let micros = epoch.micros_per_unit();
+
+                syn::parse_quote! {
+                    tokio_pg_mapper::EpochParam::<_, { #micros }>::from_ref(&self.#ident)
+                }
+            }
+            None => syn::parse_quote!(&self.#ident),
+        }
+    }
+
+    /// Get the type of the field as a typed query parameter borrowed for the
+    /// given lifetime, and the expression borrowing it like [`param`]:
+    /// strings, and `Option`s of them, are borrowed as `str`.
+    ///
+    /// [`param`]: #method.param
+    pub fn typed_param(&self, lifetime: &Lifetime) -> (Type, Expr) {
+        let ident = self.ident;
+        let ty = self.ty;
+
+        if let Some(epoch) = self.epoch {
+            let micros = epoch.micros_per_unit();
+            let param_ty = syn::parse_quote! {
+                &#lifetime tokio_pg_mapper::EpochParam<#ty, { #micros }>
+            };
+
+            return (param_ty, self.param());
+        }
+
+        match option_inner_type(ty) {
+            _ if is_string(ty) => (
+                syn::parse_quote!(&#lifetime str),
+                syn::parse_quote!(self.#ident.as_str()),
+            ),
+            Some(inner) if is_string(inner) => (
+                syn::parse_quote!(Option<&#lifetime str>),
+                syn::parse_quote!(self.#ident.as_deref()),
+            ),
+            _ => (syn::parse_quote!(&#lifetime #ty), self.param()),
+        }
+    }
+
+    /// Trims the whitespace of a string, or an `Option` of one, for `trim`
+    /// fields.
+    pub fn trimmed(&self, value: Expr) -> Expr {
+        match self.trim {
+            Some(Trim::End) => {
+                syn::parse_quote!(tokio_pg_mapper::trim_whitespace(#value, false))
+            }
+            Some(Trim::Both) => {
+                syn::parse_quote!(tokio_pg_mapper::trim_whitespace(#value, true))
+            }
+            None => value,
+        }
+    }
+}
+
+/// Whether a type is a `String`, `&str` or `Cow<str>`.
+fn is_text(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref p) => {
+            (p.qself.is_none() && p.path.is_ident("String")) || is_cow_str(ty)
+        }
+        Type::Reference(ref r) => {
+            matches!(*r.elem, Type::Path(ref p) if p.path.is_ident("str"))
+        }
+        _ => false,
+    }
+}
+
+/// Whether a type is a `Cow<str>`, which has no `FromSql` implementation
+/// owning its value and is fetched as a `String` instead.
+pub fn is_cow_str(ty: &Type) -> bool {
+    let segment = match *ty {
+        Type::Path(ref p) if p.qself.is_none() => p.path.segments.last(),
+        _ => None,
+    };
+    let segment = match segment {
+        Some(segment) if segment.ident == "Cow" => segment,
+        _ => return false,
+    };
+
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref args) => args.args.iter().any(|arg| {
+            matches!(arg, GenericArgument::Type(Type::Path(ref p)) if p.path.is_ident("str"))
+        }),
+        _ => false,
+    }
+}
+
+/// Get the type fetched in place of a field type without an owning `FromSql`
+/// implementation, and the function converting it into the field type:
+///
+/// - `String` for `Cow<str>`, wrapped in `Cow::Owned`
+/// - `PgBytea` for `Bytes`, unwrapped
+/// - `PgDuration` for `std::time::Duration`, unwrapped
+fn type_substitute(ty: &Type) -> Option<(Type, syn::Path)> {
+    if is_cow_str(ty) {
+        Some((
+            syn::parse_quote!(String),
+            syn::parse_quote!(::std::borrow::Cow::Owned),
+        ))
+    } else if is_bytes(ty) {
+        Some((
+            syn::parse_quote!(tokio_pg_mapper::PgBytea),
+            syn::parse_quote!(tokio_pg_mapper::PgBytea::into_inner),
+        ))
+    } else if is_std_duration(ty) {
+        Some((
+            syn::parse_quote!(tokio_pg_mapper::PgDuration),
+            syn::parse_quote!(tokio_pg_mapper::PgDuration::into_inner),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Whether a type is a `Bytes`.
+fn is_bytes(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref p) if p.qself.is_none() => {
+            p.path.segments.last().is_some_and(|segment| {
+                segment.ident == "Bytes" && segment.arguments.is_empty()
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Whether a type is a `std::time::Duration`, named `Duration` or by its
+/// full path, as the `Duration` of other crates, such as `chrono`, can't be
+/// told apart otherwise.
+fn is_std_duration(ty: &Type) -> bool {
+    let path = match *ty {
+        Type::Path(ref p) if p.qself.is_none() => &p.path,
+        _ => return false,
+    };
+    let segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<String>>();
+    let segments = segments.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    matches!(
+        segments[..],
+        ["Duration"] | ["std" | "core", "time", "Duration"]
+    )
+}
+
+/// Get the mapped columns of a struct, in field declaration order.
+pub fn column_specs(s: &DataStruct) -> Vec<ColumnSpec<'_>> {
+    s.fields
+        .iter()
+        .filter(|field| !parse_field_attrs(field).capture_rest)
+        .map(ColumnSpec::new)
+        .collect()
+}
+
+/// Get the `#[pg_mapper(capture_rest)]` field, which isn't a column of its
+/// own but collects the columns without a field.
+pub fn capture_rest_field(s: &DataStruct) -> Option<&Ident> {
+    let mut fields = s
+        .fields
+        .iter()
+        .filter(|field| parse_field_attrs(field).capture_rest);
+    let field = fields.next()?;
+
+    if fields.next().is_some() {
+        panic!("only one field can be annotated with capture_rest");
+    }
+
+    field.ident.as_ref()
+}
@@ -5,125 +5,1581 @@ extern crate syn;
 
 use proc_macro::TokenStream;
 
+mod column;
+mod schema;
+
+use column::{capture_rest_field, column_specs, ColumnSpec, Epoch, NullDefault, Trim};
+
 use syn::{
-    Data, DataStruct, DeriveInput, Ident, ImplGenerics, Item,
-    Meta::{List, NameValue},
+    Data, DataStruct, DeriveInput, GenericArgument, Ident, ImplGenerics, Item, Lifetime,
+    Meta::{List, NameValue, Path},
     NestedMeta::Meta,
-    TypeGenerics, WhereClause,
+    PathArguments, Type, TypeGenerics, WhereClause,
 };
 
 #[proc_macro_derive(PostgresMapper, attributes(pg_mapper))]
 pub fn postgres_mapper(input: TokenStream) -> TokenStream {
     let mut ast: DeriveInput = syn::parse(input).expect("Couldn't parse item");
 
-    impl_derive(&mut ast)
+    impl_derive(&mut ast)
+}
+
+#[proc_macro_derive(PostgresPatch, attributes(pg_patch, pg_mapper))]
+pub fn postgres_patch(input: TokenStream) -> TokenStream {
+    let mut ast: DeriveInput = syn::parse(input).expect("Couldn't parse item");
+
+    impl_patch_derive(&mut ast)
+}
+
+#[proc_macro_derive(PostgresFilter, attributes(pg_filter))]
+pub fn postgres_filter(input: TokenStream) -> TokenStream {
+    let mut ast: DeriveInput = syn::parse(input).expect("Couldn't parse item");
+
+    impl_filter_derive(&mut ast)
+}
+
+fn impl_derive(ast: &mut DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let attrs = parse_container_attrs(ast);
+
+    // The container forms annotate every `Option<String>` field, and every
+    // `String` field for `trim`.
+    if let Data::Struct(ref mut s) = ast.data {
+        for field in s.fields.iter_mut() {
+            let optional = option_inner_type(&field.ty).is_some_and(is_string);
+
+            if attrs.empty_as_none && optional {
+                let attr = syn::parse_quote!(#[pg_mapper(empty_as_none)]);
+                field.attrs.push(attr);
+            }
+
+            let trim = match attrs.trim {
+                Some(Trim::End) => "end",
+                Some(Trim::Both) => "both",
+                None => continue,
+            };
+            if optional || is_string(&field.ty) {
+                let attr = syn::parse_quote!(#[pg_mapper(trim = #trim)]);
+                field.attrs.push(attr);
+            }
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = &ast.generics.split_for_impl();
+
+    let s = match ast.data {
+        Data::Struct(ref s) => s,
+        _ => panic!("Enums or Unions can not be mapped"),
+    };
+
+    if attrs.borrowed {
+        let lifetime = ast
+            .generics
+            .lifetimes()
+            .next()
+            .map(|def| &def.lifetime)
+            .expect("borrowed mappings require a lifetime parameter: struct Foo<'a>");
+
+        let tokio_pg_mapper_borrowed = impl_tokio_pg_mapper_borrowed(
+            s,
+            name,
+            lifetime,
+            impl_generics,
+            ty_generics,
+            where_clause,
+        );
+
+        let tokens = quote! {
+            #tokio_pg_mapper_borrowed
+        };
+
+        return tokens.into();
+    }
+
+    let composite = attrs.composite.as_ref().map(|composite_name| {
+        if capture_rest_field(s).is_some() {
+            panic!("capture_rest isn't supported on composite types");
+        }
+
+        let from_sql = impl_composite_from_sql(s, name, composite_name, &ast.generics);
+        let to_sql = impl_composite_to_sql(
+            s,
+            name,
+            composite_name,
+            impl_generics,
+            ty_generics,
+            where_clause,
+        );
+
+        quote! {
+            #from_sql
+
+            #to_sql
+        }
+    });
+
+    let pivot = attrs.pivot.as_ref().map(|pivot| {
+        impl_pivot(s, name, pivot, impl_generics, ty_generics, where_clause)
+    });
+
+    // Composite and pivoted types don't have to be tables too.
+    if attrs.table_name.is_none() && (composite.is_some() || pivot.is_some()) {
+        let tokens = quote! {
+            #composite
+
+            #pivot
+        };
+
+        return tokens.into();
+    }
+
+    let table_name = attrs
+        .table_name
+        .expect("declare table name: #[pg_mapper(table = \"foo\")]");
+
+    let schema_errors = match attrs.schema_file {
+        Some(ref schema_file) => schema::validate(s, name, &table_name, schema_file),
+        None => Vec::new(),
+    };
+    // Without `PG_MAPPER_DATABASE_URL`, the verification finds no errors.
+    #[cfg(feature = "verify-schema")]
+    let schema_errors = schema_errors
+        .into_iter()
+        .chain(schema::verify(s, name, &table_name))
+        .collect::<Vec<_>>();
+    let schema_errors = schema_errors.into_iter().map(|err| err.to_compile_error());
+
+    let tokio_pg_mapper = impl_tokio_pg_mapper(
+        s,
+        name,
+        &table_name,
+        impl_generics,
+        ty_generics,
+        where_clause,
+    );
+
+    let update_from_row =
+        impl_update_from_row(s, name, impl_generics, ty_generics, where_clause);
+
+    let diff = if attrs.diff {
+        Some(impl_diff(s, name, impl_generics, ty_generics, where_clause))
+    } else {
+        None
+    };
+
+    let primary_key =
+        impl_primary_key(s, name, impl_generics, ty_generics, where_clause);
+
+    let simple_query = if attrs.simple_query {
+        Some(impl_simple_query(s, name, impl_generics, ty_generics, where_clause))
+    } else {
+        None
+    };
+
+    // The derived `TokioPostgresCrud` inserts with `TokioPostgresInsert`.
+    let insert = if attrs.insert || attrs.crud {
+        Some(impl_insert(s, name, impl_generics, ty_generics, where_clause))
+    } else {
+        None
+    };
+
+    let insert_params = if attrs.insert || attrs.crud {
+        impl_insert_params(s, name, &ast.vis, &ast.generics)
+    } else {
+        Vec::new()
+    };
+
+    let notification = if attrs.notification {
+        Some(impl_notification(s, name, impl_generics, ty_generics, where_clause))
+    } else {
+        None
+    };
+
+    let query = if attrs.query {
+        impl_query(s, name, &ast.vis, impl_generics, ty_generics, where_clause)
+    } else {
+        Vec::new()
+    };
+
+    let crud = if attrs.crud {
+        Some(impl_crud(s, name, impl_generics, ty_generics, where_clause))
+    } else {
+        None
+    };
+
+    let finders = if attrs.crud {
+        impl_finders(s, name, impl_generics, ty_generics, where_clause)
+    } else {
+        None
+    };
+
+    let copy = if attrs.copy {
+        Some(impl_copy(s, name, impl_generics, ty_generics, where_clause))
+    } else {
+        None
+    };
+
+    let tokens = quote! {
+        #tokio_pg_mapper
+
+        #update_from_row
+
+        #diff
+
+        #primary_key
+
+        #crud
+
+        #finders
+
+        #insert
+
+        #(#insert_params)*
+
+        #simple_query
+
+        #copy
+
+        #notification
+
+        #(#query)*
+
+        #composite
+
+        #pivot
+
+        #(#schema_errors)*
+    };
+
+    tokens.into()
+}
+
+fn impl_patch_derive(ast: &mut DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let attrs = parse_patch_attrs(ast);
+
+    let (impl_generics, ty_generics, where_clause) = &ast.generics.split_for_impl();
+
+    let s = match ast.data {
+        Data::Struct(ref s) => s,
+        _ => panic!("Enums or Unions can not be used as patches"),
+    };
+
+    let assignments = s.fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+
+        if option_inner_type(&field.ty).is_none() {
+            panic!("pg_patch fields must be of type Option<T>: {}", ident);
+        }
+
+        let column = ColumnSpec::new(field).name;
+        quote! {
+            if let Some(ref value) = self.#ident {
+                assignments.push(format!("{} = ${}", tokio_pg_mapper::quote_identifier(#column), first_placeholder + params.len()));
+                params.push(value);
+            }
+        }
+    });
+
+    let sql_table = match (&attrs.table_name, &attrs.parent) {
+        (Some(table_name), _) => quote! {
+            tokio_pg_mapper::quote_table(#table_name)
+        },
+        (None, Some(parent)) => quote! {
+            <#parent as tokio_pg_mapper::FromTokioPostgresRow>::sql_table()
+        },
+        (None, None) => {
+            panic!("declare patched type or table name: #[pg_patch(for = \"Foo\", table = \"foo\")]")
+        }
+    };
+
+    // Referencing every patch field on the patched type turns a field missing
+    // from it into a compile error.
+    let parent_check = attrs.parent.as_ref().map(|parent| {
+        let field_refs = s.fields.iter().map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            quote! {
+                let _ = &parent.#ident;
+            }
+        });
+
+        quote! {
+            const _: () = {
+                #[allow(dead_code)]
+                fn check_patched_fields(parent: &#parent) {
+                    #(#field_refs)*
+                }
+            };
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::TokioPostgresPatch for #name #ty_generics #where_clause {
+            fn set_clause(&self, first_placeholder: usize) -> (String, Vec<&(dyn tokio_postgres::types::ToSql + Sync)>) {
+                let mut assignments: Vec<String> = Vec::new();
+                let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
+
+                #(#assignments)*
+
+                (assignments.join(", "), params)
+            }
+
+            fn sql_table() -> String {
+                #sql_table
+            }
+        }
+
+        #parent_check
+    };
+
+    tokens.into()
+}
+
+fn impl_filter_derive(ast: &mut DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+
+    let (impl_generics, ty_generics, where_clause) = &ast.generics.split_for_impl();
+
+    let s = match ast.data {
+        Data::Struct(ref s) => s,
+        _ => panic!("Enums or Unions can not be used as filters"),
+    };
+
+    let conditions = s.fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let attrs = parse_filter_field_attrs(field);
+
+        if option_inner_type(&field.ty).is_none() {
+            panic!("pg_filter fields must be of type Option<T>: {}", ident);
+        }
+
+        let column = attrs.column.unwrap_or_else(|| ident.to_string());
+        let op = attrs.op.unwrap_or("=");
+        quote! {
+            if let Some(ref value) = self.#ident {
+                conditions.push(format!("{} {} ${}", tokio_pg_mapper::quote_identifier(#column), #op, first_placeholder + params.len()));
+                params.push(value);
+            }
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::TokioPostgresFilter for #name #ty_generics #where_clause {
+            fn where_clause(&self, first_placeholder: usize) -> (String, Vec<&(dyn tokio_postgres::types::ToSql + Sync)>) {
+                let mut conditions: Vec<String> = Vec::new();
+                let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
+
+                #(#conditions)*
+
+                if conditions.is_empty() {
+                    return (String::new(), params);
+                }
+
+                (format!(" WHERE {} ", conditions.join(" AND ")), params)
+            }
+        }
+    };
+
+    tokens.into()
+}
+
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(ref p) if p.qself.is_none() => &p.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => {
+            match args.args.first() {
+                Some(GenericArgument::Type(ref inner)) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn impl_tokio_pg_mapper(
+    s: &DataStruct,
+    name: &Ident,
+    table_name: &str,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let type_name = name.to_string();
+    let columns = column_specs(s);
+
+    let fields = columns.iter().map(|column| {
+        let ident = column.ident;
+        let ty = column.fetched_type();
+        let name = &column.name;
+        let field = column.field_name();
+        let fetched = syn::parse_quote! {
+            tokio_pg_mapper::try_get_column::<#ty>(row, #name, #field, #type_name)?
+        };
+        let value = column.field_value(fetched, &type_name);
+
+        quote! {
+            #ident: #value
+        }
+    });
+
+    let indexed_fields = columns.iter().enumerate().map(|(idx, column)| {
+        let ident = column.ident;
+        let ty = column.fetched_type();
+        let name = &column.name;
+        let field = column.field_name();
+        let fetched = syn::parse_quote! {
+            tokio_pg_mapper::try_get_column_at::<#ty>(row, indices[#idx], #name, #field, #type_name)?
+        };
+        let value = column.field_value(fetched, &type_name);
+
+        quote! {
+            #ident: #value
+        }
+    });
+
+    let rest = captured_rest(s, &columns, &type_name);
+    let indexed_rest = rest.clone();
+
+    // Columns collected by a `capture_rest` field are expected, so they
+    // aren't traced as unmapped.
+    let trace_unmapped = |row: syn::Expr| {
+        rest.is_none().then(|| {
+            quote! {
+                tokio_pg_mapper::trace_unmapped_columns(#row, Self::columns(), #type_name);
+            }
+        })
+    };
+    let trace_row = trace_unmapped(syn::parse_quote!(row));
+    let trace_rows = trace_unmapped(syn::parse_quote!(&rows[0]));
+
+    let positions = 0..columns.len();
+
+    let column_names = columns.iter().map(|column| &column.name);
+
+    let select_columns = column_names.clone();
+    let soft_delete = optional_str(soft_delete_column(&columns));
+    let select = quote! {
+        /// The `SELECT` statement of every mapped column, qualified by the
+        /// table name, leaving out soft deleted rows.
+        pub const SELECT: &'static str = {
+            // The statement is built by the runtime crate in a constant
+            // expression, so that it's quoted like every other generated
+            // statement.
+            const COLUMNS: &[&str] = &[#(#select_columns),*];
+            const SOFT_DELETE: Option<&str> = #soft_delete;
+            const LEN: usize = tokio_pg_mapper::select_statement::<0>(#table_name, COLUMNS, SOFT_DELETE).len();
+            const SQL: &tokio_pg_mapper::SqlBuffer<LEN> = &tokio_pg_mapper::select_statement(#table_name, COLUMNS, SOFT_DELETE);
+
+            SQL.as_str()
+        };
+    };
+
+    let column_metadata = columns.iter().map(|column| {
+        let name = &column.name;
+        let ty = column.ty;
+        let fetched = column.fetched_type();
+        let nullable = column.nullable();
+        let primary_key = column.primary_key;
+
+        // A `pg_type` hint overrides the type inferred from the field type,
+        // which epoch timestamps are stored as integers of.
+        let pg_type = column.pg_type.clone().or_else(|| match column.epoch {
+            Some(_) => Some("BIGINT".to_string()),
+            None => schema::infer_pg_type(ty),
+        });
+        let pg_type = optional_str(pg_type);
+
+        quote! {
+            tokio_pg_mapper::ColumnMetadata {
+                name: #name,
+                rust_type: Some(::std::any::type_name::<#ty>()),
+                nullable: #nullable,
+                accepts: Some(<#fetched as tokio_postgres::types::FromSql>::accepts),
+                pg_type: #pg_type,
+                primary_key: #primary_key,
+            }
+        }
+    });
+
+    let column_types = columns.iter().map(|column| {
+        let name = &column.name;
+        let hint = optional_str(column.pg_type.clone());
+
+        quote! {
+            (#name, #hint)
+        }
+    });
+
+    // The field mappings are only expanded once for lookups by name and once
+    // for lookups by position, in hidden inherent functions which every
+    // conversion delegates to, to keep the expansion of wide structs small.
+    let tokens = quote! {
+        const _: () = {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #select
+
+                #[doc(hidden)]
+                fn __tokio_pg_mapper_map(row: &tokio_postgres::row::Row) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                    Ok(Self {
+                        #(#fields,)*
+                        #rest
+                    })
+                }
+
+                #[doc(hidden)]
+                fn __tokio_pg_mapper_map_at(row: &tokio_postgres::row::Row, indices: &[usize]) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                    Ok(Self {
+                        #(#indexed_fields,)*
+                        #indexed_rest
+                    })
+                }
+            }
+
+            impl #impl_generics tokio_pg_mapper::FromTokioPostgresRow for #name #ty_generics #where_clause {
+                fn from_row(row: tokio_postgres::row::Row) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                    Self::from_row_ref(&row)
+                }
+
+                fn from_row_ref(row: &tokio_postgres::row::Row) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                    tokio_pg_mapper::trace_mapping(#type_name, 1, || {
+                        #trace_row
+
+                        Self::__tokio_pg_mapper_map(row)
+                    })
+                }
+
+                fn from_row_indexed(row: &tokio_postgres::row::Row) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                    tokio_pg_mapper::debug_assert_column_order(row, Self::columns(), #type_name);
+
+                    tokio_pg_mapper::trace_mapping(#type_name, 1, || {
+                        Self::__tokio_pg_mapper_map_at(row, &[#(#positions),*])
+                    })
+                }
+
+                fn from_row_with_config(row: &tokio_postgres::row::Row, config: &tokio_pg_mapper::MapperConfig) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                    if config.is_default() {
+                        return Self::from_row_ref(row);
+                    }
+
+                    tokio_pg_mapper::trace_mapping(#type_name, 1, || {
+                        let indices = tokio_pg_mapper::resolve_configured_column_indices(row, Self::columns(), config);
+
+                        Self::__tokio_pg_mapper_map_at(row, &indices)
+                    })
+                }
+
+                fn from_rows(rows: Vec<tokio_postgres::row::Row>) -> ::std::result::Result<Vec<Self>, tokio_pg_mapper::Error> {
+                    Self::from_row_refs(&rows)
+                }
+
+                fn from_row_refs(rows: &[tokio_postgres::row::Row]) -> ::std::result::Result<Vec<Self>, tokio_pg_mapper::Error> {
+                    let first = match rows.first() {
+                        Some(first) => first,
+                        None => return Ok(Vec::new()),
+                    };
+
+                    // The whole batch is mapped in a single span, rather than
+                    // one per row.
+                    tokio_pg_mapper::trace_mapping(#type_name, rows.len(), || {
+                        // Every row of a statement has the same columns, so look
+                        // them up by name once.
+                        let indices = match tokio_pg_mapper::resolve_column_indices(first, Self::columns()) {
+                            Some(indices) => indices,
+                            None => return rows.iter().map(Self::__tokio_pg_mapper_map).collect(),
+                        };
+                        #trace_rows
+
+                        let mut values = Vec::with_capacity(rows.len());
+                        for (idx, row) in rows.iter().enumerate() {
+                            match Self::__tokio_pg_mapper_map_at(row, &indices) {
+                                Ok(value) => values.push(value),
+                                Err(err) => {
+                                    tokio_pg_mapper::trace_failed_row(idx);
+
+                                    return Err(err);
+                                }
+                            }
+                        }
+
+                        Ok(values)
+                    })
+                }
+
+                fn sql_table() -> String {
+                    tokio_pg_mapper::quote_table(#table_name)
+                }
+
+                fn columns() -> &'static [&'static str] {
+                    &[#(#column_names),*]
+                }
+
+                fn column_metadata() -> Vec<tokio_pg_mapper::ColumnMetadata> {
+                    vec![#(#column_metadata),*]
+                }
+
+                fn column_types() -> &'static [(&'static str, Option<&'static str>)] {
+                    &[#(#column_types),*]
+                }
+            }
+        };
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_update_from_row(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let type_name = name.to_string();
+
+    let specs = column_specs(s);
+
+    let staged_fields = specs.iter().map(|column| {
+        let ident = column.ident;
+        let ty = column.fetched_type();
+        let staged = format_ident!("__pg_mapper_{}", ident);
+        let name = &column.name;
+        let field = column.field_name();
+        let fetched = syn::parse_quote! {
+            tokio_pg_mapper::try_get_column::<#ty>(row, #name, #field, #type_name)?
+        };
+        let value = column.field_value(fetched, &type_name);
+
+        quote! {
+            let #staged = if columns.iter().any(|column| column.name() == #name) {
+                Some(#value)
+            } else {
+                tokio_pg_mapper::trace_missing_column(#name, #type_name);
+                None
+            };
+        }
+    });
+
+    let assigned_fields = specs.iter().map(|column| {
+        let ident = column.ident;
+        let staged = format_ident!("__pg_mapper_{}", ident);
+        let field = column.field_name();
+
+        quote! {
+            if let Some(value) = #staged {
+                self.#ident = value;
+                updated.push(#field);
+            }
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::UpdateFromTokioPostgresRow for #name #ty_generics #where_clause {
+            fn update_from_row(&mut self, row: &tokio_postgres::row::Row) -> ::std::result::Result<tokio_pg_mapper::UpdatedFields, tokio_pg_mapper::Error> {
+                let columns = row.columns();
+
+                #(#staged_fields)*
+
+                let mut updated = tokio_pg_mapper::UpdatedFields::new();
+
+                #(#assigned_fields)*
+
+                Ok(updated)
+            }
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_diff(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    // Primary keys and readonly columns are never part of an `UPDATE`.
+    let specs = column_specs(s);
+    let updatable = specs
+        .iter()
+        .filter(|column| !column.primary_key && !column.readonly);
+
+    let compared_fields = updatable.clone().map(|column| {
+        let ident = column.ident;
+        let name = &column.name;
+
+        quote! {
+            if self.#ident != other.#ident {
+                changed.push(#name);
+            }
+        }
+    });
+
+    let param_fields = updatable.map(|column| {
+        let param = column.param();
+        let name = &column.name;
+
+        quote! {
+            if changed.contains(#name) {
+                params.push(#param);
+            }
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::TokioPostgresDiff for #name #ty_generics #where_clause {
+            fn diff(&self, other: &Self) -> tokio_pg_mapper::ChangedColumns {
+                let mut changed = Vec::new();
+
+                #(#compared_fields)*
+
+                tokio_pg_mapper::ChangedColumns::new(changed)
+            }
+
+            fn changed_params(&self, changed: &tokio_pg_mapper::ChangedColumns) -> Vec<&(dyn tokio_postgres::types::ToSql + Sync)> {
+                let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
+
+                #(#param_fields)*
+
+                params
+            }
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_insert(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    // Readonly columns, such as generated ones, are never part of an `INSERT`.
+    let specs = column_specs(s);
+    let inserted = specs.iter().filter(|column| !column.readonly);
+
+    let insert_columns = inserted.clone().map(|column| &column.name);
+
+    let param_fields = inserted.map(|column| {
+        let param = column.param();
+        quote! {
+            #param as &(dyn tokio_postgres::types::ToSql + Sync)
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::TokioPostgresInsert for #name #ty_generics #where_clause {
+            fn insert_columns() -> &'static [&'static str] {
+                &[#(#insert_columns),*]
+            }
+
+            fn to_params(&self) -> Vec<&(dyn tokio_postgres::types::ToSql + Sync)> {
+                vec![#(#param_fields),*]
+            }
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_insert_params(
+    s: &DataStruct,
+    name: &Ident,
+    vis: &syn::Visibility,
+    generics: &syn::Generics,
+) -> Vec<Item> {
+    let alias = format_ident!("{}InsertParams", name);
+    let doc = format!(
+        "The parameters of an `INSERT` of [`{}`], in the order of its insert columns.",
+        name
+    );
+
+    // Readonly columns are excluded, like from the `TokioPostgresInsert`
+    // parameters.
+    let lifetime: syn::Lifetime = syn::parse_quote!('__pg);
+    let (types, values): (Vec<_>, Vec<_>) = column_specs(s)
+        .iter()
+        .filter(|column| !column.readonly)
+        .map(|column| column.typed_param(&lifetime))
+        .unzip();
+
+    // Without columns to insert, there are no parameters to pass.
+    if types.is_empty() {
+        return Vec::new();
+    }
+
+    // The alias takes the parameters of the type without their bounds, which
+    // type aliases don't enforce.
+    let params = generics.params.iter().map(|param| match *param {
+        syn::GenericParam::Lifetime(ref def) => {
+            let lifetime = &def.lifetime;
+            quote!(#lifetime)
+        }
+        syn::GenericParam::Type(ref def) => {
+            let ident = &def.ident;
+            quote!(#ident)
+        }
+        syn::GenericParam::Const(ref def) => {
+            let ident = &def.ident;
+            let ty = &def.ty;
+            quote!(const #ident: #ty)
+        }
+    });
+    let args = generics.params.iter().map(|param| match *param {
+        syn::GenericParam::Lifetime(ref def) => {
+            let lifetime = &def.lifetime;
+            quote!(#lifetime)
+        }
+        syn::GenericParam::Type(syn::TypeParam { ref ident, .. })
+        | syn::GenericParam::Const(syn::ConstParam { ref ident, .. }) => quote!(#ident),
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let params_alias = quote! {
+        #[doc = #doc]
+        #vis type #alias<#lifetime, #(#params),*> = (#(#types,)*);
+    };
+
+    let params_impl = quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Get the parameters of an `INSERT` of the value as a tuple of
+            /// typed references, in the order of its insert columns.
+            pub fn to_param_tuple(&self) -> #alias<'_, #(#args),*> {
+                (#(#values,)*)
+            }
+        }
+    };
+
+    vec![
+        syn::parse_quote!(#params_alias),
+        syn::parse_quote!(#params_impl),
+    ]
+}
+
+fn impl_primary_key(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Option<Item> {
+    let specs = column_specs(s);
+    let primary_key_columns = specs
+        .iter()
+        .filter(|column| column.primary_key)
+        .map(|column| &column.name)
+        .collect::<Vec<_>>();
+
+    if primary_key_columns.is_empty() {
+        return None;
+    }
+
+    let soft_delete_column = optional_str(soft_delete_column(&specs));
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::TokioPostgresPrimaryKey for #name #ty_generics #where_clause {
+            fn primary_key_columns() -> &'static [&'static str] {
+                &[#(#primary_key_columns),*]
+            }
+
+            fn soft_delete_column() -> Option<&'static str> {
+                #soft_delete_column
+            }
+        }
+    };
+
+    Some(syn::parse_quote!(#tokens))
+}
+
+/// Get the name of the `soft_delete` column, if any.
+fn soft_delete_column(specs: &[ColumnSpec]) -> Option<String> {
+    let mut columns = specs.iter().filter(|column| column.soft_delete);
+    let column = columns.next().map(|column| column.name.clone());
+
+    if columns.next().is_some() {
+        panic!("mappings can have a single #[pg_mapper(soft_delete)] field");
+    }
+
+    column
+}
+
+fn impl_crud(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let specs = column_specs(s);
+    let (primary_key, others): (Vec<_>, Vec<_>) =
+        specs.iter().partition(|column| column.primary_key);
+
+    if primary_key.is_empty() {
+        panic!("crud mappings require a #[pg_mapper(primary_key)] field");
+    }
+
+    let key_fields = primary_key.iter().map(|column| {
+        let ident = column.ident;
+        quote!(::std::clone::Clone::clone(&self.#ident))
+    });
+
+    // Composite keys are given as a tuple.
+    let (key_type, key_params, key_value) = if primary_key.len() == 1 {
+        let ty = primary_key[0].ty;
+        (
+            quote!(#ty),
+            vec![quote!(key as &(dyn tokio_postgres::types::ToSql + Sync))],
+            quote!(#(#key_fields)*),
+        )
+    } else {
+        let types = primary_key.iter().map(|column| column.ty);
+        let params = (0..primary_key.len())
+            .map(|idx| {
+                let idx = syn::Index::from(idx);
+                quote!(&key.#idx as &(dyn tokio_postgres::types::ToSql + Sync))
+            })
+            .collect();
+        (quote!((#(#types),*)), params, quote!((#(#key_fields),*)))
+    };
+
+    let versions = others
+        .iter()
+        .filter(|column| column.version)
+        .copied()
+        .collect::<Vec<_>>();
+    if versions.len() > 1 {
+        panic!("crud mappings can have a single #[pg_mapper(version)] field");
+    }
+    let version_column =
+        optional_str(versions.first().map(|column| column.name.clone()));
+
+    // Readonly columns are never assigned, the key only identifies the
+    // updated row, and the version is compared after it.
+    let updated = others
+        .iter()
+        .filter(|column| !column.readonly && !column.version);
+    let update_columns = updated.clone().map(|column| &column.name);
+    let update_params = updated
+        .chain(primary_key.iter())
+        .chain(versions.iter())
+        .map(|column| {
+            let param = column.param();
+            quote! {
+                #param as &(dyn tokio_postgres::types::ToSql + Sync)
+            }
+        });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::TokioPostgresCrud for #name #ty_generics #where_clause {
+            type PrimaryKey = #key_type;
+
+            fn primary_key_params(key: &Self::PrimaryKey) -> Vec<&(dyn tokio_postgres::types::ToSql + Sync)> {
+                vec![#(#key_params),*]
+            }
+
+            fn primary_key(&self) -> Self::PrimaryKey {
+                #key_value
+            }
+
+            fn update_columns() -> &'static [&'static str] {
+                &[#(#update_columns),*]
+            }
+
+            fn update_params(&self) -> Vec<&(dyn tokio_postgres::types::ToSql + Sync)> {
+                vec![#(#update_params),*]
+            }
+
+            fn version_column() -> Option<&'static str> {
+                #version_column
+            }
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_finders(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Option<Item> {
+    let specs = column_specs(s);
+    let unique = specs
+        .iter()
+        .filter(|column| column.unique)
+        .collect::<Vec<_>>();
+
+    if unique.is_empty() {
+        return None;
+    }
+
+    let finders = unique.into_iter().map(|column| {
+        let ident = column.ident;
+        let field_name = column.field_name();
+        let finder = format_ident!("find_by_{}", field_name.trim_start_matches("r#"));
+        let doc = format!(
+            "Fetches the row whose `{}` column equals the given value.",
+            column.name
+        );
+        let column_name = &column.name;
+
+        // `String` columns are looked up by `&str`.
+        let ty = column.ty;
+        let ty = if is_string(ty) { quote!(str) } else { quote!(#ty) };
+
+        quote! {
+            #[doc = #doc]
+            pub async fn #finder<C>(client: &C, #ident: &#ty) -> ::std::result::Result<Option<Self>, tokio_pg_mapper::Error>
+            where
+                C: tokio_postgres::GenericClient + Sync,
+            {
+                let condition = format!("{} = $1", tokio_pg_mapper::quote_identifier(#column_name));
+                let statement = format!(
+                    "SELECT {} FROM {}{}",
+                    <Self as tokio_pg_mapper::FromTokioPostgresRow>::sql_fields(),
+                    <Self as tokio_pg_mapper::FromTokioPostgresRow>::sql_table(),
+                    tokio_pg_mapper::where_not_deleted::<Self>(Some(condition)),
+                );
+
+                tokio_pg_mapper::ClientExt::query_opt_as(client, statement.as_str(), &[&#ident]).await
+            }
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#finders)*
+        }
+    };
+
+    Some(syn::parse_quote!(#tokens))
+}
+
+fn parse_trim(value: &str) -> Trim {
+    match value {
+        "end" => Trim::End,
+        "both" => Trim::Both,
+        _ => panic!("expected trim to be \"end\" or \"both\""),
+    }
+}
+
+fn parse_pivot(list: &syn::MetaList) -> Pivot {
+    let mut key = None;
+    let mut value = None;
+
+    for nested in list.nested.iter() {
+        match nested {
+            Meta(NameValue(ref m)) if m.path.is_ident("key") => {
+                if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                    key = Some(s.value());
+                }
+            }
+            Meta(NameValue(ref m)) if m.path.is_ident("value") => {
+                if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                    value = Some(s.value());
+                }
+            }
+            _ => panic!("expected pivot(key = \"key\", value = \"value\")"),
+        }
+    }
+
+    Pivot {
+        key: key.expect("declare the pivot key column: pivot(key = \"key\", ...)"),
+        value: value
+            .expect("declare the pivot value column: pivot(value = \"value\", ...)"),
+    }
+}
+
+fn parse_epoch(value: &str) -> Epoch {
+    match value {
+        "seconds" => Epoch::Seconds,
+        "millis" => Epoch::Millis,
+        "micros" => Epoch::Micros,
+        _ => panic!("expected epoch to be \"seconds\", \"millis\" or \"micros\""),
+    }
+}
+
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ref path) => path.qself.is_none() && path.path.is_ident("String"),
+        _ => false,
+    }
+}
+
+fn impl_simple_query(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let type_name = name.to_string();
+
+    let fields = column_specs(s).into_iter().map(|column| {
+        let ident = column.ident;
+        let ty = column.fetched_type();
+        let name = &column.name;
+        let field = column.field_name();
+
+        // Blank texts are parsed as NULL for any `empty_as_none` field type.
+        let get = if column.empty_as_none {
+            quote!(tokio_pg_mapper::try_get_text_column_empty_as_null)
+        } else {
+            quote!(tokio_pg_mapper::try_get_text_column)
+        };
+        let fetched = syn::parse_quote! {
+            #get::<#ty>(row, #name, #field, #type_name)?
+        };
+        let value = column.field_value(fetched, &type_name);
+
+        quote! {
+            #ident: #value
+        }
+    });
+
+    let rest = uncaptured_rest(s);
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::FromSimpleQueryRow for #name #ty_generics #where_clause {
+            fn from_simple_query_row(row: &tokio_postgres::SimpleQueryRow) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                Ok(Self {
+                    #(#fields,)*
+                    #rest
+                })
+            }
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_query(
+    s: &DataStruct,
+    name: &Ident,
+    vis: &syn::Visibility,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Vec<Item> {
+    let enum_name = format_ident!("{}Column", name);
+    let doc = format!("The mapped columns of [`{}`].", name);
+
+    let columns = column_specs(s);
+    let variants = columns.iter().map(|column| {
+        let variant = column.variant();
+        let doc = format!("The `{}` column.", column.name);
+
+        quote! {
+            #[doc = #doc]
+            #variant
+        }
+    });
+    let names = columns.iter().map(|column| {
+        let variant = column.variant();
+        let name = &column.name;
+
+        quote! {
+            #enum_name::#variant => #name
+        }
+    });
+
+    let column_enum = quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        #vis enum #enum_name {
+            #(#variants),*
+        }
+    };
+
+    let column_impl = quote! {
+        impl tokio_pg_mapper::query::Column for #enum_name {
+            fn name(self) -> &'static str {
+                match self {
+                    #(#names),*
+                }
+            }
+        }
+    };
+
+    let query_impl = quote! {
+        impl #impl_generics tokio_pg_mapper::query::TokioPostgresQuery for #name #ty_generics #where_clause {
+            type Column = #enum_name;
+        }
+    };
+
+    vec![
+        syn::parse_quote!(#column_enum),
+        syn::parse_quote!(#column_impl),
+        syn::parse_quote!(#query_impl),
+    ]
+}
+
+fn impl_copy(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let type_name = name.to_string();
+
+    let fields = column_specs(s).into_iter().enumerate().map(|(idx, column)| {
+        let ident = column.ident;
+        let ty = column.fetched_type();
+        let name = &column.name;
+        let field = column.field_name();
+        let fetched = syn::parse_quote! {
+            tokio_pg_mapper::try_get_copy_column::<#ty>(row, #idx, #name, #field, #type_name)?
+        };
+        let value = column.field_value(fetched, &type_name);
+
+        quote! {
+            #ident: #value
+        }
+    });
+
+    let rest = uncaptured_rest(s);
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::FromBinaryCopyOutRow for #name #ty_generics #where_clause {
+            fn from_binary_copy_out_row(row: &tokio_postgres::binary_copy::BinaryCopyOutRow) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                Ok(Self {
+                    #(#fields,)*
+                    #rest
+                })
+            }
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_notification(
+    s: &DataStruct,
+    name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let type_name = name.to_string();
+
+    let fields = column_specs(s).into_iter().map(|column| {
+        let ident = column.ident;
+        let ty = column.fetched_type();
+        let name = &column.name;
+        let field = column.field_name();
+        let fetched = syn::parse_quote! {
+            tokio_pg_mapper::try_get_json_field::<#ty>(&object, #name, #field, #type_name)?
+        };
+        let value = column.field_value(fetched, &type_name);
+
+        quote! {
+            #ident: #value
+        }
+    });
+
+    let rest = uncaptured_rest(s);
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::FromNotificationPayload for #name #ty_generics #where_clause {
+            fn from_notification_payload(payload: &str) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                let object = tokio_pg_mapper::parse_notification_payload(payload)
+                    .map_err(tokio_pg_mapper::Error::for_type::<Self>)?;
+
+                Ok(Self {
+                    #(#fields,)*
+                    #rest
+                })
+            }
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+/// Get the initializer of the `capture_rest` field, if any, of a mapping
+/// from a `row`, collecting the columns which aren't mapped to a field.
+fn captured_rest(
+    s: &DataStruct,
+    columns: &[ColumnSpec],
+    type_name: &str,
+) -> Option<syn::FieldValue> {
+    let ident = capture_rest_field(s)?;
+    let names = columns.iter().map(|column| &column.name);
+
+    Some(syn::parse_quote! {
+        #ident: tokio_pg_mapper::capture_rest(row, &[#(#names),*], #type_name)?
+    })
+}
+
+/// Get the initializer of the `capture_rest` field, if any, of a mapping
+/// without column names to capture, leaving it empty.
+fn uncaptured_rest(s: &DataStruct) -> Option<syn::FieldValue> {
+    let ident = capture_rest_field(s)?;
+
+    Some(syn::parse_quote! {
+        #ident: ::std::default::Default::default()
+    })
 }
 
-fn impl_derive(ast: &mut DeriveInput) -> TokenStream {
-    let name = &ast.ident;
-    let table_name = parse_table_attr(&ast);
+fn impl_pivot(
+    s: &DataStruct,
+    name: &Ident,
+    pivot: &Pivot,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let type_name = name.to_string();
+    let columns = column_specs(s);
 
-    let (impl_generics, ty_generics, where_clause) = &ast.generics.split_for_impl();
+    let fields = columns.iter().enumerate().map(|(idx, column)| {
+        let ident = column.ident;
+        let name = &column.name;
+        let field = column.field_name();
 
-    let s = match ast.data {
-        Data::Struct(ref s) => s,
-        _ => panic!("Enums or Unions can not be mapped"),
-    };
+        // Values are parsed into the type of the field, or into `T` for
+        // `Option<T>` fields.
+        let inner = option_inner_type(column.ty);
+        let ty = inner.unwrap_or(column.ty);
+        let parse = if column.json_text {
+            quote!(tokio_pg_mapper::parse_pivot_json::<#ty>)
+        } else {
+            quote!(tokio_pg_mapper::parse_pivot_text::<#ty>)
+        };
 
-    let tokio_pg_mapper = impl_tokio_pg_mapper(
-        s,
-        name,
-        &table_name,
-        impl_generics,
-        ty_generics,
-        where_clause,
+        let optional = quote! {
+            tokio_pg_mapper::pivot_optional_field(values[#idx], #parse, #name, #type_name)?
+        };
+        let value = match column.null_default {
+            _ if inner.is_some() => optional,
+            Some(NullDefault::Default) => quote!(#optional.unwrap_or_default()),
+            Some(NullDefault::Function(ref function)) => {
+                quote!(#optional.unwrap_or_else(#function))
+            }
+            None => quote! {
+                tokio_pg_mapper::pivot_field(values[#idx], #parse, #name, #field, #type_name)?
+            },
+        };
+
+        quote! {
+            #ident: #value
+        }
+    });
+
+    let keys = columns.iter().map(|column| &column.name);
+    let key_column = &pivot.key;
+    let value_column = &pivot.value;
+    let doc = format!(
+        "Pivots rows of `{}` and `{}` columns into a value, reading every field \
+         from the row whose key is its column name.",
+        key_column, value_column
     );
 
+    let rest = uncaptured_rest(s);
+
     let tokens = quote! {
-        #tokio_pg_mapper
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #doc]
+            pub fn from_kv_rows(rows: &[tokio_postgres::row::Row]) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                let values = tokio_pg_mapper::pivot_values(rows, #key_column, #value_column, &[#(#keys),*], #type_name)?;
+
+                Ok(Self {
+                    #(#fields,)*
+                    #rest
+                })
+            }
+        }
     };
 
-    tokens.into()
+    syn::parse_quote!(#tokens)
 }
 
-fn impl_tokio_pg_mapper(
+fn impl_composite_from_sql(
     s: &DataStruct,
     name: &Ident,
-    table_name: &str,
-    impl_generics: &ImplGenerics,
-    ty_generics: &TypeGenerics,
-    where_clause: &Option<&WhereClause>,
+    composite_name: &str,
+    generics: &syn::Generics,
 ) -> Item {
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let mut impl_generics = generics.clone();
+    impl_generics.params.insert(0, syn::parse_quote!('__pg));
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+
+    let len = s.fields.len();
+
     let fields = s.fields.iter().map(|field| {
         let ident = field.ident.as_ref().unwrap();
         let ty = &field.ty;
 
-        let row_expr = format!(r##"{}"##, ident);
         quote! {
-            #ident:row.try_get::<&str,#ty>(#row_expr)?
+            #ident: reader.read::<#ty>()?
         }
     });
 
-    let ref_fields = s.fields.iter().map(|field| {
-        let ident = field.ident.as_ref().unwrap();
+    let accepts = s.fields.iter().enumerate().map(|(idx, field)| {
         let ty = &field.ty;
+        let accepts_hint = composite_accepts_hint(field, idx).into_iter();
 
-        let row_expr = format!(r##"{}"##, ident);
         quote! {
-            #ident:row.try_get::<&str,#ty>(&#row_expr)?
+            && (#(#accepts_hint ||)* <#ty as tokio_postgres::types::FromSql<'__pg>>::accepts(fields[#idx].type_()))
         }
     });
 
-    let table_columns = s
-        .fields
-        .iter()
-        .map(|field| {
-            let ident = field
-                .ident
-                .as_ref()
-                .expect("Expected structfield identifier");
-            format!(" {0}.{1} ", table_name, ident)
-        })
-        .collect::<Vec<String>>()
-        .join(", ");
-
-    let columns = s
-        .fields
-        .iter()
-        .map(|field| {
-            let ident = field
-                .ident
-                .as_ref()
-                .expect("Expected structfield identifier");
-            format!(" {} ", ident)
-        })
-        .collect::<Vec<String>>()
-        .join(", ");
-
     let tokens = quote! {
-        impl #impl_generics tokio_pg_mapper::FromTokioPostgresRow for #name #ty_generics #where_clause {
-            fn from_row(row: tokio_postgres::row::Row) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
-                Ok(Self {
+        impl #impl_generics tokio_postgres::types::FromSql<'__pg> for #name #ty_generics #where_clause {
+            fn from_sql(
+                ty: &tokio_postgres::types::Type,
+                raw: &'__pg [u8],
+            ) -> ::std::result::Result<Self, Box<dyn ::std::error::Error + Sync + Send>> {
+                let mut reader = tokio_pg_mapper::CompositeReader::new(ty, raw)?;
+
+                let value = Self {
                     #(#fields),*
-                })
+                };
+                reader.finish()?;
+
+                Ok(value)
             }
 
-            fn from_row_ref(row: &tokio_postgres::row::Row) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
-                Ok(Self {
-                    #(#ref_fields),*
-                })
+            fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+                match tokio_pg_mapper::composite_fields(ty, #composite_name, #len) {
+                    Some(fields) => true #(#accepts)*,
+                    None => false,
+                }
             }
+        }
+    };
 
-            fn sql_table() -> String {
-                #table_name.to_string()
+    syn::parse_quote!(#tokens)
+}
+
+fn impl_composite_to_sql(
+    s: &DataStruct,
+    name: &Ident,
+    composite_name: &str,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let len = s.fields.len();
+
+    let fields = s.fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+
+        quote! {
+            writer.write(&self.#ident)?;
+        }
+    });
+
+    let accepts = s.fields.iter().enumerate().map(|(idx, field)| {
+        let ty = &field.ty;
+        let accepts_hint = composite_accepts_hint(field, idx).into_iter();
+
+        quote! {
+            && (#(#accepts_hint ||)* <#ty as tokio_postgres::types::ToSql>::accepts(fields[#idx].type_()))
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_postgres::types::ToSql for #name #ty_generics #where_clause {
+            fn to_sql(
+                &self,
+                ty: &tokio_postgres::types::Type,
+                out: &mut tokio_pg_mapper::bytes::BytesMut,
+            ) -> ::std::result::Result<tokio_postgres::types::IsNull, Box<dyn ::std::error::Error + Sync + Send>> {
+                let mut writer = tokio_pg_mapper::CompositeWriter::new(ty, out)?;
+                #(#fields)*
+                writer.finish()?;
+
+                Ok(tokio_postgres::types::IsNull::No)
             }
 
-            fn sql_table_fields() -> String {
-                #table_columns.to_string()
+            fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+                match tokio_pg_mapper::composite_fields(ty, #composite_name, #len) {
+                    Some(fields) => true #(#accepts)*,
+                    None => false,
+                }
             }
 
-            fn sql_fields() -> String {
-                #columns.to_string()
+            tokio_postgres::types::to_sql_checked!();
+        }
+    };
+
+    syn::parse_quote!(#tokens)
+}
+
+/// Get the check of a composite field's type against the `pg_type` hint of
+/// the mapped field, if any, which is consulted before the field type.
+fn composite_accepts_hint(field: &syn::Field, idx: usize) -> Option<syn::Expr> {
+    let hint = column::ColumnSpec::new(field).pg_type?;
+
+    Some(syn::parse_quote! {
+        fields[#idx].type_().name().eq_ignore_ascii_case(#hint)
+    })
+}
+
+fn impl_tokio_pg_mapper_borrowed(
+    s: &DataStruct,
+    name: &Ident,
+    lifetime: &Lifetime,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: &Option<&WhereClause>,
+) -> Item {
+    let type_name = name.to_string();
+
+    let columns = column_specs(s);
+
+    let fields = columns.iter().map(|column| {
+        let ident = column.ident;
+        let ty = column.fetched_type();
+        let name = &column.name;
+        let field = column.field_name();
+        let fetched = syn::parse_quote! {
+            tokio_pg_mapper::try_get_column::<#ty>(row, #name, #field, #type_name)?
+        };
+        let value = column.field_value(fetched, &type_name);
+
+        quote! {
+            #ident: #value
+        }
+    });
+
+    let column_names = columns.iter().map(|column| &column.name);
+
+    // Columns collected by a `capture_rest` field are expected, so they
+    // aren't traced as unmapped.
+    let rest = captured_rest(s, &columns, &type_name);
+    let trace_unmapped = rest.is_none().then(|| {
+        quote! {
+            tokio_pg_mapper::trace_unmapped_columns(row, &[#(#column_names),*], #type_name);
+        }
+    });
+
+    let tokens = quote! {
+        impl #impl_generics tokio_pg_mapper::FromTokioPostgresRowBorrowed<#lifetime> for #name #ty_generics #where_clause {
+            fn from_row_borrowed(row: &#lifetime tokio_postgres::row::Row) -> ::std::result::Result<Self, tokio_pg_mapper::Error> {
+                #trace_unmapped
+
+                Ok(Self {
+                    #(#fields,)*
+                    #rest
+                })
             }
         }
     };
@@ -144,6 +1600,32 @@ fn get_mapper_meta_items(attr: &syn::Attribute) -> Option<Vec<syn::NestedMeta>>
     }
 }
 
+fn get_patch_meta_items(attr: &syn::Attribute) -> Option<Vec<syn::NestedMeta>> {
+    if attr.path.segments.len() == 1 && attr.path.segments[0].ident == "pg_patch" {
+        match attr.parse_meta() {
+            Ok(List(ref meta)) => Some(meta.nested.iter().cloned().collect()),
+            _ => {
+                panic!("declare patched type: #[pg_patch(for = \"Foo\")]");
+            }
+        }
+    } else {
+        None
+    }
+}
+
+fn get_filter_meta_items(attr: &syn::Attribute) -> Option<Vec<syn::NestedMeta>> {
+    if attr.path.segments.len() == 1 && attr.path.segments[0].ident == "pg_filter" {
+        match attr.parse_meta() {
+            Ok(List(ref meta)) => Some(meta.nested.iter().cloned().collect()),
+            _ => {
+                panic!("declare filter: #[pg_filter(column = \"foo\", op = \">=\")]");
+            }
+        }
+    } else {
+        None
+    }
+}
+
 fn get_lit_str<'a>(
     attr_name: Option<&Ident>,
     lit: &'a syn::Lit,
@@ -161,9 +1643,46 @@ fn get_lit_str<'a>(
     }
 }
 
-fn parse_table_attr(ast: &DeriveInput) -> String {
-    // Parse `#[pg_mapper(table = "foo")]`
-    let mut table_name: Option<String> = None;
+struct ContainerAttrs {
+    table_name: Option<String>,
+    composite: Option<String>,
+    borrowed: bool,
+    diff: bool,
+    simple_query: bool,
+    copy: bool,
+    insert: bool,
+    notification: bool,
+    query: bool,
+    crud: bool,
+    empty_as_none: bool,
+    trim: Option<Trim>,
+    schema_file: Option<String>,
+    pivot: Option<Pivot>,
+}
+
+/// The key and value columns of `#[pg_mapper(pivot(key = "key", value = "value"))]`.
+struct Pivot {
+    key: String,
+    value: String,
+}
+
+fn parse_container_attrs(ast: &DeriveInput) -> ContainerAttrs {
+    let mut attrs = ContainerAttrs {
+        table_name: None,
+        composite: None,
+        borrowed: false,
+        diff: false,
+        simple_query: false,
+        copy: false,
+        insert: false,
+        notification: false,
+        query: false,
+        crud: false,
+        empty_as_none: false,
+        trim: None,
+        schema_file: None,
+        pivot: None,
+    };
 
     for meta_items in ast.attrs.iter().filter_map(get_mapper_meta_items) {
         for meta_item in meta_items {
@@ -171,11 +1690,73 @@ fn parse_table_attr(ast: &DeriveInput) -> String {
                 // Parse `#[pg_mapper(table = "foo")]`
                 Meta(NameValue(ref m)) if m.path.is_ident("table") => {
                     if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
-                        table_name = Some(s.value());
+                        attrs.table_name = Some(s.value());
+                    }
+                }
+                // Parse `#[pg_mapper(composite = "foo")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("composite") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.composite = Some(s.value());
+                    }
+                }
+                // Parse `#[pg_mapper(schema_file = "db/schema.sql")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("schema_file") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.schema_file = Some(s.value());
+                    }
+                }
+                // Parse `#[pg_mapper(pivot(key = "key", value = "value"))]`
+                Meta(List(ref list)) if list.path.is_ident("pivot") => {
+                    attrs.pivot = Some(parse_pivot(list));
+                }
+                // Parse `#[pg_mapper(borrowed)]`
+                Meta(Path(ref p)) if p.is_ident("borrowed") => {
+                    attrs.borrowed = true;
+                }
+                // Parse `#[pg_mapper(diff)]`
+                Meta(Path(ref p)) if p.is_ident("diff") => {
+                    attrs.diff = true;
+                }
+                // Parse `#[pg_mapper(simple_query)]`
+                Meta(Path(ref p)) if p.is_ident("simple_query") => {
+                    attrs.simple_query = true;
+                }
+                // Parse `#[pg_mapper(copy)]`
+                Meta(Path(ref p)) if p.is_ident("copy") => {
+                    attrs.copy = true;
+                }
+                // Parse `#[pg_mapper(insert)]`
+                Meta(Path(ref p)) if p.is_ident("insert") => {
+                    attrs.insert = true;
+                }
+                // Parse `#[pg_mapper(notification)]`
+                Meta(Path(ref p)) if p.is_ident("notification") => {
+                    attrs.notification = true;
+                }
+                // Parse `#[pg_mapper(query)]`
+                Meta(Path(ref p)) if p.is_ident("query") => {
+                    attrs.query = true;
+                }
+                // Parse `#[pg_mapper(crud)]`
+                Meta(Path(ref p)) if p.is_ident("crud") => {
+                    attrs.crud = true;
+                }
+                // Parse `#[pg_mapper(empty_as_none)]`
+                Meta(Path(ref p)) if p.is_ident("empty_as_none") => {
+                    attrs.empty_as_none = true;
+                }
+                // Parse `#[pg_mapper(trim)]`
+                Meta(Path(ref p)) if p.is_ident("trim") => {
+                    attrs.trim = Some(Trim::End);
+                }
+                // Parse `#[pg_mapper(trim = "both")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("trim") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.trim = Some(parse_trim(&s.value()));
                     }
                 }
                 Meta(_) => {
-                    panic!(format!("unknown pg_mapper container attribute",))
+                    panic!("unknown pg_mapper container attribute")
                 }
                 _ => {
                     panic!("unexpected literal in pg_mapper container attribute");
@@ -184,5 +1765,237 @@ fn parse_table_attr(ast: &DeriveInput) -> String {
         }
     }
 
-    table_name.expect("declare table name: #[pg_mapper(table = \"foo\")]")
+    attrs
+}
+
+struct FieldAttrs {
+    primary_key: bool,
+    readonly: bool,
+    unique: bool,
+    version: bool,
+    soft_delete: bool,
+    rename: Option<String>,
+    pg_type: Option<String>,
+    null_default: Option<NullDefault>,
+    empty_as_none: bool,
+    trim: Option<Trim>,
+    capture_rest: bool,
+    epoch: Option<Epoch>,
+    json_text: bool,
+}
+
+fn parse_field_attrs(field: &syn::Field) -> FieldAttrs {
+    let mut attrs = FieldAttrs {
+        primary_key: false,
+        readonly: false,
+        unique: false,
+        version: false,
+        soft_delete: false,
+        rename: None,
+        pg_type: None,
+        null_default: None,
+        empty_as_none: false,
+        trim: None,
+        capture_rest: false,
+        epoch: None,
+        json_text: false,
+    };
+
+    for meta_items in field.attrs.iter().filter_map(get_mapper_meta_items) {
+        for meta_item in meta_items {
+            match meta_item {
+                // Parse `#[pg_mapper(primary_key)]`
+                Meta(Path(ref p)) if p.is_ident("primary_key") => {
+                    attrs.primary_key = true;
+                }
+                // Parse `#[pg_mapper(readonly)]`
+                Meta(Path(ref p)) if p.is_ident("readonly") => {
+                    attrs.readonly = true;
+                }
+                // Parse `#[pg_mapper(unique)]`
+                Meta(Path(ref p)) if p.is_ident("unique") => {
+                    attrs.unique = true;
+                }
+                // Parse `#[pg_mapper(version)]`
+                Meta(Path(ref p)) if p.is_ident("version") => {
+                    attrs.version = true;
+                }
+                // Parse `#[pg_mapper(soft_delete)]`
+                Meta(Path(ref p)) if p.is_ident("soft_delete") => {
+                    attrs.soft_delete = true;
+                }
+                // Parse `#[pg_mapper(rename = "foo")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("rename") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.rename = Some(s.value());
+                    }
+                }
+                // Parse `#[pg_mapper(empty_as_none)]`
+                Meta(Path(ref p)) if p.is_ident("empty_as_none") => {
+                    attrs.empty_as_none = true;
+                }
+                // Parse `#[pg_mapper(trim)]`
+                Meta(Path(ref p)) if p.is_ident("trim") => {
+                    attrs.trim = Some(Trim::End);
+                }
+                // Parse `#[pg_mapper(trim = "both")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("trim") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.trim = Some(parse_trim(&s.value()));
+                    }
+                }
+                // Parse `#[pg_mapper(epoch = "seconds")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("epoch") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.epoch = Some(parse_epoch(&s.value()));
+                    }
+                }
+                // Parse `#[pg_mapper(json_text)]`
+                Meta(Path(ref p)) if p.is_ident("json_text") => {
+                    attrs.json_text = true;
+                }
+                // Parse `#[pg_mapper(capture_rest)]`
+                Meta(Path(ref p)) if p.is_ident("capture_rest") => {
+                    attrs.capture_rest = true;
+                }
+                // Parse `#[pg_mapper(default_on_null)]`
+                Meta(Path(ref p)) if p.is_ident("default_on_null") => {
+                    attrs.null_default = Some(NullDefault::Default);
+                }
+                // Parse `#[pg_mapper(default_on_null = "path::to::function")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("default_on_null") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        let function = s
+                            .parse()
+                            .expect("expected default_on_null to be a function path");
+                        attrs.null_default = Some(NullDefault::Function(function));
+                    }
+                }
+                // Parse `#[pg_mapper(pg_type = "uuid")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("pg_type") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.pg_type = Some(s.value());
+                    }
+                }
+                Meta(_) => {
+                    panic!("unknown pg_mapper field attribute")
+                }
+                _ => {
+                    panic!("unexpected literal in pg_mapper field attribute");
+                }
+            }
+        }
+    }
+
+    attrs
+}
+
+/// Quotes an optional string as an `Option<&'static str>` expression.
+fn optional_str(value: Option<String>) -> syn::Expr {
+    match value {
+        Some(value) => syn::parse_quote!(Some(#value)),
+        None => syn::parse_quote!(None),
+    }
+}
+
+struct PatchAttrs {
+    parent: Option<syn::Path>,
+    table_name: Option<String>,
+}
+
+fn parse_patch_attrs(ast: &DeriveInput) -> PatchAttrs {
+    let mut attrs = PatchAttrs {
+        parent: None,
+        table_name: None,
+    };
+
+    for meta_items in ast.attrs.iter().filter_map(get_patch_meta_items) {
+        for meta_item in meta_items {
+            match meta_item {
+                // Parse `#[pg_patch(for = "Foo")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("for") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        let parent = s
+                            .parse()
+                            .expect("expected pg_patch for attribute to be a type path");
+                        attrs.parent = Some(parent);
+                    }
+                }
+                // Parse `#[pg_patch(table = "foo")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("table") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.table_name = Some(s.value());
+                    }
+                }
+                Meta(_) => {
+                    panic!("unknown pg_patch container attribute")
+                }
+                _ => {
+                    panic!("unexpected literal in pg_patch container attribute");
+                }
+            }
+        }
+    }
+
+    attrs
+}
+
+struct FilterFieldAttrs {
+    column: Option<String>,
+    op: Option<&'static str>,
+}
+
+/// Checks the operator of a `pg_filter` field, which is interpolated into the
+/// `WHERE` clause as is.
+fn parse_filter_op(op: &str) -> &'static str {
+    match op.to_ascii_uppercase().as_str() {
+        "=" => "=",
+        "<>" | "!=" => "<>",
+        "<" => "<",
+        "<=" => "<=",
+        ">" => ">",
+        ">=" => ">=",
+        "LIKE" => "LIKE",
+        "ILIKE" => "ILIKE",
+        "NOT LIKE" => "NOT LIKE",
+        "NOT ILIKE" => "NOT ILIKE",
+        _ => panic!(
+            "unknown pg_filter op \"{}\", expected one of =, <>, !=, <, <=, >, >=, LIKE, ILIKE, NOT LIKE or NOT ILIKE",
+            op
+        ),
+    }
+}
+
+fn parse_filter_field_attrs(field: &syn::Field) -> FilterFieldAttrs {
+    let mut attrs = FilterFieldAttrs {
+        column: None,
+        op: None,
+    };
+
+    for meta_items in field.attrs.iter().filter_map(get_filter_meta_items) {
+        for meta_item in meta_items {
+            match meta_item {
+                // Parse `#[pg_filter(column = "foo")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("column") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.column = Some(s.value());
+                    }
+                }
+                // Parse `#[pg_filter(op = ">=")]`
+                Meta(NameValue(ref m)) if m.path.is_ident("op") => {
+                    if let Ok(s) = get_lit_str(m.path.get_ident(), &m.lit) {
+                        attrs.op = Some(parse_filter_op(&s.value()));
+                    }
+                }
+                Meta(_) => {
+                    panic!("unknown pg_filter field attribute")
+                }
+                _ => {
+                    panic!("unexpected literal in pg_filter field attribute");
+                }
+            }
+        }
+    }
+
+    attrs
 }
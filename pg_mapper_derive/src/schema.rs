@@ -0,0 +1,690 @@
+//! Validation of mapped fields against the `CREATE TABLE` statements of a
+//! schema file, for `#[pg_mapper(schema_file = "...")]`, and with the
+//! `verify-schema` feature against the database at `PG_MAPPER_DATABASE_URL`.
+//!
+//! The parser only understands what it needs of `CREATE TABLE` statements:
+//! the name, type and nullability of each column. Everything else in the file
+//! is skipped.
+
+use std::path::PathBuf;
+
+use syn::{DataStruct, GenericArgument, Ident, PathArguments, Type};
+
+use crate::column::{column_specs, is_cow_str};
+use crate::option_inner_type;
+
+/// A column of a `CREATE TABLE` statement or of a live table.
+struct Column {
+    name: String,
+    pg_type: String,
+    nullable: bool,
+}
+
+/// Checks the fields of a mapped struct against the columns of its table in
+/// the schema file, returning a spanned error for every mismatch.
+pub fn validate(
+    s: &DataStruct,
+    name: &Ident,
+    table_name: &str,
+    schema_file: &str,
+) -> Vec<syn::Error> {
+    let path = match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir).join(schema_file),
+        None => PathBuf::from(schema_file),
+    };
+
+    let sql = match std::fs::read_to_string(&path) {
+        Ok(sql) => sql,
+        Err(err) => {
+            let msg = format!("can't read schema file {}: {}", path.display(), err);
+            return vec![syn::Error::new(name.span(), msg)];
+        }
+    };
+
+    let columns = match find_table(&sql, table_name) {
+        Some(columns) => columns,
+        None => {
+            let msg = format!(
+                "no CREATE TABLE statement for table `{}` in schema file {}",
+                table_name,
+                path.display()
+            );
+            return vec![syn::Error::new(name.span(), msg)];
+        }
+    };
+
+    check_columns(s, table_name, &columns)
+}
+
+/// Checks the fields of a mapped struct against the columns of its table in
+/// the database at `PG_MAPPER_DATABASE_URL`, if set at build time, returning a
+/// spanned error for every mismatch.
+///
+/// Cargo doesn't rebuild a crate when the variable or the database changes,
+/// so a stale build may need `cargo clean -p` to be checked again.
+#[cfg(feature = "verify-schema")]
+pub fn verify(s: &DataStruct, name: &Ident, table_name: &str) -> Vec<syn::Error> {
+    let url = match std::env::var("PG_MAPPER_DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut client = match postgres::Client::connect(&url, postgres::NoTls) {
+        Ok(client) => client,
+        Err(err) => {
+            let msg = format!("can't connect to PG_MAPPER_DATABASE_URL: {}", err);
+            return vec![syn::Error::new(name.span(), msg)];
+        }
+    };
+
+    // `to_regclass` resolves the table name like a query would, following
+    // the search path and quoting rules.
+    let rows = client.query(
+        "SELECT column_name::text, udt_name::text, is_nullable = 'YES' \
+         FROM information_schema.columns \
+         WHERE format('%I.%I', table_schema, table_name)::regclass \
+             = to_regclass($1) \
+         ORDER BY ordinal_position",
+        &[&table_name],
+    );
+    let columns = match rows {
+        Ok(rows) => rows
+            .iter()
+            .map(|row| Column {
+                name: row.get(0),
+                pg_type: row.get(1),
+                nullable: row.get(2),
+            })
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            let msg =
+                format!("can't query the columns of table `{}`: {}", table_name, err);
+            return vec![syn::Error::new(name.span(), msg)];
+        }
+    };
+
+    if columns.is_empty() {
+        let msg = format!(
+            "table `{}` doesn't exist in the database at PG_MAPPER_DATABASE_URL",
+            table_name
+        );
+        return vec![syn::Error::new(name.span(), msg)];
+    }
+
+    check_columns(s, table_name, &columns)
+}
+
+fn check_columns(
+    s: &DataStruct,
+    table_name: &str,
+    columns: &[Column],
+) -> Vec<syn::Error> {
+    let mut errors = Vec::new();
+
+    for spec in column_specs(s) {
+        let ident = spec.ident;
+        let column_name = &spec.name;
+
+        let column = match columns.iter().find(|c| &c.name == column_name) {
+            Some(column) => column,
+            None => {
+                errors.push(syn::Error::new_spanned(
+                    ident,
+                    format!("table `{}` has no column `{}`", table_name, column_name),
+                ));
+                continue;
+            }
+        };
+
+        let inner = option_inner_type(spec.ty);
+        if column.nullable && !spec.nullable() {
+            errors.push(syn::Error::new_spanned(
+                spec.ty,
+                format!(
+                    "column `{}` is nullable, so field `{}` must be an Option",
+                    column_name, ident
+                ),
+            ));
+        }
+
+        // A `pg_type` hint is checked instead of the field type.
+        if let Some(ref hint) = spec.pg_type {
+            let hint_family = pg_family(&hint.to_lowercase());
+            let family = pg_family(&column.pg_type);
+            if let (Some(hint_family), Some(family)) = (hint_family, family) {
+                if hint_family != family {
+                    errors.push(syn::Error::new_spanned(
+                        ident,
+                        format!(
+                            "column `{}` of type {} doesn't match the pg_type hint {}",
+                            column_name, column.pg_type, hint
+                        ),
+                    ));
+                }
+            }
+            continue;
+        }
+
+        let ty = inner.unwrap_or(spec.ty);
+        let rust_type = rust_type_name(ty);
+        let family = pg_family(&column.pg_type);
+        if let (Some(rust_type), Some(family)) = (rust_type, family) {
+            if !compatible(&rust_type, family) {
+                errors.push(syn::Error::new_spanned(
+                    ty,
+                    format!(
+                        "column `{}` of type {} can't be mapped to {}",
+                        column_name, column.pg_type, rust_type
+                    ),
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Get the name of a Rust type whose compatibility can be checked.
+fn rust_type_name(ty: &Type) -> Option<String> {
+    let path = match *ty {
+        Type::Path(ref p) if p.qself.is_none() => &p.path,
+        Type::Reference(ref r) => return rust_type_name(&r.elem),
+        _ => return None,
+    };
+    if is_cow_str(ty) {
+        return Some("String".to_string());
+    }
+    let name = path.segments.last()?.ident.to_string();
+
+    match name.as_str() {
+        "bool" | "i8" | "i16" | "i32" | "i64" | "f32" | "f64" | "String" | "str" => {
+            Some(name)
+        }
+        _ => None,
+    }
+}
+
+/// Infers the Postgres type declared for a field type, such as `BIGINT` for
+/// `i64`, ignoring an outer `Option`.
+pub fn infer_pg_type(ty: &Type) -> Option<String> {
+    let ty = option_inner_type(ty).unwrap_or(ty);
+    if is_cow_str(ty) {
+        return Some("TEXT".to_string());
+    }
+    let segment = match *ty {
+        Type::Path(ref p) if p.qself.is_none() => p.path.segments.last()?,
+        Type::Reference(ref r) => return infer_pg_type(&r.elem),
+        _ => return None,
+    };
+
+    let pg_type = match segment.ident.to_string().as_str() {
+        "bool" => "BOOLEAN",
+        "i8" => "\"char\"",
+        "i16" => "SMALLINT",
+        "i32" => "INTEGER",
+        "i64" => "BIGINT",
+        "u32" => "OID",
+        "f32" => "REAL",
+        "f64" => "DOUBLE PRECISION",
+        "String" | "str" => "TEXT",
+        "Bytes" | "PgBytea" => "BYTEA",
+        "SystemTime" | "DateTime" => "TIMESTAMPTZ",
+        "NaiveDateTime" => "TIMESTAMP",
+        "NaiveDate" => "DATE",
+        "NaiveTime" => "TIME",
+        "IpAddr" => "INET",
+        "Uuid" => "UUID",
+        "Value" | "Json" => "JSONB",
+        "PgNumeric" => "NUMERIC",
+        "PgMoney" => "MONEY",
+        "PgInterval" | "PgDuration" | "Duration" => "INTERVAL",
+        "PgHstore" => "HSTORE",
+        "PgLtree" => "LTREE",
+        "PgXml" => "XML",
+        "PgBits" => "VARBIT",
+        "PgMacAddr" => "MACADDR",
+        "PgMacAddr8" => "MACADDR8",
+        "PgPoint" => "POINT",
+        "PgBox" => "BOX",
+        "PgPath" => "PATH",
+        "PgTsVector" => "TSVECTOR",
+        "PgTsQuery" => "TSQUERY",
+        "Vec" => {
+            let element = match segment.arguments {
+                PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => {
+                    match args.args.first() {
+                        Some(GenericArgument::Type(ref element)) => element,
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            };
+
+            if let Type::Path(ref p) = *element {
+                if p.path.is_ident("u8") {
+                    return Some("BYTEA".to_string());
+                }
+            }
+
+            return infer_pg_type(element).map(|element| format!("{}[]", element));
+        }
+        _ => return None,
+    };
+
+    Some(pg_type.to_string())
+}
+
+/// Get the family of the Postgres types whose compatibility can be checked.
+fn pg_family(pg_type: &str) -> Option<&'static str> {
+    if pg_type.ends_with("[]") {
+        return None;
+    }
+    let base = pg_type.split('(').next().unwrap_or(pg_type).trim();
+
+    Some(match base {
+        "smallint" | "int2" | "smallserial" | "serial2" => "int2",
+        "integer" | "int" | "int4" | "serial" | "serial4" => "int4",
+        "bigint" | "int8" | "bigserial" | "serial8" => "int8",
+        "real" | "float4" => "float4",
+        "double precision" | "float8" => "float8",
+        "boolean" | "bool" => "bool",
+        "text" | "varchar" | "character varying" | "char" | "character" | "bpchar"
+        | "citext" | "name" => "text",
+        _ => return None,
+    })
+}
+
+/// Whether a Rust type accepts a family of Postgres types, as `FromSql` does.
+fn compatible(rust_type: &str, family: &str) -> bool {
+    match family {
+        "int2" => rust_type == "i16",
+        "int4" => rust_type == "i32",
+        "int8" => rust_type == "i64",
+        "float4" => rust_type == "f32",
+        "float8" => rust_type == "f64",
+        "bool" => rust_type == "bool",
+        "text" => rust_type == "String" || rust_type == "str",
+        _ => true,
+    }
+}
+
+/// Removes `--` and `/* */` comments, leaving quoted text alone.
+fn strip_comments(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut quote = None;
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => {
+                quote = None;
+                out.push(c);
+            }
+            (Some(_), _) => out.push(c),
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                out.push(c);
+            }
+            (None, '-') if chars.peek() == Some(&'-') => {
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            (None, '/') if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+                out.push(' ');
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Splits text on a separator outside of quotes and parentheses.
+fn split_top_level(text: &str, is_separator: impl Fn(char) -> bool) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ if depth == 0 && is_separator(c) => {
+                    if !current.trim().is_empty() {
+                        parts.push(current.trim().to_string());
+                    }
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            },
+        }
+        current.push(c);
+    }
+
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+
+    parts
+}
+
+/// Normalizes an identifier: quoted identifiers keep their case, others are
+/// folded to lower case like Postgres does. Only the last part of a qualified
+/// name is kept.
+fn normalize_ident(ident: &str) -> String {
+    let last = split_top_level(ident, |c| c == '.')
+        .pop()
+        .unwrap_or_default();
+
+    if last.starts_with('"') && last.ends_with('"') && last.len() >= 2 {
+        last[1..last.len() - 1].replace("\"\"", "\"")
+    } else {
+        last.to_lowercase()
+    }
+}
+
+/// Finds the columns of the `CREATE TABLE` statement of a table.
+fn find_table(sql: &str, table_name: &str) -> Option<Vec<Column>> {
+    let sql = strip_comments(sql);
+    let table_name = normalize_ident(table_name);
+
+    split_top_level(&sql, |c| c == ';')
+        .iter()
+        .find_map(|statement| parse_create_table(statement, &table_name))
+}
+
+fn parse_create_table(statement: &str, table_name: &str) -> Option<Vec<Column>> {
+    let open = statement.find('(')?;
+    let head = statement[..open]
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let keywords = head
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+
+    let table_idx = keywords.iter().position(|word| word == "table")?;
+    if keywords.first().map(String::as_str) != Some("create") {
+        return None;
+    }
+
+    // Skip `IF NOT EXISTS`.
+    let name = head[table_idx + 1..]
+        .iter()
+        .zip(&keywords[table_idx + 1..])
+        .filter(|(_, keyword)| !matches!(keyword.as_str(), "if" | "not" | "exists"))
+        .map(|(word, _)| word.as_str())
+        .collect::<String>();
+    if normalize_ident(&name) != table_name {
+        return None;
+    }
+
+    let close = matching_paren(statement, open)?;
+    let definitions = split_top_level(&statement[open + 1..close], |c| c == ',');
+
+    let mut columns = definitions
+        .iter()
+        .filter_map(|definition| parse_column(definition))
+        .collect::<Vec<_>>();
+
+    // Columns of a table-level `PRIMARY KEY (a, b)` are NOT NULL too.
+    for definition in &definitions {
+        let lower = definition.to_lowercase();
+        if !lower.starts_with("primary") && !lower.starts_with("constraint") {
+            continue;
+        }
+        let key = match lower.find("primary key") {
+            Some(idx) => &definition[idx..],
+            None => continue,
+        };
+        let (open, close) = match (key.find('('), key.rfind(')')) {
+            (Some(open), Some(close)) if open < close => (open, close),
+            _ => continue,
+        };
+        let keys = split_top_level(&key[open + 1..close], |c| c == ',');
+
+        for column in columns.iter_mut() {
+            if keys.iter().any(|key| normalize_ident(key) == column.name) {
+                column.nullable = false;
+            }
+        }
+    }
+
+    Some(columns)
+}
+
+/// Finds the parenthesis closing the one at `open`.
+fn matching_paren(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+
+    for (idx, c) in text.char_indices().skip_while(|&(idx, _)| idx < open) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(idx);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    None
+}
+
+const CONSTRAINT_KEYWORDS: &[&str] = &[
+    "constraint",
+    "primary",
+    "unique",
+    "foreign",
+    "check",
+    "exclude",
+    "like",
+];
+
+const COLUMN_KEYWORDS: &[&str] = &[
+    "not",
+    "null",
+    "default",
+    "primary",
+    "references",
+    "unique",
+    "check",
+    "constraint",
+    "generated",
+    "collate",
+];
+
+fn parse_column(definition: &str) -> Option<Column> {
+    let words = split_top_level(definition, char::is_whitespace);
+    let lower = words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+
+    if CONSTRAINT_KEYWORDS.contains(&lower.first()?.as_str()) {
+        return None;
+    }
+
+    let type_end = lower
+        .iter()
+        .skip(1)
+        .position(|word| COLUMN_KEYWORDS.contains(&word.as_str()))
+        .map_or(lower.len(), |idx| idx + 1);
+    let pg_type = lower[1..type_end].join(" ");
+
+    let not_null = lower.windows(2).any(|pair| {
+        matches!((&*pair[0], &*pair[1]), ("not", "null") | ("primary", "key"))
+    });
+
+    Some(Column {
+        name: normalize_ident(&words[0]),
+        pg_type,
+        nullable: !not_null,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get the name, type and nullability of the columns of a table.
+    fn columns(sql: &str, table_name: &str) -> Option<Vec<(String, String, bool)>> {
+        find_table(sql, table_name).map(|columns| {
+            columns
+                .into_iter()
+                .map(|c| (c.name, c.pg_type, c.nullable))
+                .collect()
+        })
+    }
+
+    fn column(name: &str, pg_type: &str, nullable: bool) -> (String, String, bool) {
+        (name.to_string(), pg_type.to_string(), nullable)
+    }
+
+    #[test]
+    fn quoted_identifiers_keep_their_case() {
+        let sql = r#"CREATE TABLE "Users" ("Id" BIGINT NOT NULL, "a ""b""" TEXT);"#;
+
+        assert_eq!(
+            columns(sql, "\"Users\""),
+            Some(vec![
+                column("Id", "bigint", false),
+                column("a \"b\"", "text", true)
+            ])
+        );
+        assert_eq!(columns(sql, "users"), None);
+    }
+
+    #[test]
+    fn unquoted_identifiers_are_folded_to_lower_case() {
+        let sql = "CREATE TABLE Users (ID BigInt, Email VARCHAR(255) NOT NULL);";
+
+        assert_eq!(
+            columns(sql, "users"),
+            Some(vec![
+                column("id", "bigint", true),
+                column("email", "varchar(255)", false),
+            ])
+        );
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let sql = "
+            -- CREATE TABLE users (id TEXT);
+            /* CREATE TABLE users (id TEXT); */
+            CREATE TABLE users (
+                id BIGINT, -- the key, (not a column)
+                /* name TEXT, */
+                note TEXT DEFAULT '-- not a comment'
+            );
+        ";
+
+        assert_eq!(
+            columns(sql, "users"),
+            Some(vec![
+                column("id", "bigint", true),
+                column("note", "text", true)
+            ])
+        );
+    }
+
+    #[test]
+    fn if_not_exists_is_skipped() {
+        let sql = "create table if not exists users (id int primary key);";
+
+        assert_eq!(
+            columns(sql, "users"),
+            Some(vec![column("id", "int", false)])
+        );
+    }
+
+    #[test]
+    fn schema_qualified_names_match_the_table() {
+        let sql = "
+            CREATE TABLE audit.events (id BIGINT NOT NULL);
+            CREATE TABLE \"Audit\".\"Logs\" (id INTEGER);
+        ";
+
+        assert_eq!(
+            columns(sql, "audit.events"),
+            Some(vec![column("id", "bigint", false)])
+        );
+        assert_eq!(
+            columns(sql, "events"),
+            Some(vec![column("id", "bigint", false)])
+        );
+        assert_eq!(
+            columns(sql, "\"Audit\".\"Logs\""),
+            Some(vec![column("id", "integer", true)])
+        );
+    }
+
+    #[test]
+    fn constraints_are_not_columns() {
+        let sql = "
+            CREATE TABLE memberships (
+                user_id BIGINT,
+                group_id BIGINT,
+                role TEXT CHECK (role IN ('admin', 'member')),
+                CONSTRAINT memberships_pkey PRIMARY KEY (user_id, group_id),
+                UNIQUE (role, group_id),
+                FOREIGN KEY (user_id) REFERENCES users (id),
+                CHECK (group_id > 0),
+                EXCLUDE USING gist (role WITH =),
+                LIKE templates
+            );
+        ";
+
+        assert_eq!(
+            columns(sql, "memberships"),
+            Some(vec![
+                column("user_id", "bigint", false),
+                column("group_id", "bigint", false),
+                column("role", "text", true),
+            ])
+        );
+    }
+
+    #[test]
+    fn other_statements_are_skipped() {
+        let sql = "
+            CREATE INDEX users_email ON users (email);
+            CREATE TABLE user_emails (email TEXT);
+            INSERT INTO users (id) VALUES (1);
+        ";
+
+        assert_eq!(columns(sql, "users"), None);
+        assert_eq!(
+            columns(sql, "user_emails"),
+            Some(vec![column("email", "text", true)])
+        );
+    }
+}
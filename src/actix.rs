@@ -0,0 +1,60 @@
+//! `actix-web` integration, enabled with the `actix` feature.
+//!
+//! [`Error`] implements `actix_web::ResponseError`, so handlers can return
+//! `Result<T, tokio_pg_mapper::Error>` directly. Every error is reported as a
+//! `500 Internal Server Error` with a generic body that doesn't leak column
+//! names or database messages to clients, unless overridden with
+//! [`set_status_code_hook`].
+//!
+//! [`Error`]: ../enum.Error.html
+//! [`set_status_code_hook`]: fn.set_status_code_hook.html
+
+use crate::Error;
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+
+use std::sync::RwLock;
+
+type StatusCodeHook = fn(&Error) -> Option<StatusCode>;
+
+static STATUS_CODE_HOOK: RwLock<Option<StatusCodeHook>> = RwLock::new(None);
+
+/// Sets a hook choosing the response status code for an error.
+///
+/// The hook returning `None` falls back to `500 Internal Server Error`.
+///
+/// Example:
+///
+/// ```
+///     tokio_pg_mapper::actix::set_status_code_hook(|err| {
+///         if err.is_unexpected_null() {
+///             Some(StatusCode::NOT_FOUND)
+///         } else {
+///             None
+///         }
+///     });
+/// ```
+pub fn set_status_code_hook(hook: StatusCodeHook) {
+    *STATUS_CODE_HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(hook);
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        let hook = *STATUS_CODE_HOOK
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        hook.and_then(|hook| hook(self))
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+
+        HttpResponse::build(status)
+            .body(status.canonical_reason().unwrap_or("Internal Server Error"))
+    }
+}
@@ -0,0 +1,47 @@
+//! `axum` integration, enabled with the `axum` feature.
+//!
+//! [`Error`] implements `axum::response::IntoResponse`, so handlers can use
+//! `?` on mapping results. Every error is reported as a
+//! `500 Internal Server Error`, with a body chosen by
+//! [`Error::into_response_with`].
+//!
+//! [`Error`]: ../enum.Error.html
+//! [`Error::into_response_with`]: ../enum.Error.html#method.into_response_with
+
+use crate::Error;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+impl Error {
+    /// Converts the error into an `axum` response.
+    ///
+    /// Without `detail` the body is a generic "Internal Server Error", so no
+    /// column names or database messages reach clients. With `detail` the
+    /// body describes the error: as JSON when the `serde` feature is enabled,
+    /// otherwise as text.
+    pub fn into_response_with(self, detail: bool) -> Response {
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+
+        if !detail {
+            return (status, "Internal Server Error").into_response();
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            (status, axum::Json(self)).into_response()
+        }
+
+        #[cfg(not(feature = "serde"))]
+        {
+            (status, self.to_string()).into_response()
+        }
+    }
+}
+
+/// Error details are only included in the response body of debug builds.
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        self.into_response_with(cfg!(debug_assertions))
+    }
+}
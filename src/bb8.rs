@@ -0,0 +1,119 @@
+//! `bb8-postgres` integration, enabled with the `bb8` feature.
+//!
+//! [`PoolExt`] is implemented for `bb8::Pool<PostgresConnectionManager<_>>`, so
+//! checking out a connection, running a query and mapping its rows are a
+//! single call:
+//!
+//! ```
+//!     use tokio_pg_mapper::bb8::PoolExt;
+//!
+//!     let users: Vec<User> = pool
+//!         .query_as("SELECT id, email FROM users WHERE active = $1", &[&true])
+//!         .await?;
+//! ```
+//!
+//! A connection timing out is reported as [`Error::Pool`], while errors of
+//! the database connection itself are reported as [`Error::Db`].
+//!
+//! [`PoolExt`]: trait.PoolExt.html
+//! [`Error::Pool`]: ../enum.Error.html#variant.Pool
+//! [`Error::Db`]: ../enum.Error.html#variant.Db
+
+use crate::{ClientExt, Error, FromTokioPostgresRow};
+
+use bb8_postgres::bb8::{Pool, RunError};
+use bb8_postgres::PostgresConnectionManager;
+use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Socket;
+
+use std::future::Future;
+
+impl From<RunError<tokio_postgres::Error>> for Error {
+    fn from(err: RunError<tokio_postgres::Error>) -> Self {
+        match err {
+            RunError::User(err) => Error::Db(err),
+            err => Error::Pool(Box::new(err)),
+        }
+    }
+}
+
+/// Extension trait running queries on a pooled connection and mapping the
+/// returned rows.
+///
+/// The connection is returned to the pool as soon as the rows are mapped, so
+/// nothing returned borrows from it.
+pub trait PoolExt {
+    /// Runs a query on a pooled connection and maps every returned row.
+    ///
+    /// See [`ClientExt::query_as`].
+    ///
+    /// [`ClientExt::query_as`]: ../client/trait.ClientExt.html#tymethod.query_as
+    fn query_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Vec<T>, Error>> + Send;
+
+    /// Runs a query expected to return exactly one row on a pooled
+    /// connection, and maps it.
+    ///
+    /// See [`ClientExt::query_one_as`].
+    ///
+    /// [`ClientExt::query_one_as`]: ../client/trait.ClientExt.html#tymethod.query_one_as
+    fn query_one_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<T, Error>> + Send;
+
+    /// Runs a query expected to return zero or one row on a pooled
+    /// connection, and maps it.
+    ///
+    /// See [`ClientExt::query_opt_as`].
+    ///
+    /// [`ClientExt::query_opt_as`]: ../client/trait.ClientExt.html#tymethod.query_opt_as
+    fn query_opt_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Option<T>, Error>> + Send;
+}
+
+impl<Tls> PoolExt for Pool<PostgresConnectionManager<Tls>>
+where
+    Tls: MakeTlsConnect<Socket> + Clone + Send + Sync + 'static,
+    <Tls as MakeTlsConnect<Socket>>::Stream: Send + Sync,
+    <Tls as MakeTlsConnect<Socket>>::TlsConnect: Send,
+    <<Tls as MakeTlsConnect<Socket>>::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    async fn query_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, Error> {
+        let client = self.get().await?;
+
+        client.query_as(statement, params).await
+    }
+
+    async fn query_one_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<T, Error> {
+        let client = self.get().await?;
+
+        client.query_one_as(statement, params).await
+    }
+
+    async fn query_opt_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<T>, Error> {
+        let client = self.get().await?;
+
+        client.query_opt_as(statement, params).await
+    }
+}
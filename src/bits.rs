@@ -0,0 +1,163 @@
+//! The `PgBits` field type.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Mapping of `bit` and `varbit` columns and parameters.
+///
+/// Bits are indexed from the left, as in Postgres: bit 0 of `B'100'` is set.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PgBits {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+impl PgBits {
+    /// Creates a bit string of the given length with every bit unset.
+    pub fn new(len: usize) -> Self {
+        PgBits {
+            len,
+            bytes: vec![0; len.div_ceil(8)],
+        }
+    }
+
+    /// Get the number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the bit at the given index, or `None` if it's out of bounds.
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        if idx >= self.len {
+            return None;
+        }
+
+        Some(self.bytes[idx / 8] & (0x80 >> (idx % 8)) != 0)
+    }
+
+    /// Sets or unsets the bit at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, idx: usize, value: bool) {
+        assert!(
+            idx < self.len,
+            "bit index {} out of bounds for {} bits",
+            idx,
+            self.len
+        );
+
+        let mask = 0x80 >> (idx % 8);
+        if value {
+            self.bytes[idx / 8] |= mask;
+        } else {
+            self.bytes[idx / 8] &= !mask;
+        }
+    }
+
+    /// Get the bytes holding the bits, with the unused bits of the last byte
+    /// unset.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Creates a bit string of the given length holding the lowest bits of an
+    /// integer, the last bit being its least significant one, or `None` if
+    /// the length is more than 64 bits or too short to hold the value.
+    pub fn from_u64(value: u64, len: usize) -> Option<Self> {
+        if len > 64 || (len < 64 && value >> len != 0) {
+            return None;
+        }
+
+        let mut bits = PgBits::new(len);
+        for idx in 0..len {
+            bits.set(idx, (value >> (len - 1 - idx)) & 1 != 0);
+        }
+
+        Some(bits)
+    }
+
+    /// Converts into an integer whose least significant bit is the last bit,
+    /// or `None` if there are more than 64 bits.
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.len > 64 {
+            return None;
+        }
+
+        Some((0..self.len).fold(0, |acc, idx| {
+            (acc << 1) | u64::from(self.get(idx) == Some(true))
+        }))
+    }
+}
+
+impl Display for PgBits {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let text = (0..self.len)
+            .map(|idx| if self.get(idx) == Some(true) { '1' } else { '0' })
+            .collect::<String>();
+
+        f.pad(&text)
+    }
+}
+
+impl<'a> FromSql<'a> for PgBits {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if raw.len() < 4 {
+            return Err("invalid bit string length".into());
+        }
+
+        let len =
+            usize::try_from(raw.get_i32()).map_err(|_| "invalid bit string length")?;
+        if raw.len() != len.div_ceil(8) {
+            return Err("invalid bit string length".into());
+        }
+
+        let mut bytes = raw.to_vec();
+        // Postgres leaves the padding bits unset, but don't rely on it for
+        // equality.
+        if len % 8 != 0 {
+            if let Some(last) = bytes.last_mut() {
+                *last &= 0xff << (8 - len % 8);
+            }
+        }
+
+        Ok(PgBits { len, bytes })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BIT || *ty == Type::VARBIT
+    }
+}
+
+impl ToSql for PgBits {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        let len = i32::try_from(self.len).map_err(|_| "bit string too long")?;
+        out.put_i32(len);
+        out.put_slice(&self.bytes);
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BIT || *ty == Type::VARBIT
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,112 @@
+//! The `PgBytea` field type.
+
+use bytes::{BufMut, Bytes, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::ops::Deref;
+
+/// Mapping of `bytea` columns and parameters to [`Bytes`].
+///
+/// `FromSql` implementations are only given a slice of the buffer of a row,
+/// not the `Bytes` holding it, so the value can't be borrowed from the row:
+/// mapping a column copies its bytes exactly once, into a new allocation of
+/// the value's length. That is the same single copy as mapping a `Vec<u8>`,
+/// but the result is reference counted, so cloning it or handing it to
+/// `hyper` or `axum` as a response body doesn't copy it again, and it
+/// doesn't keep the rest of the row alive.
+///
+/// With the derive, fields of type `Bytes` or `Option<Bytes>` are fetched
+/// through this type and unwrapped. Writing parameters needs `PgBytea`
+/// fields, as `Bytes` has no `ToSql` implementation.
+///
+/// [`Bytes`]: https://docs.rs/bytes/1/bytes/struct.Bytes.html
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgBytea(pub Bytes);
+
+impl PgBytea {
+    /// Unwraps the inner bytes.
+    pub fn into_inner(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for PgBytea {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Bytes> for PgBytea {
+    fn from(bytes: Bytes) -> Self {
+        PgBytea(bytes)
+    }
+}
+
+impl From<PgBytea> for Bytes {
+    fn from(bytea: PgBytea) -> Self {
+        bytea.0
+    }
+}
+
+impl<'a> FromSql<'a> for PgBytea {
+    fn from_sql(
+        _: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(PgBytea(Bytes::copy_from_slice(raw)))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BYTEA
+    }
+}
+
+impl ToSql for PgBytea {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        out.put_slice(&self.0);
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BYTEA
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PgBytea {
+    /// Deserializes a hexadecimal string in Postgres' `\\x` format, as found
+    /// in the JSON of `row_to_json`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+
+        parse_hex(&text).map(PgBytea).ok_or_else(|| {
+            serde::de::Error::custom(format_args!("invalid bytea {:?}", text))
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+fn parse_hex(text: &str) -> Option<Bytes> {
+    let hex = text.strip_prefix("\\x")?.as_bytes();
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    hex.chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect::<Option<Vec<u8>>>()
+        .map(Bytes::from)
+}
@@ -0,0 +1,129 @@
+//! Capture of the columns which aren't mapped to a field, for
+//! `#[pg_mapper(capture_rest)]` fields.
+//!
+//! The columns of a row without a field of their own are collected into a
+//! `HashMap<String, V>` field, keyed by column name, where `V` is one of:
+//!
+//! - `Option<String>`, holding the text of booleans, integers, floats and
+//!   text columns, and `None` for NULL
+//! - with the `json` feature, `serde_json::Value`, holding the same values
+//!   as JSON scalars, `json` and `jsonb` columns as-is, and `null` for NULL
+//!
+//! ```
+//!     #[derive(PostgresMapper)]
+//!     #[pg_mapper(table = "events")]
+//!     pub struct Event {
+//!         pub id: i64,
+//!         #[pg_mapper(capture_rest)]
+//!         pub experiments: HashMap<String, Option<String>>,
+//!     }
+//! ```
+//!
+//! Columns of other types fail the mapping with an
+//! [`Error::ColumnConversion`].
+//!
+//! [`Error::ColumnConversion`]: ../enum.Error.html#variant.ColumnConversion
+
+use crate::{try_get_column_at, Error};
+
+use tokio_postgres::row::Row;
+use tokio_postgres::types::FromSql;
+
+use std::collections::HashMap;
+
+/// Values of the columns captured by a `#[pg_mapper(capture_rest)]` field.
+pub trait CapturedValue: Sized {
+    /// Converts the column at the given position, reporting errors like
+    /// [`try_get_column`] under the given column name.
+    ///
+    /// [`try_get_column`]: ../fn.try_get_column.html
+    fn from_column(
+        row: &Row,
+        idx: usize,
+        column: &str,
+        type_name: &'static str,
+    ) -> Result<Self, Error>;
+}
+
+impl CapturedValue for Option<String> {
+    fn from_column(
+        row: &Row,
+        idx: usize,
+        column: &str,
+        type_name: &'static str,
+    ) -> Result<Self, Error> {
+        let ty = row.columns()[idx].type_();
+
+        macro_rules! to_text {
+            ($($rust:ty),*) => {
+                $(
+                    if <$rust as FromSql>::accepts(ty) {
+                        let value = try_get_column_at::<Option<$rust>>(
+                            row, idx, column, "", type_name,
+                        )?;
+
+                        return Ok(value.map(|value| value.to_string()));
+                    }
+                )*
+            };
+        }
+
+        to_text!(bool, i8, i16, i32, i64, u32, f32, f64);
+
+        try_get_column_at(row, idx, column, "", type_name)
+    }
+}
+
+#[cfg(feature = "json")]
+impl CapturedValue for serde_json::Value {
+    fn from_column(
+        row: &Row,
+        idx: usize,
+        column: &str,
+        type_name: &'static str,
+    ) -> Result<Self, Error> {
+        use serde_json::Value;
+
+        let ty = row.columns()[idx].type_();
+
+        macro_rules! to_json {
+            ($($rust:ty),*) => {
+                $(
+                    if <$rust as FromSql>::accepts(ty) {
+                        let value = try_get_column_at::<Option<$rust>>(
+                            row, idx, column, "", type_name,
+                        )?;
+
+                        return Ok(value.map(Value::from).unwrap_or(Value::Null));
+                    }
+                )*
+            };
+        }
+
+        to_json!(bool, i8, i16, i32, i64, u32, f32, f64, Value);
+
+        let value = Option::<String>::from_column(row, idx, column, type_name)?;
+
+        Ok(value.map(Value::String).unwrap_or(Value::Null))
+    }
+}
+
+/// Collects the columns of a row which aren't in the given mapped columns, on
+/// behalf of derive-generated code for `#[pg_mapper(capture_rest)]` fields.
+#[doc(hidden)]
+pub fn capture_rest<V: CapturedValue>(
+    row: &Row,
+    columns: &[&str],
+    type_name: &'static str,
+) -> Result<HashMap<String, V>, Error> {
+    row.columns()
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| !columns.contains(&column.name()))
+        .map(|(idx, column)| {
+            let value = V::from_column(row, idx, column.name(), type_name)?;
+
+            Ok((column.name().to_string(), value))
+        })
+        .collect()
+}
@@ -0,0 +1,715 @@
+//! Query-and-map extension methods, enabled with the `client` feature.
+//!
+//! [`ClientExt`] is implemented for every `tokio_postgres::GenericClient`, so a
+//! query and the mapping of its rows are a single call returning this crate's
+//! [`Error`], whether run on a `Client`, a `Transaction`, or a pooled
+//! connection dereferencing to a `Client`:
+//!
+//! ```
+//!     use tokio_pg_mapper::ClientExt;
+//!
+//!     let users: Vec<User> = client
+//!         .query_as("SELECT id, email FROM users WHERE active = $1", &[&true])
+//!         .await?;
+//! ```
+//!
+//! [`ClientExt`]: trait.ClientExt.html
+//! [`Error`]: ../enum.Error.html
+
+use crate::{
+    capture_backtrace, quote_identifier, try_get_column, Error, FromTokioPostgresRow,
+    FromTokioPostgresRows, TokioPostgresInsert,
+};
+
+use futures_util::future::{try_join, try_join3, try_join4};
+use futures_util::stream::Stream;
+use futures_util::{pin_mut, TryStreamExt};
+use tokio_postgres::types::{FromSql, ToSql};
+use tokio_postgres::{GenericClient, RowStream, Statement, ToStatement, Transaction};
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Extension trait running queries and mapping the returned rows.
+///
+/// The returned futures are `Send`, so they can be used in `tokio::spawn`.
+pub trait ClientExt {
+    /// Runs a query, either a `&str` or a prepared `&Statement`, and maps
+    /// every returned row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the first error
+    /// encountered by [`FromTokioPostgresRow::from_rows`].
+    ///
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_rows`]: ../trait.FromTokioPostgresRow.html#method.from_rows
+    fn query_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Vec<T>, Error>> + Send
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send;
+
+    /// Runs a query expected to return exactly one row, and maps it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoRows`] if the query returned no rows, and
+    /// [`Error::TooManyRows`] if it returned more than one.
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the error encountered
+    /// by [`FromTokioPostgresRow::from_row`].
+    ///
+    /// [`Error::NoRows`]: ../enum.Error.html#variant.NoRows
+    /// [`Error::TooManyRows`]: ../enum.Error.html#variant.TooManyRows
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_row`]: ../trait.FromTokioPostgresRow.html#tymethod.from_row
+    fn query_one_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<T, Error>> + Send
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send;
+
+    /// Runs a query expected to return zero or one row, and maps it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TooManyRows`] if the query returned more than one
+    /// row.
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the error encountered
+    /// by [`FromTokioPostgresRow::from_row`].
+    ///
+    /// [`Error::TooManyRows`]: ../enum.Error.html#variant.TooManyRows
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_row`]: ../trait.FromTokioPostgresRow.html#tymethod.from_row
+    fn query_opt_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Option<T>, Error>> + Send
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send;
+
+    /// Runs a query and converts all the returned rows into a single value,
+    /// such as a `Vec`, an `Option` or a `HashMap` of them, or a type
+    /// aggregating them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the error encountered
+    /// by [`FromTokioPostgresRows::from_rows`].
+    ///
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRows::from_rows`]: ../trait.FromTokioPostgresRows.html#tymethod.from_rows
+    fn query_rows_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<T, Error>> + Send
+    where
+        T: FromTokioPostgresRows,
+        S: ?Sized + ToStatement + Sync + Send;
+
+    /// Runs a query and returns a stream mapping the rows as they arrive,
+    /// rather than buffering the whole result.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed. The stream yields
+    /// [`Error::Db`] for rows which failed to be read, and the errors
+    /// encountered by [`FromTokioPostgresRow::from_row`].
+    ///
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_row`]: ../trait.FromTokioPostgresRow.html#tymethod.from_row
+    fn query_as_stream<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<MappedRowStream<T>, Error>> + Send
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send;
+
+    /// Runs a query and maps one page of its rows, using keyset pagination
+    /// on the given column of the mapping.
+    ///
+    /// The query must not be ordered or limited, as it's wrapped into
+    /// "SELECT * FROM (query) AS page WHERE key > $n ORDER BY key LIMIT $m",
+    /// with `after` and `limit` appended to the given parameters. The
+    /// returned [`Page`] holds the key of its last row as the cursor for the
+    /// next page.
+    ///
+    /// A `limit` of 0 returns an empty page whose cursor is `after` itself,
+    /// so that `has_more` tells whether any row follows it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the key column is not part of the
+    /// mapping, so unknown names never reach the generated SQL.
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the first error
+    /// encountered by [`FromTokioPostgresRow::from_rows`].
+    ///
+    /// [`Page`]: struct.Page.html
+    /// [`Error::ColumnNotFound`]: ../enum.Error.html#variant.ColumnNotFound
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_rows`]: ../trait.FromTokioPostgresRow.html#method.from_rows
+    fn query_page<T, K>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        key_column: &str,
+        after: Option<K>,
+        limit: u32,
+    ) -> impl Future<Output = Result<Page<T, K>, Error>> + Send
+    where
+        T: FromTokioPostgresRow,
+        K: for<'a> FromSql<'a> + ToSql + Sync + Send;
+
+    /// Runs a statement, returning the number of rows modified.
+    ///
+    /// This is `execute` returning this crate's [`Error`], named so that it
+    /// doesn't clash with `GenericClient::execute`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the statement failed.
+    ///
+    /// [`Error`]: ../enum.Error.html
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    fn exec<S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<u64, Error>> + Send
+    where
+        S: ?Sized + ToStatement + Sync + Send;
+}
+
+impl<C> ClientExt for C
+where
+    C: GenericClient + Sync,
+{
+    async fn query_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send,
+    {
+        let rows = self.query(statement, params).await?;
+
+        T::from_rows(rows)
+    }
+
+    async fn query_one_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<T, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send,
+    {
+        self.query_opt_as(statement, params)
+            .await?
+            .ok_or(Error::NoRows)
+    }
+
+    async fn query_opt_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send,
+    {
+        // Like `query_opt`, stop reading at the second row instead of
+        // buffering the whole result.
+        let params = params.iter().map(|param| *param as &dyn ToSql);
+        let stream = self.query_raw(statement, params).await?;
+        pin_mut!(stream);
+
+        let row = match stream.try_next().await? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        if stream.try_next().await?.is_some() {
+            return Err(Error::TooManyRows);
+        }
+
+        T::from_row(row).map(Some)
+    }
+
+    async fn query_rows_as<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<T, Error>
+    where
+        T: FromTokioPostgresRows,
+        S: ?Sized + ToStatement + Sync + Send,
+    {
+        let rows = self.query(statement, params).await?;
+
+        T::from_rows(&rows)
+    }
+
+    async fn query_as_stream<T, S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<MappedRowStream<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement + Sync + Send,
+    {
+        let params = params.iter().map(|param| *param as &dyn ToSql);
+        let rows = self.query_raw(statement, params).await?;
+
+        Ok(MappedRowStream {
+            rows: Box::pin(rows),
+            _marker: PhantomData,
+        })
+    }
+
+    async fn query_page<T, K>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        key_column: &str,
+        after: Option<K>,
+        limit: u32,
+    ) -> Result<Page<T, K>, Error>
+    where
+        T: FromTokioPostgresRow,
+        K: for<'a> FromSql<'a> + ToSql + Sync + Send,
+    {
+        let key_column = match T::columns().iter().find(|c| **c == key_column) {
+            Some(column) => *column,
+            None => {
+                return Err(Error::ColumnNotFound {
+                    column: key_column.to_string(),
+                    type_name: Some(std::any::type_name::<T>()),
+                    backtrace: capture_backtrace(),
+                })
+            }
+        };
+
+        let quoted_key = quote_identifier(key_column);
+
+        // Fetch one extra row to find out whether there is a next page.
+        let fetch_limit = i64::from(limit) + 1;
+        let mut page_params = params.to_vec();
+
+        let condition = match after {
+            Some(ref after) => {
+                page_params.push(after);
+                format!("WHERE {} > ${}", quoted_key, page_params.len())
+            }
+            None => String::new(),
+        };
+        page_params.push(&fetch_limit);
+
+        let statement = format!(
+            "SELECT * FROM ({}) AS page {} ORDER BY {} LIMIT ${}",
+            query,
+            condition,
+            quoted_key,
+            page_params.len(),
+        );
+        let mut rows = self.query(statement.as_str(), &page_params).await?;
+
+        let has_more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+
+        // The cursor is read into `Page::next_cursor`, which errors name as
+        // the field.
+        let type_name = std::any::type_name::<Page<T, K>>();
+        let next_cursor = match rows.last() {
+            Some(row) if has_more => {
+                Some(try_get_column(row, key_column, "next_cursor", type_name)?)
+            }
+            // An empty page doesn't move the cursor.
+            None if limit == 0 => after,
+            _ => None,
+        };
+
+        Ok(Page {
+            items: T::from_rows(rows)?,
+            has_more,
+            next_cursor,
+        })
+    }
+
+    async fn exec<S>(
+        &self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error>
+    where
+        S: ?Sized + ToStatement + Sync + Send,
+    {
+        Ok(self.execute(statement, params).await?)
+    }
+}
+
+/// The maximum number of parameters of a Postgres statement.
+const MAX_PARAMS: usize = 65535;
+
+/// The name of the savepoint taken by `insert_many` before each chunk.
+const SAVEPOINT: &str = "tokio_pg_mapper_insert_many";
+
+/// Inserts the given values within a transaction, returning the number of
+/// rows inserted.
+///
+/// Values are inserted with one multi-row [`TokioPostgresInsert::insert_stmt`]
+/// per chunk, sized to stay under the Postgres limit of 65535 parameters per
+/// statement. Chunks are run in turn, each within a savepoint of the
+/// transaction, which the caller commits or rolls back.
+///
+/// When a chunk fails, the transaction is rolled back to the savepoint taken
+/// before it, keeping the previous chunks, and the values of the chunk are
+/// inserted again one at a time within another savepoint, rolled back as
+/// well, to locate the failed one. The transaction can then still be used.
+///
+/// # Errors
+///
+/// Returns [`Error::NoInsertColumns`] before running any statement if the
+/// mapping has no columns to insert.
+///
+/// Returns [`Error::Batch`] naming the failed chunk, the index of its first
+/// value and the index of the failed value, if located, wrapping the
+/// [`Error::Db`] it failed with.
+///
+/// [`TokioPostgresInsert::insert_stmt`]: ../trait.TokioPostgresInsert.html#method.insert_stmt
+/// [`Error::NoInsertColumns`]: ../enum.Error.html#variant.NoInsertColumns
+/// [`Error::Batch`]: ../enum.Error.html#variant.Batch
+/// [`Error::Db`]: ../enum.Error.html#variant.Db
+pub async fn insert_many<T>(
+    transaction: &mut Transaction<'_>,
+    values: &[T],
+) -> Result<u64, Error>
+where
+    T: TokioPostgresInsert + Sync,
+{
+    if T::insert_columns().is_empty() {
+        return Err(Error::NoInsertColumns {
+            type_name: std::any::type_name::<T>(),
+        });
+    }
+
+    let chunk_size = (MAX_PARAMS / T::insert_columns().len()).max(1);
+    let mut inserted = 0;
+
+    for (chunk, rows) in values.chunks(chunk_size).enumerate() {
+        let savepoint = transaction.savepoint(SAVEPOINT).await?;
+
+        let statement = T::insert_stmt(rows.len());
+        let params = rows
+            .iter()
+            .flat_map(|row| row.to_params())
+            .collect::<Vec<&(dyn ToSql + Sync)>>();
+
+        match savepoint.execute(statement.as_str(), &params).await {
+            Ok(count) => {
+                savepoint.commit().await?;
+                inserted += count;
+            }
+            Err(err) => {
+                // The chunk's error is the one reported, whether the failed
+                // value is located or not.
+                let row = match savepoint.rollback().await {
+                    Ok(()) => failed_row(transaction, rows).await,
+                    Err(_) => None,
+                };
+                let offset = chunk * chunk_size;
+
+                return Err(Error::Batch {
+                    chunk,
+                    offset,
+                    row: row.map(|row| offset + row),
+                    source: Box::new(err.into()),
+                });
+            }
+        }
+    }
+
+    Ok(inserted)
+}
+
+/// Inserts the given values of a failed chunk one at a time within a
+/// savepoint, returning the index of the first one failing, and rolls the
+/// savepoint back so none of them is kept.
+///
+/// The value isn't located if the transaction can't be used anymore.
+async fn failed_row<T>(transaction: &mut Transaction<'_>, rows: &[T]) -> Option<usize>
+where
+    T: TokioPostgresInsert + Sync,
+{
+    let savepoint = transaction.savepoint(SAVEPOINT).await.ok()?;
+
+    let statement = T::insert_stmt(1);
+    let mut failed = None;
+    for (idx, row) in rows.iter().enumerate() {
+        if savepoint
+            .execute(statement.as_str(), &row.to_params())
+            .await
+            .is_err()
+        {
+            failed = Some(idx);
+            break;
+        }
+    }
+
+    let _ = savepoint.rollback().await;
+
+    failed
+}
+
+macro_rules! impl_query_as_join {
+    ($(#[$attr:meta])* $name:ident, $join:ident, $($ty:ident $query:ident),+) => {
+        $(#[$attr])*
+        pub async fn $name<$($ty,)+ C>(
+            client: &C,
+            $($query: (&str, &[&(dyn ToSql + Sync)]),)+
+        ) -> Result<($(Vec<$ty>,)+), Error>
+        where
+            $($ty: FromTokioPostgresRow,)+
+            C: GenericClient + Sync,
+        {
+            $join($(client.query_as::<$ty, str>($query.0, $query.1)),+).await
+        }
+    };
+}
+
+impl_query_as_join!(
+    /// Runs two queries concurrently on one connection, and maps the rows of
+    /// each into its own type.
+    ///
+    /// `tokio-postgres` pipelines queries polled concurrently, so this takes
+    /// about one round trip rather than two.
+    ///
+    /// Example:
+    ///
+    /// ```
+    ///     let (users, orders) = query_as_join2::<User, Order, _>(
+    ///         &client,
+    ///         ("SELECT id, email FROM users WHERE id = $1", &[&user_id]),
+    ///         ("SELECT id, total FROM orders WHERE user_id = $1", &[&user_id]),
+    ///     )
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by [`ClientExt::query_as`].
+    ///
+    /// [`ClientExt::query_as`]: trait.ClientExt.html#tymethod.query_as
+    query_as_join2, try_join, A a, B b
+);
+
+impl_query_as_join!(
+    /// Runs three queries concurrently on one connection, and maps the rows
+    /// of each into its own type.
+    ///
+    /// See [`query_as_join2`].
+    ///
+    /// [`query_as_join2`]: fn.query_as_join2.html
+    query_as_join3, try_join3, A a, B b, C2 c
+);
+
+impl_query_as_join!(
+    /// Runs four queries concurrently on one connection, and maps the rows
+    /// of each into its own type.
+    ///
+    /// See [`query_as_join2`].
+    ///
+    /// [`query_as_join2`]: fn.query_as_join2.html
+    query_as_join4, try_join4, A a, B b, C2 c, D d
+);
+
+/// One page of mapped rows, as returned by [`ClientExt::query_page`].
+///
+/// [`ClientExt::query_page`]: trait.ClientExt.html#tymethod.query_page
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Page<T, K> {
+    /// The mapped rows of the page.
+    pub items: Vec<T>,
+    /// Whether there are rows after this page.
+    pub has_more: bool,
+    /// The key of the last row of the page, to pass as `after` to fetch the
+    /// next page, if there is one.
+    pub next_cursor: Option<K>,
+}
+
+/// A stream of mapped rows, as returned by [`ClientExt::query_as_stream`].
+///
+/// It doesn't borrow from the client, so it can be moved into a spawned task.
+///
+/// [`ClientExt::query_as_stream`]: trait.ClientExt.html#tymethod.query_as_stream
+pub struct MappedRowStream<T> {
+    rows: Pin<Box<RowStream>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> MappedRowStream<T> {
+    /// Get the number of rows affected by the query, once the stream is
+    /// exhausted.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows.rows_affected()
+    }
+}
+
+impl<T: FromTokioPostgresRow> Stream for MappedRowStream<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.rows.as_mut().poll_next(cx).map(|row| {
+            row.map(|row| row.map_err(Error::from).and_then(T::from_row))
+        })
+    }
+}
+
+/// A prepared statement bundled with the type its rows are mapped into.
+///
+/// The columns of the statement are checked against the mapping when it is
+/// prepared, so a mismatched query fails at startup rather than on its first
+/// row. Like any `Statement`, it must only be used with the connection which
+/// prepared it.
+///
+/// Example:
+///
+/// ```
+///     let active_users = Mapper::<User>::prepare(
+///         &client,
+///         "SELECT id, email FROM users WHERE active = $1",
+///     )
+///     .await?;
+///
+///     let users = active_users.query(&client, &[&true]).await?;
+/// ```
+#[derive(Debug)]
+pub struct Mapper<T> {
+    statement: Statement,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Mapper<T> {
+    fn clone(&self) -> Self {
+        Self {
+            statement: self.statement.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: FromTokioPostgresRow> Mapper<T> {
+    /// Prepares a statement whose rows are mapped into `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the statement couldn't be prepared.
+    ///
+    /// Returns [`Error::ColumnNotFound`] naming the first column of the
+    /// mapping which the statement doesn't return.
+    ///
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`Error::ColumnNotFound`]: ../enum.Error.html#variant.ColumnNotFound
+    pub async fn prepare<C>(client: &C, query: &str) -> Result<Self, Error>
+    where
+        C: GenericClient + Sync,
+    {
+        let statement = client.prepare(query).await?;
+
+        let missing = T::columns().iter().find(|column| {
+            !statement
+                .columns()
+                .iter()
+                .any(|statement_column| statement_column.name() == **column)
+        });
+
+        if let Some(column) = missing {
+            return Err(Error::ColumnNotFound {
+                column: column.to_string(),
+                type_name: Some(std::any::type_name::<T>()),
+                backtrace: capture_backtrace(),
+            });
+        }
+
+        Ok(Self {
+            statement,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Get the prepared statement.
+    pub fn statement(&self) -> &Statement {
+        &self.statement
+    }
+
+    /// Runs the statement and maps every returned row.
+    ///
+    /// See [`ClientExt::query_as`].
+    ///
+    /// [`ClientExt::query_as`]: trait.ClientExt.html#tymethod.query_as
+    pub async fn query<C>(
+        &self,
+        client: &C,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, Error>
+    where
+        C: GenericClient + Sync,
+    {
+        client.query_as(&self.statement, params).await
+    }
+
+    /// Runs the statement, expecting exactly one row, and maps it.
+    ///
+    /// See [`ClientExt::query_one_as`].
+    ///
+    /// [`ClientExt::query_one_as`]: trait.ClientExt.html#tymethod.query_one_as
+    pub async fn query_one<C>(
+        &self,
+        client: &C,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<T, Error>
+    where
+        C: GenericClient + Sync,
+    {
+        client.query_one_as(&self.statement, params).await
+    }
+
+    /// Runs the statement, expecting zero or one row, and maps it.
+    ///
+    /// See [`ClientExt::query_opt_as`].
+    ///
+    /// [`ClientExt::query_opt_as`]: trait.ClientExt.html#tymethod.query_opt_as
+    pub async fn query_opt<C>(
+        &self,
+        client: &C,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<T>, Error>
+    where
+        C: GenericClient + Sync,
+    {
+        client.query_opt_as(&self.statement, params).await
+    }
+}
@@ -0,0 +1,299 @@
+//! Generation of mapped structs from the schema of a live database, enabled
+//! with the `client` feature.
+//!
+//! [`generate_structs`] reads `information_schema` and returns the source of
+//! one `#[derive(PostgresMapper)]` struct per table, which a build script or
+//! a small binary can write to a file:
+//!
+//! ```
+//!     use tokio_pg_mapper::StructGenerator;
+//!
+//!     let source = StructGenerator::new()
+//!         .type_override("users", "id", "uuid::Uuid")
+//!         .generate(&client, &["public"])
+//!         .await?;
+//!
+//!     std::fs::write("src/models.rs", source)?;
+//! ```
+//!
+//! Columns are mapped to Rust types by the name of their Postgres type:
+//!
+//! | Postgres                             | Rust                             |
+//! |--------------------------------------|----------------------------------|
+//! | `bool`                               | `bool`                           |
+//! | `"char"`                             | `i8`                             |
+//! | `int2`, `int4`, `int8`               | `i16`, `i32`, `i64`              |
+//! | `float4`, `float8`                   | `f32`, `f64`                     |
+//! | `oid`                                | `u32`                            |
+//! | `text`, `varchar`, `bpchar`, `name`  | `String`                         |
+//! | `bytea`                              | `Vec<u8>`                        |
+//! | `inet`                               | `std::net::IpAddr`               |
+//! | `timestamp`, `timestamptz`           | `std::time::SystemTime`          |
+//! | `uuid`                               | `uuid::Uuid`                     |
+//! | `json`, `jsonb`                      | `Json<serde_json::Value>`        |
+//! | `numeric`, `money`, `interval`, ...  | `PgNumeric`, `PgMoney`, ...      |
+//!
+//! With the `chrono` feature, `timestamptz`, `timestamp`, `date` and `time`
+//! are mapped to `DateTime<Utc>`, `NaiveDateTime`, `NaiveDate` and
+//! `NaiveTime`. Arrays are mapped to a `Vec` of their element type, and
+//! nullable columns to an `Option`. The columns of any other type are mapped
+//! to `String` with a `FIXME` comment, unless overridden.
+//!
+//! [`generate_structs`]: fn.generate_structs.html
+
+use crate::Error;
+
+use tokio_postgres::GenericClient;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Generates the mapped structs of every table in the given schemas, with
+/// the default type mapping.
+///
+/// # Errors
+///
+/// Returns [`Error::Db`] if the query of the schemas failed.
+///
+/// [`Error::Db`]: enum.Error.html#variant.Db
+pub async fn generate_structs(
+    client: &impl GenericClient,
+    schemas: &[&str],
+) -> Result<String, Error> {
+    StructGenerator::new().generate(client, schemas).await
+}
+
+/// Builder of the source of mapped structs, overriding the Rust types of
+/// some columns.
+#[derive(Clone, Debug, Default)]
+pub struct StructGenerator {
+    overrides: HashMap<(String, String), String>,
+}
+
+impl StructGenerator {
+    /// Creates a generator with the default type mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps a column to the given Rust type, such as a wrapper the default
+    /// mapping can't infer. The table may be qualified by its schema.
+    ///
+    /// The type replaces the whole field type, so a nullable column must be
+    /// given an `Option`.
+    pub fn type_override(mut self, table: &str, column: &str, rust_type: &str) -> Self {
+        self.overrides.insert(
+            (table.to_string(), column.to_string()),
+            rust_type.to_string(),
+        );
+
+        self
+    }
+
+    /// Generates the mapped structs of every table in the given schemas,
+    /// ordered by schema and table name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query of the schemas failed.
+    ///
+    /// [`Error::Db`]: enum.Error.html#variant.Db
+    pub async fn generate(
+        &self,
+        client: &impl GenericClient,
+        schemas: &[&str],
+    ) -> Result<String, Error> {
+        let rows = client
+            .query(
+                "SELECT c.table_schema::text, c.table_name::text, c.column_name::text, \
+                     c.is_nullable = 'YES', c.udt_name::text, \
+                     EXISTS ( \
+                         SELECT 1 \
+                         FROM information_schema.table_constraints t \
+                         JOIN information_schema.key_column_usage k \
+                             USING (constraint_schema, constraint_name) \
+                         WHERE t.constraint_type = 'PRIMARY KEY' \
+                             AND k.table_schema = c.table_schema \
+                             AND k.table_name = c.table_name \
+                             AND k.column_name = c.column_name \
+                     ) \
+                 FROM information_schema.columns c \
+                 WHERE c.table_schema::text = ANY($1::text[]) \
+                 ORDER BY c.table_schema, c.table_name, c.ordinal_position",
+                &[&schemas],
+            )
+            .await?;
+
+        let mut out = String::from("use tokio_pg_mapper::PostgresMapper;\n");
+        let mut current = None;
+
+        for row in &rows {
+            let schema: &str = row.get(0);
+            let table: &str = row.get(1);
+            let column: &str = row.get(2);
+
+            let qualified = if schema == "public" {
+                table.to_string()
+            } else {
+                format!("{}.{}", schema, table)
+            };
+
+            if current.as_ref() != Some(&qualified) {
+                if current.is_some() {
+                    out.push_str("}\n");
+                }
+                out.push_str("\n#[derive(Debug, PostgresMapper)]\n");
+                let _ = writeln!(out, "#[pg_mapper(table = {:?})]", qualified);
+                let _ = writeln!(out, "pub struct {} {{", struct_name(table));
+                current = Some(qualified.clone());
+            }
+
+            let field = field_name(column);
+            if row.get::<_, bool>(5) {
+                out.push_str("    #[pg_mapper(primary_key)]\n");
+            }
+            if field != column {
+                let _ = writeln!(out, "    #[pg_mapper(rename = {:?})]", column);
+            }
+
+            let overridden = self
+                .overrides
+                .get(&(qualified.clone(), column.to_string()))
+                .or_else(|| {
+                    self.overrides.get(&(table.to_string(), column.to_string()))
+                });
+            let rust_type = match overridden {
+                Some(rust_type) => rust_type.clone(),
+                None => {
+                    let udt_name: &str = row.get(4);
+                    let rust_type = match rust_type(udt_name) {
+                        Some(rust_type) => rust_type,
+                        None => {
+                            let _ = writeln!(
+                                out,
+                                "    // FIXME: no known Rust type for {}",
+                                udt_name
+                            );
+                            "String".to_string()
+                        }
+                    };
+
+                    if row.get::<_, bool>(3) {
+                        format!("Option<{}>", rust_type)
+                    } else {
+                        rust_type
+                    }
+                }
+            };
+
+            let _ = writeln!(out, "    pub {}: {},", field, rust_type);
+        }
+
+        if current.is_some() {
+            out.push_str("}\n");
+        }
+
+        Ok(out)
+    }
+}
+
+/// Get the Rust type of a Postgres type, by the name given by `udt_name`.
+fn rust_type(udt_name: &str) -> Option<String> {
+    if let Some(element) = udt_name.strip_prefix('_') {
+        return rust_type(element).map(|element| format!("Vec<{}>", element));
+    }
+
+    let rust_type = match udt_name {
+        "bool" => "bool",
+        "char" => "i8",
+        "int2" => "i16",
+        "int4" => "i32",
+        "int8" => "i64",
+        "float4" => "f32",
+        "float8" => "f64",
+        "oid" => "u32",
+        "text" | "varchar" | "bpchar" | "name" | "citext" => "String",
+        "bytea" => "Vec<u8>",
+        "inet" => "std::net::IpAddr",
+        "uuid" => "uuid::Uuid",
+        "json" | "jsonb" => "tokio_pg_mapper::Json<serde_json::Value>",
+        "numeric" => "tokio_pg_mapper::PgNumeric",
+        "money" => "tokio_pg_mapper::PgMoney",
+        "interval" => "tokio_pg_mapper::PgInterval",
+        "hstore" => "tokio_pg_mapper::PgHstore",
+        "ltree" => "tokio_pg_mapper::PgLtree",
+        "lquery" => "tokio_pg_mapper::PgLquery",
+        "xml" => "tokio_pg_mapper::PgXml",
+        "bit" | "varbit" => "tokio_pg_mapper::PgBits",
+        "macaddr" => "tokio_pg_mapper::PgMacAddr",
+        "macaddr8" => "tokio_pg_mapper::PgMacAddr8",
+        "point" => "tokio_pg_mapper::PgPoint",
+        "box" => "tokio_pg_mapper::PgBox",
+        "path" => "tokio_pg_mapper::PgPath",
+        "tsvector" => "tokio_pg_mapper::PgTsVector",
+        "tsquery" => "tokio_pg_mapper::PgTsQuery",
+        "timestamptz" if cfg!(feature = "chrono") => "chrono::DateTime<chrono::Utc>",
+        "timestamp" if cfg!(feature = "chrono") => "chrono::NaiveDateTime",
+        "date" if cfg!(feature = "chrono") => "chrono::NaiveDate",
+        "time" if cfg!(feature = "chrono") => "chrono::NaiveTime",
+        "timestamptz" | "timestamp" => "std::time::SystemTime",
+        _ => return None,
+    };
+
+    Some(rust_type.to_string())
+}
+
+/// Get the name of the struct of a table, in upper camel case.
+fn struct_name(table: &str) -> String {
+    let mut name = String::new();
+    let mut upper = true;
+
+    for c in table.chars() {
+        if !c.is_ascii_alphanumeric() {
+            upper = true;
+        } else if upper {
+            name.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            name.push(c);
+        }
+    }
+
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'T');
+    }
+
+    name
+}
+
+/// Get a valid field name for a column, which is renamed if they differ.
+fn field_name(column: &str) -> String {
+    let mut name = column
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+
+    name
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override",
+    "priv", "pub", "ref", "return", "self", "static", "struct", "super", "trait",
+    "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+    "while", "yield",
+];
@@ -0,0 +1,231 @@
+//! Support for the `FromSql` and `ToSql` implementations derived for
+//! composite types with `#[pg_mapper(composite = "...")]`.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{Field, FromSql, IsNull, Kind, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Get the attributes of a composite type with the given name and number of
+/// attributes, on behalf of derive-generated `accepts` implementations.
+#[doc(hidden)]
+pub fn composite_fields<'t>(
+    ty: &'t Type,
+    name: &str,
+    len: usize,
+) -> Option<&'t [Field]> {
+    if ty.name() != name {
+        return None;
+    }
+
+    match ty.kind() {
+        Kind::Composite(fields) if fields.len() == len => Some(fields),
+        _ => None,
+    }
+}
+
+/// Reader of the attributes of a composite value in the binary format, on
+/// behalf of derive-generated `FromSql` implementations.
+#[doc(hidden)]
+pub struct CompositeReader<'t, 'a> {
+    fields: &'t [Field],
+    raw: &'a [u8],
+    idx: usize,
+}
+
+impl<'t, 'a> CompositeReader<'t, 'a> {
+    pub fn new(
+        ty: &'t Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let fields = match ty.kind() {
+            Kind::Composite(fields) => fields,
+            _ => return Err(format!("{} is not a composite type", ty).into()),
+        };
+
+        if raw.len() < 4 {
+            return Err("invalid composite length".into());
+        }
+        let count = raw.get_i32();
+        if count < 0 || count as usize != fields.len() {
+            return Err(format!(
+                "expected {} composite attributes, got {}",
+                fields.len(),
+                count
+            )
+            .into());
+        }
+
+        Ok(CompositeReader {
+            fields,
+            raw,
+            idx: 0,
+        })
+    }
+
+    /// Reads the next attribute.
+    pub fn read<T: FromSql<'a>>(
+        &mut self,
+    ) -> Result<T, Box<dyn StdError + Sync + Send>> {
+        let field = self
+            .fields
+            .get(self.idx)
+            .ok_or("too few composite attributes")?;
+        self.idx += 1;
+
+        self.read_field(field).map_err(|source| {
+            Box::new(CompositeAttributeError {
+                attribute: field.name().to_string(),
+                source,
+            }) as Box<dyn StdError + Sync + Send>
+        })
+    }
+
+    fn read_field<T: FromSql<'a>>(
+        &mut self,
+        field: &Field,
+    ) -> Result<T, Box<dyn StdError + Sync + Send>> {
+        if self.raw.len() < 8 {
+            return Err("invalid composite length".into());
+        }
+
+        let oid = self.raw.get_u32();
+        if oid != field.type_().oid() {
+            return Err(format!("unexpected type oid {}", oid).into());
+        }
+
+        let len = self.raw.get_i32();
+        if len < 0 {
+            return T::from_sql_null(field.type_());
+        }
+
+        let len = len as usize;
+        if self.raw.len() < len {
+            return Err("invalid composite length".into());
+        }
+        let (value, rest) = self.raw.split_at(len);
+        self.raw = rest;
+
+        T::from_sql(field.type_(), value)
+    }
+
+    /// Checks that every attribute was read.
+    pub fn finish(self) -> Result<(), Box<dyn StdError + Sync + Send>> {
+        if self.idx != self.fields.len() || !self.raw.is_empty() {
+            return Err("invalid composite length".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Writer of the attributes of a composite value in the binary format, on
+/// behalf of derive-generated `ToSql` implementations.
+#[doc(hidden)]
+pub struct CompositeWriter<'t, 'o> {
+    fields: &'t [Field],
+    out: &'o mut BytesMut,
+    idx: usize,
+}
+
+impl<'t, 'o> CompositeWriter<'t, 'o> {
+    pub fn new(
+        ty: &'t Type,
+        out: &'o mut BytesMut,
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let fields = match ty.kind() {
+            Kind::Composite(fields) => fields,
+            _ => return Err(format!("{} is not a composite type", ty).into()),
+        };
+
+        out.put_i32(fields.len() as i32);
+
+        Ok(CompositeWriter {
+            fields,
+            out,
+            idx: 0,
+        })
+    }
+
+    /// Writes the next attribute, as NULL if `value.to_sql` reports it as
+    /// such.
+    pub fn write<T: ToSql>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Box<dyn StdError + Sync + Send>> {
+        let field = self
+            .fields
+            .get(self.idx)
+            .ok_or("too many composite attributes")?;
+        self.idx += 1;
+
+        self.out.put_u32(field.type_().oid());
+
+        // Reserve the length, written once the value is encoded.
+        let start = self.out.len();
+        self.out.put_i32(0);
+
+        let is_null = value
+            .to_sql_checked(field.type_(), self.out)
+            .map_err(|source| {
+                Box::new(CompositeAttributeError {
+                    attribute: field.name().to_string(),
+                    source,
+                }) as Box<dyn StdError + Sync + Send>
+            })?;
+
+        let len = match is_null {
+            IsNull::Yes => {
+                self.out.truncate(start + 4);
+                -1
+            }
+            IsNull::No => i32::try_from(self.out.len() - start - 4)
+                .map_err(|_| "composite attribute too long")?,
+        };
+        self.out[start..start + 4].copy_from_slice(&len.to_be_bytes());
+
+        Ok(())
+    }
+
+    /// Checks that every attribute was written.
+    pub fn finish(self) -> Result<(), Box<dyn StdError + Sync + Send>> {
+        if self.idx != self.fields.len() {
+            return Err("too few composite attributes".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// The error converting an attribute of a composite value, naming the
+/// attribute.
+#[derive(Debug)]
+pub struct CompositeAttributeError {
+    attribute: String,
+    source: Box<dyn StdError + Sync + Send>,
+}
+
+impl CompositeAttributeError {
+    /// Get the name of the attribute which couldn't be converted.
+    pub fn attribute(&self) -> &str {
+        &self.attribute
+    }
+}
+
+impl Display for CompositeAttributeError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "error converting composite attribute \"{}\"",
+            self.attribute
+        )
+    }
+}
+
+impl StdError for CompositeAttributeError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
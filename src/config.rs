@@ -0,0 +1,98 @@
+//! The `MapperConfig` runtime configuration of mappings.
+
+use tokio_postgres::row::Row as TokioRow;
+
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+/// Runtime configuration of the mappings of [`from_row_with_config`] and
+/// [`from_rows_with_config`], for schemas whose conventions vary between
+/// deployments rather than being known at compile time.
+///
+/// A column name transformation is applied to the name of every mapped
+/// column, as declared by the field or its `rename` attribute, before
+/// looking it up in a row:
+///
+/// ```
+///     use std::borrow::Cow;
+///     use tokio_pg_mapper::{FromTokioPostgresRow, MapperConfig};
+///
+///     // Some deployments of the partner database uppercase every column.
+///     let config = MapperConfig::new()
+///         .column_name_transform(|name| Cow::Owned(name.to_uppercase()));
+///
+///     let user = User::from_row_with_config(&row, &config)?;
+/// ```
+///
+/// The default configuration has no transformation, and its mappings are
+/// those of [`from_row_ref`].
+///
+/// [`from_row_with_config`]: trait.FromTokioPostgresRow.html#method.from_row_with_config
+/// [`from_rows_with_config`]: trait.FromTokioPostgresRow.html#method.from_rows_with_config
+/// [`from_row_ref`]: trait.FromTokioPostgresRow.html#tymethod.from_row_ref
+#[derive(Clone, Copy, Default)]
+pub struct MapperConfig {
+    column_name: Option<fn(&str) -> Cow<'_, str>>,
+}
+
+impl MapperConfig {
+    /// Creates a configuration without a column name transformation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Transforms the names of the mapped columns with the given function
+    /// before looking them up in rows.
+    pub fn column_name_transform(mut self, transform: fn(&str) -> Cow<'_, str>) -> Self {
+        self.column_name = Some(transform);
+
+        self
+    }
+
+    /// Get the name the given mapped column is looked up under.
+    pub fn column_name<'a>(&self, column: &'a str) -> Cow<'a, str> {
+        match self.column_name {
+            Some(transform) => transform(column),
+            None => Cow::Borrowed(column),
+        }
+    }
+
+    /// Get whether column names are looked up unchanged.
+    pub fn is_default(&self) -> bool {
+        self.column_name.is_none()
+    }
+}
+
+impl Debug for MapperConfig {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("MapperConfig")
+            .field("column_name_transform", &self.column_name.is_some())
+            .finish()
+    }
+}
+
+/// Resolves the positions of the mapped columns in a row under the names
+/// given by a configuration, on behalf of derive-generated code.
+///
+/// Missing columns are given a position past the end of the row, so that
+/// fetching them reports an [`Error::ColumnNotFound`].
+///
+/// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+#[doc(hidden)]
+pub fn resolve_configured_column_indices(
+    row: &TokioRow,
+    columns: &[&str],
+    config: &MapperConfig,
+) -> Vec<usize> {
+    columns
+        .iter()
+        .map(|column| {
+            let name = config.column_name(column);
+
+            row.columns()
+                .iter()
+                .position(|c| c.name() == name)
+                .unwrap_or(usize::MAX)
+        })
+        .collect()
+}
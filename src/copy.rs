@@ -0,0 +1,141 @@
+//! Mapping of binary `COPY ... TO STDOUT` rows, enabled with the `copy`
+//! feature.
+//!
+//! The rows of a `BinaryCopyOutStream` have no column names, so columns are
+//! mapped by position, in field declaration order. The Postgres types of the
+//! copied columns must be supplied by the caller, for instance from the
+//! columns of the equivalent prepared `SELECT`:
+//!
+//! ```
+//!     let types = client
+//!         .prepare("SELECT id, email FROM users")
+//!         .await?
+//!         .columns()
+//!         .iter()
+//!         .map(|column| column.type_().clone())
+//!         .collect::<Vec<_>>();
+//!
+//!     let stream = client
+//!         .copy_out("COPY (SELECT id, email FROM users) TO STDOUT (FORMAT binary)")
+//!         .await?;
+//!     let users = MappedCopyOutStream::<User>::new(stream, &types);
+//! ```
+
+use crate::{capture_backtrace, AnyValue, Error};
+
+use futures_util::stream::Stream;
+use tokio_postgres::binary_copy::{BinaryCopyOutRow, BinaryCopyOutStream};
+use tokio_postgres::types::{FromSql, Type, WasNull};
+use tokio_postgres::CopyOutStream;
+
+use std::error::Error as StdError;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Trait for converting from a `tokio-postgres` `BinaryCopyOutRow` into a
+/// mapped type, by column position.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(copy)]` container attribute, this will be
+/// implemented on types, mapping columns in field declaration order.
+pub trait FromBinaryCopyOutRow: Sized {
+    /// Converts from a `tokio-postgres` `BinaryCopyOutRow` into a mapped
+    /// type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the row has fewer columns than
+    /// the mapping.
+    ///
+    /// Returns [`Error::UnexpectedNull`] if a column mapped to a non-`Option`
+    /// field was NULL.
+    ///
+    /// Returns [`Error::ColumnConversion`] if there was an error converting a
+    /// column into the requested type.
+    ///
+    /// [`Error::ColumnNotFound`]: ../enum.Error.html#variant.ColumnNotFound
+    /// [`Error::UnexpectedNull`]: ../enum.Error.html#variant.UnexpectedNull
+    /// [`Error::ColumnConversion`]: ../enum.Error.html#variant.ColumnConversion
+    fn from_binary_copy_out_row(row: &BinaryCopyOutRow) -> Result<Self, Error>;
+}
+
+/// Gets the value of a column by position on behalf of derive-generated code,
+/// reporting errors like [`try_get_column`] under the name the column has in
+/// the mapping.
+///
+/// [`try_get_column`]: ../fn.try_get_column.html
+#[doc(hidden)]
+pub fn try_get_copy_column<'a, T: FromSql<'a>>(
+    row: &'a BinaryCopyOutRow,
+    idx: usize,
+    column: &str,
+    field: &'static str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    row.try_get(idx).map_err(|err| {
+        if err.source().is_none() {
+            return Error::ColumnNotFound {
+                column: column.to_string(),
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            };
+        }
+
+        // NULL elements of arrays fail with `WasNull` too.
+        if err.source().is_some_and(|source| source.is::<WasNull>())
+            && row
+                .try_get::<Option<AnyValue>>(idx)
+                .is_ok_and(|value| value.is_none())
+        {
+            return Error::UnexpectedNull {
+                column: column.to_string(),
+                field,
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            };
+        }
+
+        Error::ColumnConversion {
+            column: column.to_string(),
+            rust_type: std::any::type_name::<T>(),
+            pg_type: None,
+            source: err.into_source().expect("error source checked above"),
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        }
+    })
+}
+
+/// A stream of rows of a binary `COPY ... TO STDOUT`, mapped by position.
+pub struct MappedCopyOutStream<T> {
+    rows: Pin<Box<BinaryCopyOutStream>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> MappedCopyOutStream<T> {
+    /// Creates a stream mapping the rows of a binary `COPY ... TO STDOUT`,
+    /// whose columns have the given types.
+    pub fn new(stream: CopyOutStream, types: &[Type]) -> Self {
+        Self {
+            rows: Box::pin(BinaryCopyOutStream::new(stream, types)),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: FromBinaryCopyOutRow> Stream for MappedCopyOutStream<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.rows.as_mut().poll_next(cx).map(|row| {
+            row.map(|row| {
+                row.map_err(Error::from)
+                    .and_then(|row| T::from_binary_copy_out_row(&row))
+            })
+        })
+    }
+}
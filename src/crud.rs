@@ -0,0 +1,417 @@
+//! Primary key based data access, enabled with the `client` feature.
+//!
+//! With the `#[pg_mapper(crud)]` container attribute, the derive implements
+//! [`TokioPostgresCrud`] for types with `#[pg_mapper(primary_key)]` fields, so
+//! fetching a row by key needs no hand-written SQL:
+//!
+//! ```
+//!     use tokio_pg_mapper::TokioPostgresCrud;
+//!
+//!     #[derive(PostgresMapper)]
+//!     #[pg_mapper(table = "user", crud)]
+//!     pub struct User {
+//!         #[pg_mapper(primary_key)]
+//!         pub id: i64,
+//!         pub email: Option<String>,
+//!     }
+//!
+//!     let user: Option<User> = User::find(&client, &1).await?;
+//!     let users: Vec<Option<User>> = User::load_many(&client, &[3, 1, 2]).await?;
+//!     let users: Vec<User> = User::list_all(&client).await?;
+//!     let user = User::insert(&client, &new_user).await?;
+//!     let updated: bool = User::update(&client, &user).await?;
+//!     let deleted: bool = User::delete(&client, &user.id).await?;
+//! ```
+//!
+//! Fields annotated with `#[pg_mapper(unique)]` also get an inherent finder
+//! named after them, such as `User::find_by_email(&client, "a@b.c")` for an
+//! `email: String` field. `String` fields are looked up by `&str`, and other
+//! fields by a reference to their type.
+//!
+//! An integer field annotated with `#[pg_mapper(version)]` is used for
+//! optimistic locking: `update` only updates a row which still has the
+//! version of the updated value, incrementing it, and otherwise fails with
+//! [`Error::StaleVersion`].
+//!
+//! [`TokioPostgresCrud`]: trait.TokioPostgresCrud.html
+//! [`Error::StaleVersion`]: ../enum.Error.html#variant.StaleVersion
+
+use crate::ident::quote_identifier;
+use crate::{
+    where_not_deleted, ClientExt, Error, MappedRowStream, TokioPostgresInsert,
+    TokioPostgresPrimaryKey,
+};
+
+use tokio_postgres::types::ToSql;
+use tokio_postgres::GenericClient;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+
+/// Trait for mapped types fetched and persisted by primary key.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(crud)]` container attribute, this will be
+/// implemented on types with at least one `#[pg_mapper(primary_key)]` field,
+/// along with [`TokioPostgresInsert`]. Every field must then be
+/// `ToSql + Sync`, and the primary key fields `Clone`.
+///
+/// The returned futures are `Send`, so they can be used in `tokio::spawn`.
+///
+/// [`TokioPostgresInsert`]: ../trait.TokioPostgresInsert.html
+pub trait TokioPostgresCrud: TokioPostgresPrimaryKey + TokioPostgresInsert {
+    /// The type of the primary key: the type of its field, or a tuple of the
+    /// types of its fields, in declaration order, for composite keys.
+    type PrimaryKey: Sync;
+
+    /// Get references to the columns of a primary key, in the order of
+    /// [`primary_key_columns`].
+    ///
+    /// [`primary_key_columns`]: ../trait.TokioPostgresPrimaryKey.html#tymethod.primary_key_columns
+    fn primary_key_params(key: &Self::PrimaryKey) -> Vec<&(dyn ToSql + Sync)>;
+
+    /// Get the primary key of a value, cloning its fields.
+    fn primary_key(&self) -> Self::PrimaryKey;
+
+    /// Get the column names assigned by [`update_stmt`], in field
+    /// declaration order: every column but the primary key, readonly columns
+    /// and the [`version_column`].
+    ///
+    /// [`update_stmt`]: #method.update_stmt
+    /// [`version_column`]: #method.version_column
+    fn update_columns() -> &'static [&'static str];
+
+    /// Get references to the fields of the [`update_columns`], followed by
+    /// the primary key fields and the field of the [`version_column`], if
+    /// any, as the parameters of [`update_stmt`].
+    ///
+    /// [`update_columns`]: #tymethod.update_columns
+    /// [`version_column`]: #method.version_column
+    /// [`update_stmt`]: #method.update_stmt
+    fn update_params(&self) -> Vec<&(dyn ToSql + Sync)>;
+
+    /// Get the name of the integer column annotated with
+    /// `#[pg_mapper(version)]`, used for optimistic locking: [`update_stmt`]
+    /// only updates the row if it still has the version of the updated
+    /// value, and increments it.
+    ///
+    /// The default implementation returns `None`.
+    ///
+    /// [`update_stmt`]: #method.update_stmt
+    fn version_column() -> Option<&'static str> {
+        None
+    }
+
+    /// Get an `UPDATE` statement assigning the [`update_columns`] of the row
+    /// with a given primary key, whose parameters are the [`update_params`].
+    ///
+    /// For the `User` type of the [module documentation] this will return the
+    /// String "UPDATE \"user\" SET email = $1 WHERE id = $2". With a
+    /// [`version_column`] named `version`, it would return the String
+    /// "UPDATE \"user\" SET email = $1, version = version + 1 WHERE id = $2
+    /// AND version = $3".
+    ///
+    /// Without update columns nor version column, the `SET` clause is empty,
+    /// which [`update`] reports as [`Error::NoUpdateColumns`].
+    ///
+    /// [module documentation]: index.html
+    /// [`update_columns`]: #tymethod.update_columns
+    /// [`update_params`]: #tymethod.update_params
+    /// [`version_column`]: #method.version_column
+    /// [`update`]: #method.update
+    /// [`Error::NoUpdateColumns`]: ../enum.Error.html#variant.NoUpdateColumns
+    fn update_stmt() -> String {
+        let columns = Self::update_columns();
+        let mut assignments = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| format!("{} = ${}", quote_identifier(column), i + 1))
+            .collect::<Vec<String>>();
+        let mut condition = Self::primary_key_condition(columns.len() + 1);
+
+        if let Some(version) = Self::version_column() {
+            let version = quote_identifier(version);
+            let placeholder = columns.len() + Self::primary_key_columns().len() + 1;
+
+            assignments.push(format!("{} = {} + 1", version, version));
+            condition = format!("{} AND {} = ${}", condition, version, placeholder);
+        }
+
+        format!(
+            "UPDATE {} SET {} WHERE {}",
+            Self::sql_table(),
+            assignments.join(", "),
+            condition,
+        )
+    }
+
+    /// Fetches the row with the given primary key, with
+    /// [`select_by_primary_key_stmt`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the error encountered
+    /// by [`FromTokioPostgresRow::from_row`].
+    ///
+    /// [`select_by_primary_key_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.select_by_primary_key_stmt
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_row`]: ../trait.FromTokioPostgresRow.html#tymethod.from_row
+    fn find<C>(
+        client: &C,
+        key: &Self::PrimaryKey,
+    ) -> impl Future<Output = Result<Option<Self>, Error>> + Send
+    where
+        C: GenericClient + Sync,
+    {
+        async move {
+            let statement = Self::select_by_primary_key_stmt();
+            let params = Self::primary_key_params(key);
+
+            client.query_opt_as(statement.as_str(), &params).await
+        }
+    }
+
+    /// Fetches every row, ordered by primary key, with [`select_all_stmt`].
+    ///
+    /// This is meant for small tables. Larger ones are better read with
+    /// [`stream_all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the first error
+    /// encountered by [`FromTokioPostgresRow::from_rows`].
+    ///
+    /// [`select_all_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.select_all_stmt
+    /// [`stream_all`]: #method.stream_all
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_rows`]: ../trait.FromTokioPostgresRow.html#method.from_rows
+    fn list_all<C>(client: &C) -> impl Future<Output = Result<Vec<Self>, Error>> + Send
+    where
+        C: GenericClient + Sync,
+    {
+        async move {
+            let statement = Self::select_all_stmt();
+
+            client.query_as(statement.as_str(), &[]).await
+        }
+    }
+
+    /// Fetches every row, ordered by primary key, with [`select_all_stmt`],
+    /// returning a stream mapping the rows as they arrive.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed. The stream yields the
+    /// errors of [`ClientExt::query_as_stream`].
+    ///
+    /// [`select_all_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.select_all_stmt
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`ClientExt::query_as_stream`]: ../trait.ClientExt.html#tymethod.query_as_stream
+    fn stream_all<C>(
+        client: &C,
+    ) -> impl Future<Output = Result<MappedRowStream<Self>, Error>> + Send
+    where
+        C: GenericClient + Sync,
+    {
+        async move {
+            let statement = Self::select_all_stmt();
+
+            client.query_as_stream(statement.as_str(), &[]).await
+        }
+    }
+
+    /// Get a `SELECT` statement for the rows whose primary key is one of the
+    /// elements of the array `$1`.
+    ///
+    /// For the `User` type of the [module documentation] this will return the
+    /// String "SELECT  id ,  email  FROM \"user\" WHERE id = ANY($1)".
+    ///
+    /// Composite keys are given as one array per key column, in the order of
+    /// [`primary_key_columns`], the keys being the elements at the same
+    /// index of every array. For a key of columns `a` and `b`, the statement
+    /// ends with "WHERE a = ANY($1) AND b = ANY($2) AND EXISTS (SELECT FROM
+    /// generate_subscripts($1, 1) AS i WHERE $1\[i\] = a AND $2\[i\] = b)".
+    ///
+    /// [module documentation]: index.html
+    /// [`primary_key_columns`]: ../trait.TokioPostgresPrimaryKey.html#tymethod.primary_key_columns
+    fn select_by_primary_keys_stmt() -> String {
+        let columns = Self::primary_key_columns()
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<_>>();
+
+        let mut condition = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| format!("{} = ANY(${})", column, i + 1))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        // The `ANY` comparisons give the arrays their types, and the pairing
+        // of their elements is checked by index.
+        if columns.len() > 1 {
+            let pairs = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| format!("${}[i] = {}", i + 1, column))
+                .collect::<Vec<_>>();
+
+            condition = format!(
+                "{} AND EXISTS (SELECT FROM generate_subscripts($1, 1) AS i WHERE {})",
+                condition,
+                pairs.join(" AND "),
+            );
+        }
+
+        format!(
+            "SELECT {} FROM {}{}",
+            Self::sql_fields(),
+            Self::sql_table(),
+            where_not_deleted::<Self>(Some(condition)),
+        )
+    }
+
+    /// Fetches the rows with the given primary keys in a single query, with
+    /// [`select_by_primary_keys_stmt`], aligned with the keys: the value at
+    /// every index is the row of the key at that index, or `None` if there
+    /// is no such row. Every value is a clone of the mapped row, so repeated
+    /// keys get their own copy.
+    ///
+    /// This is only available for single column keys, which can be bound as
+    /// an array: the tuples of composite keys aren't `ToSql`. Their rows can
+    /// be fetched by binding an array per key column to
+    /// [`select_by_primary_keys_stmt`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the first error
+    /// encountered by [`FromTokioPostgresRow::from_rows`].
+    ///
+    /// [`select_by_primary_keys_stmt`]: #method.select_by_primary_keys_stmt
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_rows`]: ../trait.FromTokioPostgresRow.html#method.from_rows
+    fn load_many<C>(
+        client: &C,
+        keys: &[Self::PrimaryKey],
+    ) -> impl Future<Output = Result<Vec<Option<Self>>, Error>> + Send
+    where
+        C: GenericClient + Sync,
+        Self: Clone,
+        Self::PrimaryKey: ToSql + Eq + Hash,
+    {
+        async move {
+            let statement = Self::select_by_primary_keys_stmt();
+            let rows: Vec<Self> = client.query_as(statement.as_str(), &[&keys]).await?;
+
+            let by_key = rows
+                .into_iter()
+                .map(|row| (row.primary_key(), row))
+                .collect::<HashMap<_, _>>();
+
+            Ok(keys.iter().map(|key| by_key.get(key).cloned()).collect())
+        }
+    }
+
+    /// Inserts a row, with [`insert_returning_stmt`], and maps the stored
+    /// row, so that ids and defaults filled in by the database are set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the error encountered
+    /// by [`FromTokioPostgresRow::from_row`].
+    ///
+    /// [`insert_returning_stmt`]: ../trait.TokioPostgresInsert.html#method.insert_returning_stmt
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_row`]: ../trait.FromTokioPostgresRow.html#tymethod.from_row
+    fn insert<C>(
+        client: &C,
+        value: &Self,
+    ) -> impl Future<Output = Result<Self, Error>> + Send
+    where
+        C: GenericClient + Sync,
+        Self: Sync,
+    {
+        async move {
+            let statement = Self::insert_returning_stmt();
+            let params = value.to_params();
+
+            client.query_one_as(statement.as_str(), &params).await
+        }
+    }
+
+    /// Updates the row with the primary key of the given value, with
+    /// [`update_stmt`], returning whether it existed.
+    ///
+    /// With a [`version_column`], the row is only updated if its version is
+    /// the one of the given value, and its version is then incremented in
+    /// the database, but not in the given value: the updated value has to be
+    /// read again, or its version incremented, before updating it again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoUpdateColumns`] before running any statement if
+    /// the mapping has no columns to update nor version column.
+    ///
+    /// Returns [`Error::StaleVersion`] with a [`version_column`] if no row
+    /// was updated, as the row with the primary key of the value has another
+    /// version or doesn't exist anymore.
+    ///
+    /// Returns [`Error::Db`] if the query failed.
+    ///
+    /// [`update_stmt`]: #method.update_stmt
+    /// [`version_column`]: #method.version_column
+    /// [`Error::NoUpdateColumns`]: ../enum.Error.html#variant.NoUpdateColumns
+    /// [`Error::StaleVersion`]: ../enum.Error.html#variant.StaleVersion
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    fn update<C>(
+        client: &C,
+        value: &Self,
+    ) -> impl Future<Output = Result<bool, Error>> + Send
+    where
+        C: GenericClient + Sync,
+        Self: Sync,
+    {
+        async move {
+            let type_name = std::any::type_name::<Self>();
+            let versioned = Self::version_column().is_some();
+            if Self::update_columns().is_empty() && !versioned {
+                return Err(Error::NoUpdateColumns { type_name });
+            }
+
+            let statement = Self::update_stmt();
+            let params = value.update_params();
+
+            let updated = client.execute(statement.as_str(), &params).await? > 0;
+            if versioned && !updated {
+                return Err(Error::StaleVersion { type_name });
+            }
+
+            Ok(updated)
+        }
+    }
+
+    /// Deletes the row with the given primary key, with
+    /// [`delete_by_primary_key_stmt`], returning whether it existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed.
+    ///
+    /// [`delete_by_primary_key_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.delete_by_primary_key_stmt
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    fn delete<C>(
+        client: &C,
+        key: &Self::PrimaryKey,
+    ) -> impl Future<Output = Result<bool, Error>> + Send
+    where
+        C: GenericClient + Sync,
+    {
+        async move {
+            let statement = Self::delete_by_primary_key_stmt();
+            let params = Self::primary_key_params(key);
+
+            Ok(client.execute(statement.as_str(), &params).await? > 0)
+        }
+    }
+}
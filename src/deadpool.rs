@@ -0,0 +1,136 @@
+//! `deadpool-postgres` integration, enabled with the `deadpool` feature.
+//!
+//! [`PoolExt`] is implemented for `deadpool_postgres::Pool`, so getting a
+//! connection, running a query and mapping its rows are a single call:
+//!
+//! ```
+//!     use tokio_pg_mapper::deadpool::PoolExt;
+//!
+//!     let users: Vec<User> = pool
+//!         .query_as("SELECT id, email FROM users WHERE active = $1", &[&true])
+//!         .await?;
+//! ```
+//!
+//! Pool errors are reported as [`Error::Pool`], except for errors of the
+//! database connection itself, which are reported as [`Error::Db`].
+//!
+//! [`PoolExt`]: trait.PoolExt.html
+//! [`Error::Pool`]: ../enum.Error.html#variant.Pool
+//! [`Error::Db`]: ../enum.Error.html#variant.Db
+
+use crate::{ClientExt, Error, FromTokioPostgresRow};
+
+use deadpool_postgres::{Pool, PoolError};
+use tokio_postgres::types::ToSql;
+
+use std::future::Future;
+
+impl From<PoolError> for Error {
+    fn from(err: PoolError) -> Self {
+        match err {
+            PoolError::Backend(err) => Error::Db(err),
+            err => Error::Pool(Box::new(err)),
+        }
+    }
+}
+
+/// Extension trait running queries on a pooled connection and mapping the
+/// returned rows.
+///
+/// Statements are prepared with `prepare_cached`, so each connection of the
+/// pool prepares a given query only once. The connection is returned to the
+/// pool as soon as the rows are mapped.
+pub trait PoolExt {
+    /// Runs a query on a pooled connection and maps every returned row.
+    ///
+    /// See [`ClientExt::query_as`].
+    ///
+    /// [`ClientExt::query_as`]: ../client/trait.ClientExt.html#tymethod.query_as
+    fn query_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Vec<T>, Error>> + Send;
+
+    /// Runs a query expected to return exactly one row on a pooled
+    /// connection, and maps it.
+    ///
+    /// See [`ClientExt::query_one_as`].
+    ///
+    /// [`ClientExt::query_one_as`]: ../client/trait.ClientExt.html#tymethod.query_one_as
+    fn query_one_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<T, Error>> + Send;
+
+    /// Runs a query expected to return zero or one row on a pooled
+    /// connection, and maps it.
+    ///
+    /// See [`ClientExt::query_opt_as`].
+    ///
+    /// [`ClientExt::query_opt_as`]: ../client/trait.ClientExt.html#tymethod.query_opt_as
+    fn query_opt_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Option<T>, Error>> + Send;
+
+    /// Runs a statement on a pooled connection, returning the number of rows
+    /// modified.
+    ///
+    /// See [`ClientExt::exec`].
+    ///
+    /// [`ClientExt::exec`]: ../client/trait.ClientExt.html#tymethod.exec
+    fn exec(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<u64, Error>> + Send;
+}
+
+impl PoolExt for Pool {
+    async fn query_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, Error> {
+        let client = self.get().await?;
+        let statement = client.prepare_cached(statement).await?;
+
+        client.query_as(&statement, params).await
+    }
+
+    async fn query_one_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<T, Error> {
+        let client = self.get().await?;
+        let statement = client.prepare_cached(statement).await?;
+
+        client.query_one_as(&statement, params).await
+    }
+
+    async fn query_opt_as<T: FromTokioPostgresRow>(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<T>, Error> {
+        let client = self.get().await?;
+        let statement = client.prepare_cached(statement).await?;
+
+        client.query_opt_as(&statement, params).await
+    }
+
+    async fn exec(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        let client = self.get().await?;
+        let statement = client.prepare_cached(statement).await?;
+
+        client.exec(&statement, params).await
+    }
+}
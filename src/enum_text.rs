@@ -0,0 +1,133 @@
+//! The `EnumText` field type.
+
+use bytes::BytesMut;
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, Kind, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter, Result as FmtResult};
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// Wrapper mapping text columns to and from any type implementing `FromStr`
+/// and `Display`, such as enums deriving `EnumString` and `Display` with
+/// `strum`.
+///
+/// Values are read from `text`, `varchar`, `char` and `name` columns as well
+/// as Postgres enums, and written as the output of `to_string`. When mapping
+/// a row, text which `T` can't parse results in an
+/// [`Error::ColumnConversion`] naming the column, with an
+/// [`InvalidEnumText`] source holding the offending text.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+/// [`InvalidEnumText`]: struct.InvalidEnumText.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct EnumText<T>(pub T);
+
+impl<T> EnumText<T> {
+    /// Unwraps the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for EnumText<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for EnumText<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for EnumText<T> {
+    fn from(value: T) -> Self {
+        EnumText(value)
+    }
+}
+
+fn accepts_text(ty: &Type) -> bool {
+    matches!(ty.kind(), Kind::Enum(_)) || <&str as FromSql>::accepts(ty)
+}
+
+impl<'a, T> FromSql<'a> for EnumText<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fn from_sql(
+        _: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let text = std::str::from_utf8(raw)?;
+
+        text.parse().map(EnumText).map_err(|err: T::Err| {
+            InvalidEnumText {
+                text: text.to_string(),
+                type_name: std::any::type_name::<T>(),
+                message: err.to_string(),
+            }
+            .into()
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts_text(ty)
+    }
+}
+
+impl<T: Display + fmt::Debug> ToSql for EnumText<T> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        self.0.to_string().as_str().to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts_text(ty)
+    }
+
+    to_sql_checked!();
+}
+
+/// The source of an [`Error::ColumnConversion`] for text which an
+/// [`EnumText`] couldn't parse, holding the offending text.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+/// [`EnumText`]: struct.EnumText.html
+#[derive(Debug)]
+pub struct InvalidEnumText {
+    text: String,
+    type_name: &'static str,
+    message: String,
+}
+
+impl InvalidEnumText {
+    /// Get the text which couldn't be parsed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the name of the type the text couldn't be parsed into.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl Display for InvalidEnumText {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "invalid {} value \"{}\": {}",
+            self.type_name, self.text, self.message
+        )
+    }
+}
+
+impl StdError for InvalidEnumText {}
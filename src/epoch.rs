@@ -0,0 +1,215 @@
+//! Mapping of timestamps stored as Unix epoch integers, for
+//! `#[pg_mapper(epoch = "seconds")]` fields.
+//!
+//! Schemas storing timestamps as `bigint` seconds, milliseconds or
+//! microseconds since the Unix epoch can be mapped to `SystemTime` fields,
+//! or, with the `chrono` feature, `DateTime<Utc>` fields:
+//!
+//! ```
+//!     #[derive(PostgresMapper)]
+//!     #[pg_mapper(table = "events", insert)]
+//!     pub struct Event {
+//!         pub id: i64,
+//!         #[pg_mapper(epoch = "millis")]
+//!         pub created_at: DateTime<Utc>,
+//!         #[pg_mapper(epoch = "seconds")]
+//!         pub deleted_at: Option<SystemTime>,
+//!     }
+//! ```
+//!
+//! The unit is `"seconds"`, `"millis"` or `"micros"`. The derive fetches an
+//! `i64` and converts it, failing with an [`Error::ColumnConversion`] naming
+//! the column, with an [`EpochOutOfRange`] source, for values the field type
+//! can't hold. The parameters of the derived `TokioPostgresInsert`,
+//! `TokioPostgresDiff` and `TokioPostgresCrud` implementations write the
+//! timestamps back in the same unit, rounded down.
+//!
+//! [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+//! [`EpochOutOfRange`]: struct.EpochOutOfRange.html
+
+use crate::{capture_backtrace, Error};
+
+use bytes::BytesMut;
+use tokio_postgres::types::{to_sql_checked, IsNull, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Timestamps which can be converted from and to microseconds since the Unix
+/// epoch, for `#[pg_mapper(epoch)]` fields.
+pub trait EpochTimestamp: Sized {
+    /// Creates a timestamp from microseconds since the Unix epoch, or `None`
+    /// if it's out of range.
+    fn from_epoch_micros(micros: i64) -> Option<Self>;
+
+    /// Get the microseconds since the Unix epoch, truncating any finer
+    /// precision, or `None` if they're out of range.
+    fn to_epoch_micros(&self) -> Option<i64>;
+}
+
+impl EpochTimestamp for SystemTime {
+    fn from_epoch_micros(micros: i64) -> Option<Self> {
+        let duration = Duration::from_micros(micros.unsigned_abs());
+
+        if micros < 0 {
+            UNIX_EPOCH.checked_sub(duration)
+        } else {
+            UNIX_EPOCH.checked_add(duration)
+        }
+    }
+
+    fn to_epoch_micros(&self) -> Option<i64> {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_micros()).ok(),
+            Err(before) => i64::try_from(before.duration().as_micros())
+                .ok()
+                .map(|micros| -micros),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl EpochTimestamp for chrono::DateTime<chrono::Utc> {
+    fn from_epoch_micros(micros: i64) -> Option<Self> {
+        use chrono::TimeZone;
+
+        chrono::Utc.timestamp_micros(micros).single()
+    }
+
+    fn to_epoch_micros(&self) -> Option<i64> {
+        Some(self.timestamp_micros())
+    }
+}
+
+/// Converts an epoch integer of an `#[pg_mapper(epoch)]` field, counting
+/// the given microseconds per unit, on behalf of derive-generated code.
+#[doc(hidden)]
+pub fn from_epoch<T: EpochTimestamp>(
+    value: i64,
+    micros_per_unit: i64,
+    column: &str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    value
+        .checked_mul(micros_per_unit)
+        .and_then(T::from_epoch_micros)
+        .ok_or_else(|| Error::ColumnConversion {
+            column: column.to_string(),
+            rust_type: std::any::type_name::<T>(),
+            pg_type: Some(Type::INT8),
+            source: Box::new(EpochOutOfRange {
+                value,
+                micros_per_unit,
+            }),
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        })
+}
+
+/// A parameter writing a timestamp field, or an `Option` of one, as an epoch
+/// integer counting the given microseconds per unit, on behalf of
+/// derive-generated code.
+#[doc(hidden)]
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct EpochParam<T, const MICROS_PER_UNIT: i64>(T);
+
+impl<T, const MICROS_PER_UNIT: i64> EpochParam<T, MICROS_PER_UNIT> {
+    /// Views a reference to a field as a parameter, borrowing it for as long
+    /// as the field.
+    pub fn from_ref(value: &T) -> &Self {
+        // SAFETY: `EpochParam` is a `repr(transparent)` wrapper of `T`, so
+        // both references have the same layout and lifetime.
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+/// The timestamps, or `Option`s of them, which can be written as epoch
+/// integers by an `EpochParam`.
+#[doc(hidden)]
+pub trait EpochParamValue {
+    fn epoch_micros(&self) -> Option<Option<i64>>;
+}
+
+impl EpochParamValue for SystemTime {
+    fn epoch_micros(&self) -> Option<Option<i64>> {
+        self.to_epoch_micros().map(Some)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl EpochParamValue for chrono::DateTime<chrono::Utc> {
+    fn epoch_micros(&self) -> Option<Option<i64>> {
+        self.to_epoch_micros().map(Some)
+    }
+}
+
+impl<T: EpochTimestamp> EpochParamValue for Option<T> {
+    fn epoch_micros(&self) -> Option<Option<i64>> {
+        match *self {
+            Some(ref value) => value.to_epoch_micros().map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+impl<T, const MICROS_PER_UNIT: i64> ToSql for EpochParam<T, MICROS_PER_UNIT>
+where
+    T: EpochParamValue + std::fmt::Debug,
+{
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        let micros = self.0.epoch_micros().ok_or("timestamp is out of range")?;
+
+        match micros {
+            Some(micros) => micros.div_euclid(MICROS_PER_UNIT).to_sql(ty, out),
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <i64 as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+/// The source of an [`Error::ColumnConversion`] for an epoch integer which
+/// is out of the range of the timestamp type, holding the offending value.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[derive(Clone, Copy, Debug)]
+pub struct EpochOutOfRange {
+    value: i64,
+    micros_per_unit: i64,
+}
+
+impl EpochOutOfRange {
+    /// Get the epoch integer which is out of range.
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+}
+
+impl Display for EpochOutOfRange {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let unit = match self.micros_per_unit {
+            1 => "microseconds",
+            1_000 => "milliseconds",
+            _ => "seconds",
+        };
+
+        write!(
+            f,
+            "{} {} since the Unix epoch is out of range for the timestamp",
+            self.value, unit
+        )
+    }
+}
+
+impl StdError for EpochOutOfRange {}
@@ -0,0 +1,285 @@
+//! The `PgPoint`, `PgBox` and `PgPath` field types.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+
+/// Mapping of `point` columns and parameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PgPoint {
+    /// The x coordinate.
+    pub x: f64,
+    /// The y coordinate.
+    pub y: f64,
+}
+
+impl PgPoint {
+    /// Creates a point from its coordinates.
+    pub fn new(x: f64, y: f64) -> Self {
+        PgPoint { x, y }
+    }
+
+    fn read(raw: &mut &[u8]) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if raw.len() < 16 {
+            return Err("invalid point length".into());
+        }
+
+        Ok(PgPoint {
+            x: raw.get_f64(),
+            y: raw.get_f64(),
+        })
+    }
+
+    fn write(&self, out: &mut BytesMut) {
+        out.put_f64(self.x);
+        out.put_f64(self.y);
+    }
+}
+
+impl From<(f64, f64)> for PgPoint {
+    fn from((x, y): (f64, f64)) -> Self {
+        PgPoint { x, y }
+    }
+}
+
+impl<'a> FromSql<'a> for PgPoint {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let point = PgPoint::read(&mut raw)?;
+        if !raw.is_empty() {
+            return Err("invalid point length".into());
+        }
+
+        Ok(point)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::POINT
+    }
+}
+
+impl ToSql for PgPoint {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        self.write(out);
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::POINT
+    }
+
+    to_sql_checked!();
+}
+
+/// Mapping of `box` columns and parameters.
+///
+/// Postgres stores the upper right corner of a box as `high` and its lower
+/// left corner as `low`, swapping coordinates on input as needed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PgBox {
+    /// The upper right corner.
+    pub high: PgPoint,
+    /// The lower left corner.
+    pub low: PgPoint,
+}
+
+impl PgBox {
+    /// Creates a box from any two opposite corners.
+    pub fn new(a: PgPoint, b: PgPoint) -> Self {
+        PgBox {
+            high: PgPoint::new(a.x.max(b.x), a.y.max(b.y)),
+            low: PgPoint::new(a.x.min(b.x), a.y.min(b.y)),
+        }
+    }
+}
+
+impl<'a> FromSql<'a> for PgBox {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let high = PgPoint::read(&mut raw)?;
+        let low = PgPoint::read(&mut raw)?;
+        if !raw.is_empty() {
+            return Err("invalid box length".into());
+        }
+
+        Ok(PgBox { high, low })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BOX
+    }
+}
+
+impl ToSql for PgBox {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        let normalized = PgBox::new(self.high, self.low);
+        normalized.high.write(out);
+        normalized.low.write(out);
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BOX
+    }
+
+    to_sql_checked!();
+}
+
+/// Mapping of `path` columns and parameters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PgPath {
+    /// Whether the last point connects back to the first one, as in a
+    /// polygon.
+    pub closed: bool,
+    /// The points of the path.
+    pub points: Vec<PgPoint>,
+}
+
+impl<'a> FromSql<'a> for PgPath {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if raw.len() < 5 {
+            return Err("invalid path length".into());
+        }
+
+        let closed = raw.get_u8() != 0;
+        let count = usize::try_from(raw.get_i32()).map_err(|_| "invalid path length")?;
+        if raw.len() != count * 16 {
+            return Err("invalid path length".into());
+        }
+
+        let points = (0..count)
+            .map(|_| PgPoint::read(&mut raw))
+            .collect::<Result<_, _>>()?;
+
+        Ok(PgPath { closed, points })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::PATH
+    }
+}
+
+impl ToSql for PgPath {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        let count =
+            i32::try_from(self.points.len()).map_err(|_| "too many path points")?;
+
+        out.put_u8(self.closed as u8);
+        out.put_i32(count);
+        for point in &self.points {
+            point.write(out);
+        }
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::PATH
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(feature = "geo-types")]
+mod geo {
+    use super::{PgBox, PgPath, PgPoint};
+
+    use geo_types::{Coord, LineString, Point, Rect};
+
+    impl From<PgPoint> for Point<f64> {
+        fn from(point: PgPoint) -> Self {
+            Point::new(point.x, point.y)
+        }
+    }
+
+    impl From<Point<f64>> for PgPoint {
+        fn from(point: Point<f64>) -> Self {
+            PgPoint::new(point.x(), point.y())
+        }
+    }
+
+    impl From<PgPoint> for Coord<f64> {
+        fn from(point: PgPoint) -> Self {
+            Coord {
+                x: point.x,
+                y: point.y,
+            }
+        }
+    }
+
+    impl From<Coord<f64>> for PgPoint {
+        fn from(coord: Coord<f64>) -> Self {
+            PgPoint::new(coord.x, coord.y)
+        }
+    }
+
+    impl From<PgBox> for Rect<f64> {
+        fn from(rect: PgBox) -> Self {
+            Rect::new(Coord::from(rect.low), Coord::from(rect.high))
+        }
+    }
+
+    impl From<Rect<f64>> for PgBox {
+        fn from(rect: Rect<f64>) -> Self {
+            PgBox::new(rect.min().into(), rect.max().into())
+        }
+    }
+
+    /// Converts into a `LineString`, repeating the first point at the end of
+    /// a closed path as `geo-types` expects.
+    impl From<PgPath> for LineString<f64> {
+        fn from(path: PgPath) -> Self {
+            let mut coords = path
+                .points
+                .iter()
+                .map(|&point| Coord::from(point))
+                .collect::<Vec<_>>();
+
+            if path.closed {
+                if let Some(&first) = coords.first() {
+                    coords.push(first);
+                }
+            }
+
+            LineString(coords)
+        }
+    }
+
+    /// Converts from a `LineString`, which is closed if its first and last
+    /// points are equal.
+    impl From<LineString<f64>> for PgPath {
+        fn from(line: LineString<f64>) -> Self {
+            let closed = line.is_closed() && line.0.len() > 1;
+            let mut points = line.0.into_iter().map(PgPoint::from).collect::<Vec<_>>();
+
+            if closed {
+                points.pop();
+            }
+
+            PgPath { closed, points }
+        }
+    }
+}
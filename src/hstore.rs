@@ -0,0 +1,138 @@
+//! The `PgHstore` field type.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::ops::{Deref, DerefMut};
+
+/// Mapping of `hstore` columns and parameters.
+///
+/// `hstore` is created by an extension, so it's recognized by its name rather
+/// than an OID fixed across databases.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PgHstore(pub HashMap<String, Option<String>>);
+
+impl PgHstore {
+    /// Unwraps the inner map.
+    pub fn into_inner(self) -> HashMap<String, Option<String>> {
+        self.0
+    }
+}
+
+impl Deref for PgHstore {
+    type Target = HashMap<String, Option<String>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for PgHstore {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<HashMap<String, Option<String>>> for PgHstore {
+    fn from(map: HashMap<String, Option<String>>) -> Self {
+        PgHstore(map)
+    }
+}
+
+fn read_str<'a>(
+    raw: &mut &'a [u8],
+    len: i32,
+) -> Result<&'a str, Box<dyn StdError + Sync + Send>> {
+    let len = usize::try_from(len).map_err(|_| "invalid hstore entry length")?;
+    if raw.len() < len {
+        return Err("invalid hstore entry length".into());
+    }
+
+    let (text, rest) = raw.split_at(len);
+    *raw = rest;
+
+    Ok(std::str::from_utf8(text)?)
+}
+
+fn read_i32(raw: &mut &[u8]) -> Result<i32, Box<dyn StdError + Sync + Send>> {
+    if raw.len() < 4 {
+        return Err("invalid hstore length".into());
+    }
+
+    Ok(raw.get_i32())
+}
+
+impl<'a> FromSql<'a> for PgHstore {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let count = read_i32(&mut raw)?;
+        let count = usize::try_from(count).map_err(|_| "invalid hstore entry count")?;
+        let mut map = HashMap::with_capacity(count);
+
+        for _ in 0..count {
+            let len = read_i32(&mut raw)?;
+            let key = read_str(&mut raw, len)?;
+
+            let value = match read_i32(&mut raw)? {
+                -1 => None,
+                len => Some(read_str(&mut raw, len)?.to_string()),
+            };
+
+            map.insert(key.to_string(), value);
+        }
+
+        if !raw.is_empty() {
+            return Err("invalid hstore length".into());
+        }
+
+        Ok(PgHstore(map))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "hstore"
+    }
+}
+
+fn write_str(
+    out: &mut BytesMut,
+    text: &str,
+) -> Result<(), Box<dyn StdError + Sync + Send>> {
+    let len = i32::try_from(text.len()).map_err(|_| "hstore entry too long")?;
+    out.put_i32(len);
+    out.put_slice(text.as_bytes());
+
+    Ok(())
+}
+
+impl ToSql for PgHstore {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        let count = i32::try_from(self.0.len()).map_err(|_| "too many hstore entries")?;
+        out.put_i32(count);
+
+        for (key, value) in &self.0 {
+            write_str(out, key)?;
+
+            match value {
+                Some(value) => write_str(out, value)?,
+                None => out.put_i32(-1),
+            }
+        }
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "hstore"
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,361 @@
+//! Quoting of the identifiers interpolated into generated SQL.
+//!
+//! This is the only implementation of the quoting rules: the strings the
+//! derive generates, such as `sql_fields` or the `SELECT` constant, are built
+//! with these functions, so every statement spells a column or table the same
+//! way.
+
+use std::borrow::Cow;
+
+/// Quotes a column name, unless it is a lower case identifier which isn't a
+/// reserved keyword, so that renamed columns such as `User Name` or `order`
+/// are valid in SQL.
+#[doc(hidden)]
+pub fn quote_identifier(name: &str) -> Cow<'_, str> {
+    if is_plain(name.as_bytes()) {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("\"{}\"", name.replace('"', "\"\"")))
+    }
+}
+
+/// Quotes every dot-separated part of a table name, such as the schema of
+/// `auth.user`, like [`quote_identifier`].
+///
+/// [`quote_identifier`]: fn.quote_identifier.html
+#[doc(hidden)]
+pub fn quote_table(name: &str) -> String {
+    name.trim()
+        .split('.')
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Quotes and joins column names with ", ".
+pub(crate) fn join_identifiers<'a>(names: impl IntoIterator<Item = &'a str>) -> String {
+    names
+        .into_iter()
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Builds the `SELECT` statement of the given columns of a table, quoted
+/// like [`quote_identifier`] and [`quote_table`], in a constant expression,
+/// leaving out the rows where the given soft delete column is set.
+///
+/// The statement is first built with a `N` of 0 to measure its length, then
+/// with a `N` of that length:
+///
+/// ```
+///     const LEN: usize = select_statement::<0>("user", &["id"], None).len();
+///     const SQL: &SqlBuffer<LEN> = &select_statement("user", &["id"], None);
+///
+///     assert_eq!(SQL.as_str(), "SELECT \"user\".id FROM \"user\"");
+/// ```
+///
+/// [`quote_identifier`]: fn.quote_identifier.html
+/// [`quote_table`]: fn.quote_table.html
+#[doc(hidden)]
+pub const fn select_statement<const N: usize>(
+    table: &str,
+    columns: &[&str],
+    soft_delete: Option<&str>,
+) -> SqlBuffer<N> {
+    let table = trim(table.as_bytes());
+    let mut sql = SqlBuffer::new().push_bytes(b"SELECT ");
+
+    let mut idx = 0;
+    while idx < columns.len() {
+        if idx > 0 {
+            sql = sql.push_bytes(b", ");
+        }
+        sql = sql
+            .push_table(table)
+            .push_bytes(b".")
+            .push_identifier(columns[idx].as_bytes());
+        idx += 1;
+    }
+
+    sql = sql.push_bytes(b" FROM ").push_table(table);
+
+    if let Some(column) = soft_delete {
+        sql = sql
+            .push_bytes(b" WHERE ")
+            .push_table(table)
+            .push_bytes(b".")
+            .push_identifier(column.as_bytes())
+            .push_bytes(b" IS NULL");
+    }
+
+    sql
+}
+
+/// A fixed-capacity SQL string built in constant expressions.
+///
+/// Bytes pushed past the capacity are only counted, so that building a
+/// string with a capacity of 0 measures it.
+#[doc(hidden)]
+pub struct SqlBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> SqlBuffer<N> {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Get the length of the string, including the bytes past the capacity.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Get whether the string is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than the capacity.
+    pub const fn as_str(&self) -> &str {
+        if self.len > N {
+            panic!("the SQL string is longer than its buffer");
+        }
+
+        match std::str::from_utf8(self.bytes.split_at(self.len).0) {
+            Ok(sql) => sql,
+            Err(_) => panic!("the SQL string isn't valid UTF-8"),
+        }
+    }
+
+    const fn push(mut self, byte: u8) -> Self {
+        if self.len < N {
+            self.bytes[self.len] = byte;
+        }
+        self.len += 1;
+
+        self
+    }
+
+    const fn push_bytes(mut self, bytes: &[u8]) -> Self {
+        let mut idx = 0;
+        while idx < bytes.len() {
+            self = self.push(bytes[idx]);
+            idx += 1;
+        }
+
+        self
+    }
+
+    const fn push_identifier(mut self, name: &[u8]) -> Self {
+        if is_plain(name) {
+            return self.push_bytes(name);
+        }
+
+        self = self.push(b'"');
+        let mut idx = 0;
+        while idx < name.len() {
+            if name[idx] == b'"' {
+                self = self.push(b'"');
+            }
+            self = self.push(name[idx]);
+            idx += 1;
+        }
+
+        self.push(b'"')
+    }
+
+    const fn push_table(mut self, name: &[u8]) -> Self {
+        let mut start = 0;
+        let mut idx = 0;
+        while idx <= name.len() {
+            if idx == name.len() || name[idx] == b'.' {
+                if start > 0 {
+                    self = self.push(b'.');
+                }
+                self = self.push_identifier(slice(name, start, idx));
+                start = idx + 1;
+            }
+            idx += 1;
+        }
+
+        self
+    }
+}
+
+/// Whether a name is a lower case identifier which isn't a reserved keyword,
+/// and so doesn't need quoting.
+const fn is_plain(name: &[u8]) -> bool {
+    if name.is_empty() || !(name[0].is_ascii_lowercase() || name[0] == b'_') {
+        return false;
+    }
+
+    let mut idx = 0;
+    while idx < name.len() {
+        let c = name[idx];
+        if !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'_' || c == b'$') {
+            return false;
+        }
+        idx += 1;
+    }
+
+    let mut idx = 0;
+    while idx < RESERVED_KEYWORDS.len() {
+        if bytes_eq(RESERVED_KEYWORDS[idx].as_bytes(), name) {
+            return false;
+        }
+        idx += 1;
+    }
+
+    true
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut idx = 0;
+    while idx < a.len() {
+        if a[idx] != b[idx] {
+            return false;
+        }
+        idx += 1;
+    }
+
+    true
+}
+
+const fn slice(bytes: &[u8], start: usize, end: usize) -> &[u8] {
+    bytes.split_at(end).0.split_at(start).1
+}
+
+const fn trim(mut bytes: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = bytes {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let [rest @ .., last] = bytes {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+
+    bytes
+}
+
+/// The keywords which can't be used as column names without quoting.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
@@ -0,0 +1,258 @@
+//! The `PgU32`, `PgU64` and `PgNonZero` field types.
+
+use bytes::BytesMut;
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::num::{NonZeroI16, NonZeroI32, NonZeroI64};
+use std::ops::Deref;
+
+fn accepts_int(ty: &Type) -> bool {
+    *ty == Type::INT2 || *ty == Type::INT4 || *ty == Type::INT8
+}
+
+/// Reads a `smallint`, `integer` or `bigint` value.
+fn read_int(ty: &Type, raw: &[u8]) -> Result<i64, Box<dyn StdError + Sync + Send>> {
+    if *ty == Type::INT2 {
+        i16::from_sql(ty, raw).map(i64::from)
+    } else if *ty == Type::INT4 {
+        i32::from_sql(ty, raw).map(i64::from)
+    } else {
+        i64::from_sql(ty, raw)
+    }
+}
+
+/// Writes a `smallint`, `integer` or `bigint` value, failing if it doesn't
+/// fit in the column's type.
+fn write_int<T>(
+    ty: &Type,
+    value: T,
+    out: &mut BytesMut,
+) -> Result<IsNull, Box<dyn StdError + Sync + Send>>
+where
+    T: Copy + Into<i128>,
+    i16: TryFrom<T>,
+    i32: TryFrom<T>,
+    i64: TryFrom<T>,
+{
+    let out_of_range = || IntegerOutOfRange {
+        value: value.into(),
+        type_name: ty.name().to_string(),
+    };
+
+    if *ty == Type::INT2 {
+        i16::try_from(value).map_err(|_| out_of_range())?.to_sql(ty, out)
+    } else if *ty == Type::INT4 {
+        i32::try_from(value).map_err(|_| out_of_range())?.to_sql(ty, out)
+    } else {
+        i64::try_from(value).map_err(|_| out_of_range())?.to_sql(ty, out)
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($name:ident, $ty:ty) => {
+        impl Deref for $name {
+            type Target = $ty;
+
+            fn deref(&self) -> &$ty {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter) -> FmtResult {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl From<$ty> for $name {
+            fn from(value: $ty) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for $ty {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl<'a> FromSql<'a> for $name {
+            fn from_sql(
+                ty: &Type,
+                raw: &'a [u8],
+            ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+                let value = read_int(ty, raw)?;
+
+                <$ty>::try_from(value).map($name).map_err(|_| {
+                    Box::new(IntegerOutOfRange {
+                        value: value.into(),
+                        type_name: stringify!($ty).to_string(),
+                    }) as Box<dyn StdError + Sync + Send>
+                })
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                accepts_int(ty)
+            }
+        }
+
+        impl ToSql for $name {
+            fn to_sql(
+                &self,
+                ty: &Type,
+                out: &mut BytesMut,
+            ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+                write_int(ty, self.0, out)
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                accepts_int(ty)
+            }
+
+            to_sql_checked!();
+        }
+    };
+}
+
+/// Mapping of `smallint`, `integer` and `bigint` columns and parameters to a
+/// `u32`, which is usually stored in a `bigint`.
+///
+/// Negative values result in an [`Error::ColumnConversion`] naming the column,
+/// with an [`IntegerOutOfRange`] source holding the value, rather than
+/// wrapping around. Likewise, values which don't fit in the parameter's type
+/// fail to be written.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+/// [`IntegerOutOfRange`]: struct.IntegerOutOfRange.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgU32(pub u32);
+
+impl_unsigned!(PgU32, u32);
+
+/// Mapping of `smallint`, `integer` and `bigint` columns and parameters to a
+/// `u64`.
+///
+/// Like with [`PgU32`], negative values fail to be read, and values above
+/// `i64::MAX` fail to be written.
+///
+/// [`PgU32`]: struct.PgU32.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgU64(pub u64);
+
+impl_unsigned!(PgU64, u64);
+
+/// Mapping of `smallint`, `integer` and `bigint` columns and parameters to
+/// `NonZeroI16`, `NonZeroI32` or `NonZeroI64`, such as ids.
+///
+/// Zero results in an [`Error::ColumnConversion`] naming the column, with an
+/// [`IntegerOutOfRange`] source. NULL isn't zero: `Option<PgNonZero<_>>`
+/// maps NULL to `None` and still fails on zero. Like the `NonZero` types, an
+/// `Option<PgNonZero<_>>` is the size of the integer.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+/// [`IntegerOutOfRange`]: struct.IntegerOutOfRange.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgNonZero<T>(pub T);
+
+impl<T> PgNonZero<T> {
+    /// Unwraps the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for PgNonZero<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Display> Display for PgNonZero<T> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<T> From<T> for PgNonZero<T> {
+    fn from(value: T) -> Self {
+        PgNonZero(value)
+    }
+}
+
+macro_rules! impl_non_zero {
+    ($($nonzero:ident => $int:ty),*) => {
+        $(
+            impl<'a> FromSql<'a> for PgNonZero<$nonzero> {
+                fn from_sql(
+                    ty: &Type,
+                    raw: &'a [u8],
+                ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+                    let value = read_int(ty, raw)?;
+
+                    <$int>::try_from(value)
+                        .ok()
+                        .and_then($nonzero::new)
+                        .map(PgNonZero)
+                        .ok_or_else(|| {
+                            Box::new(IntegerOutOfRange {
+                                value: value.into(),
+                                type_name: stringify!($nonzero).to_string(),
+                            }) as Box<dyn StdError + Sync + Send>
+                        })
+                }
+
+                fn accepts(ty: &Type) -> bool {
+                    accepts_int(ty)
+                }
+            }
+
+            impl ToSql for PgNonZero<$nonzero> {
+                fn to_sql(
+                    &self,
+                    ty: &Type,
+                    out: &mut BytesMut,
+                ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+                    write_int(ty, self.0.get(), out)
+                }
+
+                fn accepts(ty: &Type) -> bool {
+                    accepts_int(ty)
+                }
+
+                to_sql_checked!();
+            }
+        )*
+    };
+}
+
+impl_non_zero!(NonZeroI16 => i16, NonZeroI32 => i32, NonZeroI64 => i64);
+
+/// The source of an [`Error::ColumnConversion`] for an integer which doesn't
+/// fit in the requested type, holding the offending value.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[derive(Debug)]
+pub struct IntegerOutOfRange {
+    value: i128,
+    type_name: String,
+}
+
+impl IntegerOutOfRange {
+    /// Get the value which doesn't fit.
+    pub fn value(&self) -> i128 {
+        self.value
+    }
+}
+
+impl Display for IntegerOutOfRange {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{} is out of range for {}", self.value, self.type_name)
+    }
+}
+
+impl StdError for IntegerOutOfRange {}
@@ -0,0 +1,425 @@
+//! The `PgInterval` and `PgDuration` field types.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+use std::ops::Deref;
+use std::time::Duration;
+
+const MICROSECONDS_PER_SECOND: i64 = 1_000_000;
+const MICROSECONDS_PER_MINUTE: i64 = 60 * MICROSECONDS_PER_SECOND;
+const MICROSECONDS_PER_HOUR: i64 = 60 * MICROSECONDS_PER_MINUTE;
+const MICROSECONDS_PER_DAY: i128 = 24 * MICROSECONDS_PER_HOUR as i128;
+
+/// Mapping of `interval` columns and parameters.
+///
+/// Postgres keeps the months, days and time of an interval apart, since
+/// neither months nor days have a fixed length once time zones are involved,
+/// so they're only converted to durations on request.
+///
+/// `Display` formats intervals in the ISO 8601 style of the `iso_8601`
+/// `IntervalStyle`, such as `P1Y2M3DT4H5M6.5S`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PgInterval {
+    /// The months of the interval, including its years.
+    pub months: i32,
+    /// The days of the interval.
+    pub days: i32,
+    /// The time of the interval, in microseconds.
+    pub microseconds: i64,
+}
+
+impl PgInterval {
+    /// The total length of the days and time of the interval, in
+    /// microseconds.
+    fn day_time_microseconds(&self) -> i128 {
+        i128::from(self.days) * MICROSECONDS_PER_DAY + i128::from(self.microseconds)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl PgInterval {
+    /// Parses an interval in the `postgres` `IntervalStyle`, the default,
+    /// such as `1 year 2 mons 3 days 04:05:06.5`, as found in the JSON of
+    /// `row_to_json`.
+    fn parse_postgres(text: &str) -> Option<Self> {
+        let mut interval = PgInterval::default();
+        let mut words = text.split_whitespace();
+
+        while let Some(word) = words.next() {
+            if word.contains(':') {
+                interval.microseconds = interval
+                    .microseconds
+                    .checked_add(parse_postgres_time(word)?)?;
+                continue;
+            }
+
+            let count = word.parse::<i32>().ok()?;
+            match words.next()? {
+                "year" | "years" => {
+                    interval.months = interval.months.checked_add(count.checked_mul(12)?)?
+                }
+                "mon" | "mons" => interval.months = interval.months.checked_add(count)?,
+                "day" | "days" => interval.days = interval.days.checked_add(count)?,
+                _ => return None,
+            }
+        }
+
+        Some(interval)
+    }
+}
+
+/// Parses the `[-]HH:MM:SS[.ffffff]` time of an interval into microseconds.
+#[cfg(feature = "serde")]
+fn parse_postgres_time(text: &str) -> Option<i64> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    let mut parts = text.splitn(3, ':');
+    let hours = parts.next()?.parse::<i64>().ok()?;
+    let minutes = parts.next()?.parse::<i64>().ok()?;
+    let seconds = parts.next()?;
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let seconds = seconds.parse::<i64>().ok()?;
+
+    if fraction.len() > 6 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let fraction = format!("{:0<6}", fraction).parse::<i64>().ok()?;
+
+    let microseconds = hours
+        .checked_mul(MICROSECONDS_PER_HOUR)?
+        .checked_add(minutes * MICROSECONDS_PER_MINUTE)?
+        .checked_add(seconds * MICROSECONDS_PER_SECOND)?
+        .checked_add(fraction)?;
+
+    Some(if negative { -microseconds } else { microseconds })
+}
+
+impl Display for PgInterval {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let years = self.months / 12;
+        let months = self.months % 12;
+        let hours = self.microseconds / MICROSECONDS_PER_HOUR;
+        let minutes =
+            self.microseconds % MICROSECONDS_PER_HOUR / MICROSECONDS_PER_MINUTE;
+        let microseconds = self.microseconds % MICROSECONDS_PER_MINUTE;
+
+        let mut text = String::from("P");
+
+        if years != 0 {
+            write!(text, "{}Y", years)?;
+        }
+        if months != 0 {
+            write!(text, "{}M", months)?;
+        }
+        if self.days != 0 {
+            write!(text, "{}D", self.days)?;
+        }
+
+        if self.microseconds != 0 {
+            text.push('T');
+
+            if hours != 0 {
+                write!(text, "{}H", hours)?;
+            }
+            if minutes != 0 {
+                write!(text, "{}M", minutes)?;
+            }
+            if microseconds != 0 {
+                if microseconds < 0 {
+                    text.push('-');
+                }
+
+                let microseconds = microseconds.unsigned_abs();
+                write!(text, "{}", microseconds / MICROSECONDS_PER_SECOND as u64)?;
+
+                let fraction = microseconds % MICROSECONDS_PER_SECOND as u64;
+                if fraction != 0 {
+                    let fraction = format!("{:06}", fraction);
+                    write!(text, ".{}", fraction.trim_end_matches('0'))?;
+                }
+
+                text.push('S');
+            }
+        } else if text.len() == 1 {
+            text.push_str("T0S");
+        }
+
+        f.pad(&text)
+    }
+}
+
+impl<'a> FromSql<'a> for PgInterval {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err("invalid interval length".into());
+        }
+
+        let microseconds = raw.get_i64();
+        let days = raw.get_i32();
+        let months = raw.get_i32();
+
+        Ok(PgInterval {
+            months,
+            days,
+            microseconds,
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+impl ToSql for PgInterval {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        out.put_i64(self.microseconds);
+        out.put_i32(self.days);
+        out.put_i32(self.months);
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+
+    to_sql_checked!();
+}
+
+impl TryFrom<PgInterval> for Duration {
+    type Error = IntervalError;
+
+    /// Converts into a `Duration`, counting days as 24 hours, failing if the
+    /// interval has months or is negative.
+    fn try_from(value: PgInterval) -> Result<Self, IntervalError> {
+        if value.months != 0 {
+            return Err(IntervalError::Months);
+        }
+
+        let microseconds = u64::try_from(value.day_time_microseconds())
+            .map_err(|_| IntervalError::Negative)?;
+
+        Ok(Duration::from_micros(microseconds))
+    }
+}
+
+impl TryFrom<Duration> for PgInterval {
+    type Error = IntervalError;
+
+    /// Converts from a `Duration` into an interval of microseconds, failing
+    /// if it's too long to be held by an `i64`.
+    fn try_from(value: Duration) -> Result<Self, IntervalError> {
+        let microseconds =
+            i64::try_from(value.as_micros()).map_err(|_| IntervalError::OutOfRange)?;
+
+        Ok(PgInterval {
+            months: 0,
+            days: 0,
+            microseconds,
+        })
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<PgInterval> for chrono::Duration {
+    type Error = IntervalError;
+
+    /// Converts into a `chrono::Duration`, counting days as 24 hours, failing
+    /// if the interval has months.
+    fn try_from(value: PgInterval) -> Result<Self, IntervalError> {
+        if value.months != 0 {
+            return Err(IntervalError::Months);
+        }
+
+        let microseconds = i64::try_from(value.day_time_microseconds())
+            .map_err(|_| IntervalError::OutOfRange)?;
+
+        Ok(chrono::Duration::microseconds(microseconds))
+    }
+}
+
+/// Mapping of `interval` columns and parameters to a `std::time::Duration`.
+///
+/// Intervals with months, or which are negative, can't be converted and
+/// result in an [`Error::ColumnConversion`] naming the column, with an
+/// [`InvalidDuration`] source holding the interval. Days count as 24 hours.
+///
+/// With the derive, fields of type `Duration` or `Option<Duration>`, spelled
+/// `Duration` or `std::time::Duration`, are fetched through this type and
+/// unwrapped. Writing parameters needs `PgDuration` fields, as `Duration`
+/// has no `ToSql` implementation.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+/// [`InvalidDuration`]: struct.InvalidDuration.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgDuration(pub Duration);
+
+impl PgDuration {
+    /// Unwraps the inner duration.
+    pub fn into_inner(self) -> Duration {
+        self.0
+    }
+}
+
+impl Deref for PgDuration {
+    type Target = Duration;
+
+    fn deref(&self) -> &Duration {
+        &self.0
+    }
+}
+
+impl From<Duration> for PgDuration {
+    fn from(duration: Duration) -> Self {
+        PgDuration(duration)
+    }
+}
+
+impl From<PgDuration> for Duration {
+    fn from(duration: PgDuration) -> Self {
+        duration.0
+    }
+}
+
+impl<'a> FromSql<'a> for PgDuration {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let interval = PgInterval::from_sql(ty, raw)?;
+
+        match Duration::try_from(interval) {
+            Ok(duration) => Ok(PgDuration(duration)),
+            Err(error) => Err(Box::new(InvalidDuration { interval, error })),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+impl ToSql for PgDuration {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        PgInterval::try_from(self.0)?.to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+
+    to_sql_checked!();
+}
+
+/// The source of an [`Error::ColumnConversion`] for an interval which can't
+/// be converted to a `Duration`, holding the offending interval.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[derive(Clone, Copy, Debug)]
+pub struct InvalidDuration {
+    interval: PgInterval,
+    error: IntervalError,
+}
+
+impl InvalidDuration {
+    /// Get the interval which can't be converted.
+    pub fn interval(&self) -> PgInterval {
+        self.interval
+    }
+
+    /// Get the reason the interval can't be converted.
+    pub fn error(&self) -> IntervalError {
+        self.error
+    }
+}
+
+impl Display for InvalidDuration {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "interval {} can't be converted to a Duration: {}",
+            self.interval, self.error
+        )
+    }
+}
+
+impl StdError for InvalidDuration {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// An error converting a `PgInterval` to or from a duration.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum IntervalError {
+    /// The interval has months, whose length varies.
+    Months,
+    /// The interval is negative.
+    Negative,
+    /// The duration is out of the range of the target type.
+    OutOfRange,
+}
+
+impl Display for IntervalError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(match *self {
+            IntervalError::Months => "interval has months, which have no fixed length",
+            IntervalError::Negative => "interval is negative",
+            IntervalError::OutOfRange => "interval is out of range",
+        })
+    }
+}
+
+impl StdError for IntervalError {}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PgInterval {
+    /// Deserializes an interval in the default `postgres` `IntervalStyle`,
+    /// such as `1 day 02:03:04`, as found in the JSON of `row_to_json`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+
+        PgInterval::parse_postgres(&text).ok_or_else(|| {
+            serde::de::Error::custom(format_args!("invalid interval {:?}", text))
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PgDuration {
+    /// Deserializes an interval like `PgInterval`, failing with an
+    /// [`InvalidDuration`] message for intervals with months or negative
+    /// ones.
+    ///
+    /// [`InvalidDuration`]: struct.InvalidDuration.html
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let interval = PgInterval::deserialize(deserializer)?;
+
+        match Duration::try_from(interval) {
+            Ok(duration) => Ok(PgDuration(duration)),
+            Err(error) => Err(serde::de::Error::custom(InvalidDuration {
+                interval,
+                error,
+            })),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! The `Json` and `JsonText` field types, enabled with the `json` feature.
+
+use crate::simple_query::FromSqlText;
+
+use bytes::{BufMut, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Wrapper mapping `json` and `jsonb` columns to and from any serde type.
+///
+/// When mapping a row, a value which can't be deserialized into `T` results
+/// in an [`Error::ColumnConversion`] naming the column, whose source is the
+/// `serde_json` error.
+///
+/// `Json<T>` serializes and deserializes exactly like `T`, so it can be used
+/// in API types as-is.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    /// Unwraps the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for Json<T> {
+    fn from(value: T) -> Self {
+        Json(value)
+    }
+}
+
+impl<'a, T: Deserialize<'a>> FromSql<'a> for Json<T> {
+    fn from_sql(
+        ty: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if *ty == Type::JSONB {
+            match raw.split_first() {
+                Some((1, rest)) => raw = rest,
+                _ => return Err("unsupported JSONB encoding version".into()),
+            }
+        }
+
+        serde_json::from_slice(raw).map(Json).map_err(Into::into)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::JSON || *ty == Type::JSONB
+    }
+}
+
+impl<T: Serialize + fmt::Debug> ToSql for Json<T> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        if *ty == Type::JSONB {
+            out.put_u8(1);
+        }
+
+        serde_json::to_writer(out.writer(), &self.0)?;
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::JSON || *ty == Type::JSONB
+    }
+
+    to_sql_checked!();
+}
+
+impl<T: Serialize> Serialize for Json<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Json<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Json)
+    }
+}
+
+/// Wrapper mapping JSON documents stored in `text` columns, as well as `json`
+/// and `jsonb` ones, to any serde type, for `#[pg_mapper(json_text)]` fields.
+///
+/// Accepting every column type holding JSON lets a mapping survive the
+/// migration of a `text` column to `jsonb`. A text which can't be
+/// deserialized into `T` results in an [`Error::ColumnConversion`] naming the
+/// column, whose source is an [`InvalidJsonText`] holding the start of the
+/// text.
+///
+/// With the derive, `#[pg_mapper(json_text)]` fields of type `T` or
+/// `Option<T>` are fetched through this type and unwrapped.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+/// [`InvalidJsonText`]: struct.InvalidJsonText.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct JsonText<T>(pub T);
+
+impl<T> JsonText<T> {
+    /// Unwraps the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for JsonText<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for JsonText<T> {
+    fn from(value: T) -> Self {
+        JsonText(value)
+    }
+}
+
+impl<T: DeserializeOwned> JsonText<T> {
+    fn parse(text: &str) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        serde_json::from_str(text)
+            .map(JsonText)
+            .map_err(|source| InvalidJsonText::new(text, source).into())
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for JsonText<T> {
+    /// Deserializes a string holding a JSON document, or the document itself,
+    /// as `row_to_json` embeds `json` and `jsonb` columns as is.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(text) => {
+                Self::parse(&text).map_err(serde::de::Error::custom)
+            }
+            value => serde_json::from_value(value)
+                .map(JsonText)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl<'a, T: DeserializeOwned> FromSql<'a> for JsonText<T> {
+    fn from_sql(
+        ty: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if *ty == Type::JSONB {
+            match raw.split_first() {
+                Some((1, rest)) => raw = rest,
+                _ => return Err("unsupported JSONB encoding version".into()),
+            }
+        }
+
+        Self::parse(std::str::from_utf8(raw)?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(
+            *ty,
+            Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::JSON | Type::JSONB
+        )
+    }
+}
+
+impl<T: DeserializeOwned> FromSqlText for JsonText<T> {
+    fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+        Self::parse(text)
+    }
+}
+
+/// The source of an [`Error::ColumnConversion`] for a text which isn't a
+/// valid JSON document of the requested type, holding the start of the
+/// text.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[derive(Debug)]
+pub struct InvalidJsonText {
+    snippet: String,
+    source: serde_json::Error,
+}
+
+impl InvalidJsonText {
+    /// The number of characters of the text kept in the snippet.
+    const SNIPPET_LENGTH: usize = 64;
+
+    fn new(text: &str, source: serde_json::Error) -> Self {
+        let mut snippet = text.chars().take(Self::SNIPPET_LENGTH).collect::<String>();
+        if snippet.len() < text.len() {
+            snippet.push_str("...");
+        }
+
+        InvalidJsonText { snippet, source }
+    }
+
+    /// Get the start of the text, followed by `...` if it's truncated.
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+}
+
+impl fmt::Display for InvalidJsonText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid JSON text {:?}: {}", self.snippet, self.source)
+    }
+}
+
+impl StdError for InvalidJsonText {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
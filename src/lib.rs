@@ -46,23 +46,161 @@
 //! `impl FromTokioPostgresRow<::tokio_postgres::row::Row> for T` and
 //! `impl FromTokioPostgresRow<&::tokio_postgres::row::Row> for T` implementations
 //! - `pg-mapper` which, for each of the above features, implements
-//! `pg-mapper`'s `FromTokioPostgresRow` trait
+//!   `pg-mapper`'s `FromTokioPostgresRow` trait
 //!
 //!
 //! This will derive implementations for converting from owned and referenced
 //! `tokio-postgres::row::Row`s, as well as implementing `pg-mapper`'s
 //! `FromTokioPostgresRow` trait for non-panicking conversions.
 #[cfg(feature = "derive")]
-#[allow(unused_imports)]
 pub extern crate tokio_pg_mapper_derive;
 
+#[doc(hidden)]
+pub use bytes;
+
 #[cfg(feature = "derive")]
 #[doc(hidden)]
 pub use tokio_pg_mapper_derive::*;
 
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "bb8")]
+pub mod bb8;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "client")]
+pub mod codegen;
+#[cfg(feature = "copy")]
+pub mod copy;
+#[cfg(feature = "client")]
+pub mod crud;
+#[cfg(feature = "deadpool")]
+pub mod deadpool;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub mod notification;
+#[cfg(feature = "query")]
+pub mod query;
+#[cfg(feature = "client")]
+pub mod repository;
+#[cfg(feature = "client")]
+pub mod statement_cache;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+mod bits;
+mod bytea;
+mod capture;
+mod composite;
+mod config;
+mod enum_text;
+mod epoch;
+mod geometry;
+mod hstore;
+mod ident;
+mod integer;
+mod interval;
+mod ltree;
+mod macaddr;
+mod money;
+mod numeric;
+mod pg_char;
+mod pivot;
+mod range;
+mod row_text;
+mod rows;
+mod schema;
+mod secret;
+mod text_search;
+mod xml;
+pub mod simple_query;
+
+#[cfg(feature = "client")]
+pub use client::{
+    insert_many, query_as_join2, query_as_join3, query_as_join4, ClientExt,
+    MappedRowStream, Mapper, Page,
+};
+#[cfg(feature = "client")]
+pub use codegen::{generate_structs, StructGenerator};
+#[cfg(feature = "client")]
+pub use crud::TokioPostgresCrud;
+#[cfg(feature = "client")]
+pub use repository::Repository;
+#[cfg(feature = "client")]
+pub use statement_cache::{StatementCache, StatementKind};
+#[cfg(feature = "copy")]
+#[doc(hidden)]
+pub use copy::try_get_copy_column;
+#[cfg(feature = "copy")]
+pub use copy::{FromBinaryCopyOutRow, MappedCopyOutStream};
+pub use bits::PgBits;
+pub use bytea::PgBytea;
+#[doc(hidden)]
+pub use capture::capture_rest;
+pub use capture::CapturedValue;
+pub use composite::CompositeAttributeError;
+#[doc(hidden)]
+pub use config::resolve_configured_column_indices;
+pub use config::MapperConfig;
+#[doc(hidden)]
+pub use epoch::{from_epoch, EpochParam, EpochParamValue};
+pub use epoch::{EpochOutOfRange, EpochTimestamp};
+#[doc(hidden)]
+pub use composite::{composite_fields, CompositeReader, CompositeWriter};
+pub use enum_text::{EnumText, InvalidEnumText};
+pub use geometry::{PgBox, PgPath, PgPoint};
+pub use hstore::PgHstore;
+#[doc(hidden)]
+pub use ident::{quote_identifier, quote_table, select_statement, SqlBuffer};
+pub use integer::{IntegerOutOfRange, PgNonZero, PgU32, PgU64};
+pub use interval::{IntervalError, InvalidDuration, PgDuration, PgInterval};
+pub use ltree::{PgLquery, PgLtree};
+pub use macaddr::{PgMacAddr, PgMacAddr8};
+pub use money::PgMoney;
+pub use numeric::{NumericError, PgNumeric};
+pub use pg_char::{InvalidChar, PgChar};
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub use pivot::parse_pivot_json;
+pub use pivot::DuplicatePivotKey;
+#[doc(hidden)]
+pub use pivot::{
+    parse_pivot_text, pivot_field, pivot_optional_field, pivot_values, PivotParse,
+};
+pub use range::PgRange;
+pub use row_text::row_to_text;
+pub use rows::FromTokioPostgresRows;
+pub use schema::{ColumnMetadata, SchemaDiff, SchemaIssue, SchemaSuggestion};
+#[cfg(feature = "client")]
+pub use schema::{diff_schema, validate};
+pub use secret::{Secret, SecretValue};
+pub use text_search::{PgTsQuery, PgTsVector, TsLexeme, TsWeight};
+pub use xml::PgXml;
+#[cfg(feature = "json")]
+pub use json::{InvalidJsonText, Json, JsonText};
+#[cfg(feature = "json")]
+pub use notification::FromNotificationPayload;
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub use notification::{parse_notification_payload, try_get_json_field};
+#[doc(hidden)]
+pub use simple_query::{try_get_text_column, try_get_text_column_empty_as_null};
+pub use simple_query::{FromSimpleQueryRow, FromSqlText};
+
+use ident::join_identifiers;
 use tokio_postgres::row::Row as TokioRow;
+use tokio_postgres::error::{DbError, SqlState};
+use tokio_postgres::types::{FromSql, ToSql, Type, WasNull, WrongType};
 
+use std::backtrace::Backtrace;
 use std::error::Error as StdError;
+use std::rc::Rc;
+use std::sync::Arc;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 /// Trait containing various methods for converting from a `tokio-postgres` Row
@@ -83,11 +221,11 @@ pub trait FromTokioPostgresRow: Sized {
     /// Returns [`Error::ColumnNotFound`] if the column in a mapping was not
     /// found.
     ///
-    /// Returns [`Error::Conversion`] if there was an error converting the row
+    /// Returns [`Error::ColumnConversion`] if there was an error converting the row
     /// column to the requested type.
     ///
     /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
-    /// [`Error::Conversion`]: enum.Error.html#variant.Conversion
+    /// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
     fn from_row(row: TokioRow) -> Result<Self, Error>;
 
     /// Converts from a `tokio-postgres` `Row` into a mapped type, borrowing the
@@ -98,19 +236,198 @@ pub trait FromTokioPostgresRow: Sized {
     /// Returns [`Error::ColumnNotFound`] if the column in a mapping was not
     /// found.
     ///
-    /// Returns [`Error::Conversion`] if there was an error converting the row
+    /// Returns [`Error::ColumnConversion`] if there was an error converting the row
     /// column into the requested type.
     ///
     /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
-    /// [`Error::Conversion`]: enum.Error.html#variant.Conversion
+    /// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
     fn from_row_ref(row: &TokioRow) -> Result<Self, Error>;
 
-    /// Get the name of the annotated sql table name.
+    /// Converts from a `tokio-postgres` `Row` whose columns are in the order
+    /// of [`columns`], fetching every column by position rather than looking
+    /// it up by name.
+    ///
+    /// This is only correct when the select list is under control, such as
+    /// `SELECT {sql_fields}`. In debug builds, the column names are checked
+    /// and a mismatch panics, so misuse fails loudly in tests.
+    ///
+    /// Without the `pg_mapper_derive` crate's `TokioPostgresMapper`
+    /// proc-macro, this defaults to [`from_row_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the row has fewer columns than
+    /// mapped.
+    ///
+    /// Returns [`Error::ColumnConversion`] if there was an error converting the row
+    /// column to the requested type.
+    ///
+    /// [`columns`]: #method.columns
+    /// [`from_row_ref`]: #tymethod.from_row_ref
+    /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+    /// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+    fn from_row_indexed(row: &TokioRow) -> Result<Self, Error> {
+        Self::from_row_ref(row)
+    }
+
+    /// Converts a batch of `tokio-postgres` `Row`s into mapped types,
+    /// consuming the given `Row`s.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by [`from_row`].
+    ///
+    /// [`from_row`]: #tymethod.from_row
+    fn from_rows(rows: Vec<TokioRow>) -> Result<Vec<Self>, Error> {
+        let mut values = Vec::with_capacity(rows.len());
+        for row in rows {
+            values.push(Self::from_row(row)?);
+        }
+
+        Ok(values)
+    }
+
+    /// Converts a batch of `tokio-postgres` `Row`s into mapped types,
+    /// borrowing the given `Row`s.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by [`from_row_ref`].
+    ///
+    /// [`from_row_ref`]: #tymethod.from_row_ref
+    fn from_row_refs(rows: &[TokioRow]) -> Result<Vec<Self>, Error> {
+        let mut values = Vec::with_capacity(rows.len());
+        for row in rows {
+            values.push(Self::from_row_ref(row)?);
+        }
+
+        Ok(values)
+    }
+
+    /// Converts a batch of `tokio-postgres` `Row`s into mapped types on the
+    /// threads of the global `rayon` pool, consuming the given `Row`s.
+    ///
+    /// Mapping is CPU-bound, so large batches of wide rows are mapped faster
+    /// in parallel, while small ones are faster to map with [`from_rows`].
+    /// The values are returned in the order of their rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Row`] wrapping the error of the first row which
+    /// failed to be mapped, with the index of the row.
+    ///
+    /// [`from_rows`]: #method.from_rows
+    /// [`Error::Row`]: enum.Error.html#variant.Row
+    #[cfg(feature = "rayon")]
+    fn from_rows_par(rows: Vec<TokioRow>) -> Result<Vec<Self>, Error>
+    where
+        Self: Send,
+    {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        let results = rows
+            .into_par_iter()
+            .map(Self::from_row)
+            .collect::<Vec<_>>();
+
+        let mut values = Vec::with_capacity(results.len());
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(value) => values.push(value),
+                Err(err) => {
+                    return Err(Error::Row {
+                        index,
+                        source: Box::new(err),
+                    })
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Converts a batch of `tokio-postgres` `Row`s into mapped types,
+    /// consuming the given `Row`s, and setting aside the rows which fail to
+    /// be mapped rather than stopping at the first one.
+    ///
+    /// Returns the mapped values in the order of their rows, along with the
+    /// index of every row which failed and its error from [`from_row`].
+    ///
+    /// [`from_row`]: #tymethod.from_row
+    fn from_rows_filtered(rows: Vec<TokioRow>) -> (Vec<Self>, Vec<(usize, Error)>) {
+        let mut values = Vec::with_capacity(rows.len());
+        let mut errors = Vec::new();
+
+        for (idx, row) in rows.into_iter().enumerate() {
+            match Self::from_row(row) {
+                Ok(value) => values.push(value),
+                Err(err) => errors.push((idx, err)),
+            }
+        }
+
+        (values, errors)
+    }
+
+    /// Converts from a `tokio-postgres` `Row` into a mapped type, borrowing
+    /// the given `Row`, and looking every column up under the name given by
+    /// the [`MapperConfig`].
+    ///
+    /// With the default configuration, this is [`from_row_ref`]. Without the
+    /// `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro, this
+    /// defaults to [`from_row_ref`], ignoring the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the column in a mapping was not
+    /// found under its configured name.
+    ///
+    /// Returns [`Error::ColumnConversion`] if there was an error converting the row
+    /// column into the requested type.
+    ///
+    /// [`MapperConfig`]: struct.MapperConfig.html
+    /// [`from_row_ref`]: #tymethod.from_row_ref
+    /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+    /// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+    fn from_row_with_config(
+        row: &TokioRow,
+        _config: &MapperConfig,
+    ) -> Result<Self, Error> {
+        Self::from_row_ref(row)
+    }
+
+    /// Converts a batch of `tokio-postgres` `Row`s into mapped types,
+    /// borrowing the given `Row`s, and looking every column up under the
+    /// name given by the [`MapperConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by [`from_row_with_config`].
+    ///
+    /// [`MapperConfig`]: struct.MapperConfig.html
+    /// [`from_row_with_config`]: #method.from_row_with_config
+    fn from_rows_with_config(
+        rows: &[TokioRow],
+        config: &MapperConfig,
+    ) -> Result<Vec<Self>, Error> {
+        let mut values = Vec::with_capacity(rows.len());
+        for row in rows {
+            values.push(Self::from_row_with_config(row, config)?);
+        }
+
+        Ok(values)
+    }
+
+    /// Get the name of the annotated sql table, quoted like the column names
+    /// if needed.
+    ///
+    /// Every dot-separated part of the name is quoted on its own, so a table
+    /// declared as "auth.users" stays qualified by its schema.
     ///
     /// Example:
     ///
-    /// The following will return the String " user ".
-    /// Note the extra spaces on either side to avoid incorrect formatting.
+    /// The following will return the String "\"user\"", quoted as `user` is
+    /// a reserved keyword, and without padding. See [`sql_table_trimmed`] for
+    /// a name guaranteed not to be padded.
     ///
     /// ```
     ///     #[derive(PostgresMapper)]
@@ -120,15 +437,19 @@ pub trait FromTokioPostgresRow: Sized {
     ///         pub email: Option<String>,
     ///     }
     /// ```
+    ///
+    /// [`sql_table_trimmed`]: #method.sql_table_trimmed
     fn sql_table() -> String;
-    
-    
-    /// Get a list of the field names, excluding table name prefix. 
+
+    /// Get a list of the field names, excluding table name prefix.
+    ///
+    /// Every name is padded with a space on either side, to avoid incorrect
+    /// formatting when interpolated, and the names are joined with ", ".
     ///
     /// Example:
     ///
-    /// The following will return the String " id, email ".
-    /// Note the extra spaces on either side to avoid incorrect formatting.
+    /// The following will return the String " id ,  email ". See
+    /// [`sql_fields_trimmed`] for the unpadded "id, email".
     ///
     /// ```
     ///     #[derive(PostgresMapper)]
@@ -139,17 +460,90 @@ pub trait FromTokioPostgresRow: Sized {
     ///     }
     /// ```
     ///
-    fn sql_fields() -> String;
+    /// [`sql_fields_trimmed`]: #method.sql_fields_trimmed
+    fn sql_fields() -> String {
+        Self::columns()
+            .iter()
+            .map(|column| format!(" {} ", quote_identifier(column)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
 
     /// Get a list of the field names, including table name prefix.
     ///
     /// We also expect an attribute tag #[pg_mapper(table = "foo")]
-    /// so that a scoped list of fields can be generated.
+    /// so that a scoped list of fields can be generated. The names are padded
+    /// and joined like [`sql_fields`].
     ///
     /// Example:
     ///
-    /// The following will return the String " user.id, user.email ".
-    /// Note the extra spaces on either side to avoid incorrect formatting.
+    /// The following will return the String " \"user\".id ,  \"user\".email ".
+    /// See [`sql_table_fields_trimmed`] for the unpadded
+    /// "\"user\".id, \"user\".email".
+    ///
+    /// ```
+    ///     #[derive(PostgresMapper)]
+    ///     #[pg_mapper(table = "user")]
+    ///     pub struct User {
+    ///         pub id: i64,
+    ///         pub email: Option<String>,
+    ///     }
+    /// ```
+    ///
+    /// [`sql_fields`]: #method.sql_fields
+    /// [`sql_table_fields_trimmed`]: #method.sql_table_fields_trimmed
+    fn sql_table_fields() -> String {
+        let table = Self::sql_table();
+
+        Self::columns()
+            .iter()
+            .map(|column| format!(" {}.{} ", table, quote_identifier(column)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Get the name of the annotated sql table, without surrounding spaces.
+    ///
+    /// For the `User` example of [`sql_table`], this returns "\"user\"".
+    ///
+    /// [`sql_table`]: #tymethod.sql_table
+    fn sql_table_trimmed() -> String {
+        Self::sql_table().trim().to_string()
+    }
+
+    /// Get a list of the field names, excluding table name prefix, joined
+    /// with ", " and without any padding.
+    ///
+    /// For the `User` example of [`sql_fields`], this returns "id, email".
+    ///
+    /// [`sql_fields`]: #method.sql_fields
+    fn sql_fields_trimmed() -> String {
+        join_identifiers(Self::columns().iter().copied())
+    }
+
+    /// Get a list of the field names, including table name prefix, joined
+    /// with ", " and without any padding.
+    ///
+    /// For the `User` example of [`sql_table_fields`], this returns
+    /// "\"user\".id, \"user\".email".
+    ///
+    /// [`sql_table_fields`]: #method.sql_table_fields
+    fn sql_table_fields_trimmed() -> String {
+        let table = Self::sql_table_trimmed();
+
+        Self::columns()
+            .iter()
+            .map(|column| format!("{}.{}", table, quote_identifier(column)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Get the column names of the mapping, excluding table name prefix, in
+    /// field declaration order.
+    ///
+    /// Example:
+    ///
+    /// The following will return `&["id", "email"]`.
     ///
     /// ```
     ///     #[derive(PostgresMapper)]
@@ -160,52 +554,1673 @@ pub trait FromTokioPostgresRow: Sized {
     ///     }
     /// ```
     ///
-    fn sql_table_fields() -> String;
+    /// Defaults to no columns, so that implementations written before this
+    /// method existed still compile. Those should override it, as the
+    /// provided methods built on the column names, such as
+    /// [`sql_fields_except`] and [`has_column`], otherwise see no columns.
+    ///
+    /// [`sql_fields_except`]: #method.sql_fields_except
+    /// [`has_column`]: #method.has_column
+    fn columns() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Get the metadata of the mapped columns, in field declaration order,
+    /// which [`validate`] compares with the schema of the table.
+    ///
+    /// The derived implementation records the Rust type of every field, and
+    /// whether it is an `Option`. By default, every column of [`columns`] is
+    /// [`untyped`].
+    ///
+    /// [`validate`]: fn.validate.html
+    /// [`columns`]: #tymethod.columns
+    /// [`untyped`]: struct.ColumnMetadata.html#method.untyped
+    fn column_metadata() -> Vec<ColumnMetadata> {
+        Self::columns()
+            .iter()
+            .copied()
+            .map(ColumnMetadata::untyped)
+            .collect()
+    }
+
+    /// Get the `pg_type` hint of every mapped column, in field declaration
+    /// order, as given by the `#[pg_mapper(pg_type = "uuid")]` field
+    /// attribute.
+    ///
+    /// Hints are free-form and passed through verbatim. They take precedence
+    /// over the types inferred from the fields in [`create_table_stmt`],
+    /// [`validate`] and derived composite types.
+    ///
+    /// The default implementation returns no columns.
+    ///
+    /// [`create_table_stmt`]: #method.create_table_stmt
+    /// [`validate`]: fn.validate.html
+    fn column_types() -> &'static [(&'static str, Option<&'static str>)] {
+        &[]
+    }
+
+    /// Get the Postgres type of every mapped column, in field declaration
+    /// order, such as for checking the columns of a statement or writing a
+    /// binary `COPY`.
+    ///
+    /// The types are those of the [`column_metadata`], given by the
+    /// `#[pg_mapper(pg_type = "uuid")]` field attribute or inferred from the
+    /// field types as in [`create_table_stmt`]. A column's type is `None` if
+    /// it isn't known, or isn't a built-in type with a fixed OID, such as
+    /// `hstore`.
+    ///
+    /// For the `User` example of [`create_table_stmt`], this returns
+    /// `vec![Some(Type::INT8), Some(Type::TEXT), Some(Type::TEXT)]`.
+    ///
+    /// [`column_metadata`]: #method.column_metadata
+    /// [`create_table_stmt`]: #method.create_table_stmt
+    fn column_pg_types() -> Vec<Option<Type>> {
+        Self::column_metadata()
+            .iter()
+            .map(ColumnMetadata::postgres_type)
+            .collect()
+    }
+
+    /// Get a `CREATE TABLE IF NOT EXISTS` statement for the mapped table,
+    /// such as for integration tests. It is no replacement for migrations.
+    ///
+    /// Example:
+    ///
+    /// ```
+    ///     #[derive(PostgresMapper)]
+    ///     #[pg_mapper(table = "users")]
+    ///     pub struct User {
+    ///         #[pg_mapper(primary_key)]
+    ///         pub id: i64,
+    ///         pub name: String,
+    ///         pub email: Option<String>,
+    ///     }
+    /// ```
+    ///
+    /// `User::create_table_stmt()` returns the String
+    /// "CREATE TABLE IF NOT EXISTS users (id BIGINT NOT NULL, name TEXT NOT
+    /// NULL, email TEXT, PRIMARY KEY (id))".
+    ///
+    /// The columns of `Option` fields are nullable, and the Postgres types
+    /// are inferred from the field types, ignoring the `Option`:
+    ///
+    /// | Rust                           | Postgres                        |
+    /// |--------------------------------|---------------------------------|
+    /// | `bool`                         | `BOOLEAN`                       |
+    /// | `i8`                           | `"char"`                        |
+    /// | `i16`, `i32`, `i64`            | `SMALLINT`, `INTEGER`, `BIGINT` |
+    /// | `u32`                          | `OID`                           |
+    /// | `f32`, `f64`                   | `REAL`, `DOUBLE PRECISION`      |
+    /// | `String`, `&str`               | `TEXT`                          |
+    /// | `Vec<u8>`, `Bytes`, `PgBytea`  | `BYTEA`                         |
+    /// | `SystemTime`, `DateTime<Tz>`   | `TIMESTAMPTZ`                   |
+    /// | `NaiveDateTime`                | `TIMESTAMP`                     |
+    /// | `NaiveDate`, `NaiveTime`       | `DATE`, `TIME`                  |
+    /// | `IpAddr`                       | `INET`                          |
+    /// | `Uuid`                         | `UUID`                          |
+    /// | `serde_json::Value`, `Json<T>` | `JSONB`                         |
+    /// | `PgNumeric`, `PgInterval`, ... | `NUMERIC`, `INTERVAL`, ...      |
+    /// | `Vec<T>`                       | the type of `T` with `[]`       |
+    ///
+    /// The type of other fields must be given with the
+    /// `#[pg_mapper(pg_type = "uuid")]` field attribute, or the column is
+    /// declared as `TEXT`.
+    fn create_table_stmt() -> String {
+        let columns = Self::column_metadata();
+
+        let mut definitions = columns
+            .iter()
+            .map(|column| {
+                let pg_type = column.pg_type.unwrap_or("TEXT");
+                let null = if column.nullable { "" } else { " NOT NULL" };
+
+                format!("{} {}{}", quote_identifier(column.name), pg_type, null)
+            })
+            .collect::<Vec<String>>();
+
+        let primary_key = columns
+            .iter()
+            .filter(|column| column.primary_key)
+            .map(|column| column.name)
+            .collect::<Vec<&str>>();
+        if !primary_key.is_empty() {
+            definitions.push(format!("PRIMARY KEY ({})", join_identifiers(primary_key)));
+        }
+
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            Self::sql_table_trimmed(),
+            definitions.join(", ")
+        )
+    }
+
+    /// Get a list of the field names, excluding table name prefix, omitting
+    /// the given columns.
+    ///
+    /// The result is formatted like [`sql_fields`], so
+    /// `User::sql_fields_except(&["email"])` returns the String " id ".
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if one of the excluded columns is
+    /// not part of the mapping.
+    ///
+    /// [`sql_fields`]: #method.sql_fields
+    /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+    fn sql_fields_except(exclude: &[&str]) -> Result<String, Error> {
+        Ok(columns_except(Self::columns(), exclude)?
+            .map(|column| format!(" {} ", quote_identifier(column)))
+            .collect::<Vec<String>>()
+            .join(", "))
+    }
+
+    /// Get a list of the field names, including table name prefix, omitting
+    /// the given columns.
+    ///
+    /// The result is formatted like [`sql_table_fields`], so
+    /// `User::sql_table_fields_except(&["email"])` returns the String
+    /// " \"user\".id ".
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if one of the excluded columns is
+    /// not part of the mapping.
+    ///
+    /// [`sql_table_fields`]: #method.sql_table_fields
+    /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+    fn sql_table_fields_except(exclude: &[&str]) -> Result<String, Error> {
+        let table = Self::sql_table();
+
+        Ok(columns_except(Self::columns(), exclude)?
+            .map(|column| format!(" {0}.{1} ", table, quote_identifier(column)))
+            .collect::<Vec<String>>()
+            .join(", "))
+    }
+
+    /// Returns whether the given column name is part of the mapping.
+    ///
+    /// This is useful for validating user-supplied column names before
+    /// interpolating them into SQL.
+    fn has_column(name: &str) -> bool {
+        Self::columns().contains(&name)
+    }
+
+    /// Get an `ORDER BY` clause for the given column, which must be part of
+    /// the mapping.
+    ///
+    /// Example:
+    ///
+    /// `User::order_by_clause("email", SortDirection::Desc)` will return the
+    /// String " ORDER BY email DESC ".
+    /// Note the extra spaces on either side to avoid incorrect formatting.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the column is not part of the
+    /// mapping, so unknown names never reach the generated SQL.
+    ///
+    /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+    fn order_by_clause(name: &str, direction: SortDirection) -> Result<String, Error> {
+        if !Self::has_column(name) {
+            return Err(Error::ColumnNotFound {
+                column: name.to_string(),
+                type_name: None,
+                backtrace: capture_backtrace(),
+            });
+        }
+
+        Ok(format!(
+            " ORDER BY {} {} ",
+            quote_identifier(name),
+            direction
+        ))
+    }
 }
 
-/// General error type returned throughout the library.
-#[derive(Debug)]
-pub enum Error {
-    /// A column in a row was not found.
-    ColumnNotFound,
-    /// An error from the `tokio-postgres` crate while converting a type.
-    Conversion(Box<dyn StdError + Send + Sync>),
-    /// Used in a scenario where tokios_postgres::Error::into_source returns None
-    UnknownTokioPG(String)
+/// Direction of an `ORDER BY` clause built by
+/// [`FromTokioPostgresRow::order_by_clause`].
+///
+/// [`FromTokioPostgresRow::order_by_clause`]: trait.FromTokioPostgresRow.html#method.order_by_clause
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SortDirection {
+    /// Ascending order, `ASC`.
+    Asc,
+    /// Descending order, `DESC`.
+    Desc,
 }
 
-impl From<tokio_postgres::Error> for Error {
-    fn from(err: tokio_postgres::Error) -> Self {
-        let reason = err.to_string();
-        if let Some(source) = err.into_source() {
-            source.into()
-        } else {
-            Error::UnknownTokioPG(reason)
+impl Display for SortDirection {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            SortDirection::Asc => f.write_str("ASC"),
+            SortDirection::Desc => f.write_str("DESC"),
         }
     }
 }
 
-impl From<Box<dyn StdError + Send + Sync>> for Error {
-    fn from(err: Box<dyn StdError + Send + Sync>) -> Self {
-        Error::Conversion(err)
+/// Trait for mapped types identified by a primary key, providing the
+/// statements that depend on it.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro,
+/// this will be implemented on types with at least one field annotated with
+/// `#[pg_mapper(primary_key)]`.
+///
+/// Rows whose [`soft_delete_column`] isn't NULL are left out of the `SELECT`
+/// statements, as deleted.
+///
+/// [`soft_delete_column`]: #method.soft_delete_column
+pub trait TokioPostgresPrimaryKey: FromTokioPostgresRow {
+    /// Get the primary key column names, in field declaration order.
+    fn primary_key_columns() -> &'static [&'static str];
+
+    /// Get the name of the column annotated with
+    /// `#[pg_mapper(soft_delete)]`, such as a `deleted_at` timestamp, which
+    /// is set on the rows deleted without removing them.
+    ///
+    /// The default implementation returns `None`.
+    fn soft_delete_column() -> Option<&'static str> {
+        None
+    }
+
+    /// Get a `SELECT` statement for every row, ordered by primary key.
+    ///
+    /// For the type of the [`select_page_stmt`] example this will return the
+    /// String "SELECT  id ,  email  FROM \"user\" ORDER BY id".
+    ///
+    /// [`select_page_stmt`]: #method.select_page_stmt
+    fn select_all_stmt() -> String {
+        format!(
+            "SELECT {} FROM {}{} ORDER BY {}",
+            Self::sql_fields(),
+            Self::sql_table(),
+            where_not_deleted::<Self>(None),
+            join_identifiers(Self::primary_key_columns().iter().copied()),
+        )
+    }
+
+    /// Get a `SELECT` statement for one page of rows ordered by primary key,
+    /// taking the limit as `$1` and the offset as `$2`.
+    ///
+    /// Example:
+    ///
+    /// The following will return the String
+    /// "SELECT  id ,  email  FROM \"user\" ORDER BY id LIMIT $1 OFFSET $2",
+    /// or with a `deleted_at` [`soft_delete_column`] the String
+    /// "SELECT  id ,  email ,  deleted_at  FROM \"user\" WHERE deleted_at IS
+    /// NULL ORDER BY id LIMIT $1 OFFSET $2".
+    ///
+    /// ```
+    ///     #[derive(PostgresMapper)]
+    ///     #[pg_mapper(table = "user")]
+    ///     pub struct User {
+    ///         #[pg_mapper(primary_key)]
+    ///         pub id: i64,
+    ///         pub email: Option<String>,
+    ///     }
+    /// ```
+    ///
+    /// [`soft_delete_column`]: #method.soft_delete_column
+    fn select_page_stmt() -> String {
+        format!(
+            "SELECT {} FROM {}{} ORDER BY {} LIMIT $1 OFFSET $2",
+            Self::sql_fields(),
+            Self::sql_table(),
+            where_not_deleted::<Self>(None),
+            join_identifiers(Self::primary_key_columns().iter().copied()),
+        )
+    }
+
+    /// Get a keyset pagination `SELECT` statement for the rows following a
+    /// given primary key, taking the key as `$1` (or `$1`..`$n` for composite
+    /// keys) and the limit as the next placeholder.
+    ///
+    /// For the type above this will return the String
+    /// "SELECT  id ,  email  FROM \"user\" WHERE id > $1 ORDER BY id LIMIT $2".
+    /// Composite keys are compared as a row, as in `WHERE (a, b) > ($1, $2)`.
+    /// With a [`soft_delete_column`], the condition is followed by
+    /// `AND deleted_at IS NULL`.
+    ///
+    /// [`soft_delete_column`]: #method.soft_delete_column
+    fn select_after_stmt() -> String {
+        let primary_key = join_identifiers(Self::primary_key_columns().iter().copied());
+        let len = Self::primary_key_columns().len();
+        let placeholders = (1..=len)
+            .map(|i| format!("${}", i))
+            .collect::<Vec<String>>();
+
+        let condition = if len == 1 {
+            format!("{} > {}", primary_key, placeholders[0])
+        } else {
+            format!("({}) > ({})", primary_key, placeholders.join(", "))
+        };
+
+        format!(
+            "SELECT {} FROM {}{} ORDER BY {} LIMIT ${}",
+            Self::sql_fields(),
+            Self::sql_table(),
+            where_not_deleted::<Self>(Some(condition)),
+            primary_key,
+            len + 1,
+        )
+    }
+
+    /// Get the condition matching one row by primary key, taking the key
+    /// columns as placeholders numbered from `first_placeholder`.
+    ///
+    /// For the type above and a `first_placeholder` of 1 this will return
+    /// the String "id = $1", and "a = $1 AND b = $2" for composite keys.
+    fn primary_key_condition(first_placeholder: usize) -> String {
+        Self::primary_key_columns()
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                format!("{} = ${}", quote_identifier(column), first_placeholder + i)
+            })
+            .collect::<Vec<String>>()
+            .join(" AND ")
+    }
+
+    /// Get a `SELECT` statement for the row with a given primary key, taking
+    /// the key as `$1` (or `$1`..`$n` for composite keys).
+    ///
+    /// For the type above this will return the String
+    /// "SELECT  id ,  email  FROM \"user\" WHERE id = $1", followed by
+    /// `AND deleted_at IS NULL` with a [`soft_delete_column`].
+    ///
+    /// [`soft_delete_column`]: #method.soft_delete_column
+    fn select_by_primary_key_stmt() -> String {
+        format!(
+            "SELECT {} FROM {}{}",
+            Self::sql_fields(),
+            Self::sql_table(),
+            where_not_deleted::<Self>(Some(Self::primary_key_condition(1))),
+        )
+    }
+
+    /// Get a `DELETE` statement for the row with a given primary key, taking
+    /// the key as `$1` (or `$1`..`$n` for composite keys).
+    ///
+    /// For the type above this will return the String
+    /// "DELETE FROM \"user\" WHERE id = $1".
+    fn delete_by_primary_key_stmt() -> String {
+        format!(
+            "DELETE FROM {} WHERE {}",
+            Self::sql_table(),
+            Self::primary_key_condition(1),
+        )
     }
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        match self {
-            Error::ColumnNotFound => f.write_str("Tokio-postgres-mapper: Column not found"),
-            Error::UnknownTokioPG(reason) => f.write_str(reason),
-            Error::Conversion(err) => f.write_str(err.to_string().as_str()),
+/// Builds the `WHERE` clause of a `SELECT` of a mapped type from the given
+/// condition and the condition leaving out soft deleted rows, preceded by a
+/// space, or an empty string without conditions.
+#[doc(hidden)]
+pub fn where_not_deleted<T: TokioPostgresPrimaryKey>(
+    condition: Option<String>,
+) -> String {
+    let not_deleted = T::soft_delete_column()
+        .map(|column| format!("{} IS NULL", quote_identifier(column)));
+
+    match (condition, not_deleted) {
+        (Some(condition), Some(not_deleted)) => {
+            format!(" WHERE {} AND {}", condition, not_deleted)
+        }
+        (Some(condition), None) | (None, Some(condition)) => {
+            format!(" WHERE {}", condition)
         }
+        (None, None) => String::new(),
+    }
+}
+
+/// Trait for mapped types which can be inserted, providing the columns and
+/// query parameters of an `INSERT`.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(insert)]` container attribute, this will be
+/// implemented on types. Every field must then be `ToSql + Sync`.
+///
+/// Fields annotated with `#[pg_mapper(readonly)]`, such as generated
+/// columns, are left out of the `INSERT`.
+pub trait TokioPostgresInsert: FromTokioPostgresRow {
+    /// Get the inserted column names, in field declaration order.
+    fn insert_columns() -> &'static [&'static str];
+
+    /// Get references to the fields of the inserted columns, in the order of
+    /// [`insert_columns`].
+    ///
+    /// [`insert_columns`]: #tymethod.insert_columns
+    fn to_params(&self) -> Vec<&(dyn ToSql + Sync)>;
+
+    /// Get an `INSERT` statement for the given number of rows, whose
+    /// parameters are the [`to_params`] of each row in turn.
+    ///
+    /// Example:
+    ///
+    /// For the type below, `User::insert_stmt(2)` will return the String
+    /// "INSERT INTO \"user\" (name, email) VALUES ($1, $2), ($3, $4)".
+    ///
+    /// ```
+    ///     #[derive(PostgresMapper)]
+    ///     #[pg_mapper(table = "user", insert)]
+    ///     pub struct User {
+    ///         #[pg_mapper(readonly)]
+    ///         pub id: i64,
+    ///         pub name: String,
+    ///         pub email: Option<String>,
+    ///     }
+    /// ```
+    ///
+    /// [`to_params`]: #tymethod.to_params
+    fn insert_stmt(rows: usize) -> String {
+        let columns = Self::insert_columns();
+
+        let values = (0..rows)
+            .map(|row| {
+                let placeholders = (1..=columns.len())
+                    .map(|i| format!("${}", row * columns.len() + i))
+                    .collect::<Vec<String>>();
+
+                format!("({})", placeholders.join(", "))
+            })
+            .collect::<Vec<String>>();
+
+        format!(
+            "INSERT INTO {} ({}) VALUES {}",
+            Self::sql_table(),
+            join_identifiers(columns.iter().copied()),
+            values.join(", "),
+        )
+    }
+
+    /// Get an `INSERT` statement for one row returning every mapped column,
+    /// including the readonly ones filled in by the database.
+    ///
+    /// For the type above this will return the String
+    /// "INSERT INTO \"user\" (name, email) VALUES ($1, $2) RETURNING  id ,  name ,  email ".
+    fn insert_returning_stmt() -> String {
+        format!("{} RETURNING {}", Self::insert_stmt(1), Self::sql_fields())
+    }
+}
+
+fn columns_except<'a>(
+    columns: &'a [&'a str],
+    exclude: &'a [&str],
+) -> Result<impl Iterator<Item = &'a str>, Error> {
+    if let Some(name) = exclude.iter().find(|name| !columns.contains(name)) {
+        return Err(Error::ColumnNotFound {
+            column: name.to_string(),
+            type_name: None,
+            backtrace: capture_backtrace(),
+        });
+    }
+
+    Ok(columns
+        .iter()
+        .copied()
+        .filter(move |column| !exclude.contains(column)))
+}
+
+macro_rules! impl_from_row_for_wrapper {
+    ($($wrapper:ident),*) => {
+        $(
+            impl<T: FromTokioPostgresRow> FromTokioPostgresRow for $wrapper<T> {
+                fn from_row(row: TokioRow) -> Result<Self, Error> {
+                    T::from_row(row).map($wrapper::new)
+                }
+
+                fn from_row_ref(row: &TokioRow) -> Result<Self, Error> {
+                    T::from_row_ref(row).map($wrapper::new)
+                }
+
+                fn from_row_indexed(row: &TokioRow) -> Result<Self, Error> {
+                    T::from_row_indexed(row).map($wrapper::new)
+                }
+
+                fn from_rows(rows: Vec<TokioRow>) -> Result<Vec<Self>, Error> {
+                    T::from_rows(rows).map(|v| v.into_iter().map($wrapper::new).collect())
+                }
+
+                fn from_row_refs(rows: &[TokioRow]) -> Result<Vec<Self>, Error> {
+                    T::from_row_refs(rows).map(|v| v.into_iter().map($wrapper::new).collect())
+                }
+
+                fn from_rows_filtered(rows: Vec<TokioRow>) -> (Vec<Self>, Vec<(usize, Error)>) {
+                    let (values, errors) = T::from_rows_filtered(rows);
+
+                    (values.into_iter().map($wrapper::new).collect(), errors)
+                }
+
+                fn from_row_with_config(row: &TokioRow, config: &MapperConfig) -> Result<Self, Error> {
+                    T::from_row_with_config(row, config).map($wrapper::new)
+                }
+
+                fn from_rows_with_config(rows: &[TokioRow], config: &MapperConfig) -> Result<Vec<Self>, Error> {
+                    T::from_rows_with_config(rows, config)
+                        .map(|v| v.into_iter().map($wrapper::new).collect())
+                }
+
+                fn sql_table() -> String {
+                    T::sql_table()
+                }
+
+                fn sql_fields() -> String {
+                    T::sql_fields()
+                }
+
+                fn sql_table_fields() -> String {
+                    T::sql_table_fields()
+                }
+
+                fn columns() -> &'static [&'static str] {
+                    T::columns()
+                }
+
+                fn column_metadata() -> Vec<ColumnMetadata> {
+                    T::column_metadata()
+                }
+
+                fn column_types() -> &'static [(&'static str, Option<&'static str>)] {
+                    T::column_types()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_row_for_wrapper!(Box, Arc, Rc);
+
+/// Names of the fields overwritten by
+/// [`UpdateFromTokioPostgresRow::update_from_row`], in field declaration order.
+///
+/// [`UpdateFromTokioPostgresRow::update_from_row`]: trait.UpdateFromTokioPostgresRow.html#tymethod.update_from_row
+pub type UpdatedFields = Vec<&'static str>;
+
+/// Trait for refreshing an existing mapped value in place from a `Row` that
+/// may only contain a subset of the mapped columns.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro,
+/// this will automatically be implemented on types.
+pub trait UpdateFromTokioPostgresRow {
+    /// Overwrites every field whose column is present in the given `Row`,
+    /// leaving the fields of absent columns untouched.
+    ///
+    /// Returns the names of the fields that were updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnConversion`] if there was an error converting a row
+    /// column into the requested type. In that case no field is modified.
+    ///
+    /// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+    fn update_from_row(&mut self, row: &TokioRow) -> Result<UpdatedFields, Error>;
+}
+
+/// Trait for patch types, whose `Option` fields describe which columns of a
+/// row should be changed by an `UPDATE`.
+///
+/// When using the `pg_mapper_derive` crate's `PostgresPatch` proc-macro, this
+/// will automatically be implemented on types.
+///
+/// Example:
+///
+/// ```
+///     #[derive(PostgresPatch)]
+///     #[pg_patch(for = "User", table = "users")]
+///     pub struct UserPatch {
+///         pub name: Option<String>,
+///         #[pg_mapper(rename = "Email")]
+///         pub email: Option<Option<String>>,
+///     }
+/// ```
+///
+/// Fields are assigned to the column of the same name, unless renamed with
+/// `#[pg_mapper(rename = "...")]` like the fields of the patched type.
+pub trait TokioPostgresPatch {
+    /// Get the `SET` clause assigning every `Some` field, along with the
+    /// matching query parameters.
+    ///
+    /// Placeholders are numbered starting from `first_placeholder`, so a patch
+    /// with `name` and `email` set and a `first_placeholder` of 1 returns
+    /// "name = $1, email = $2".
+    ///
+    /// Returns an empty clause if no field is set, in which case the `UPDATE`
+    /// should be skipped.
+    fn set_clause(&self, first_placeholder: usize)
+        -> (String, Vec<&(dyn ToSql + Sync)>);
+
+    /// Get the name of the patched sql table.
+    fn sql_table() -> String;
+}
+
+/// Trait for filter types, whose `Option` fields describe the conditions of
+/// a `WHERE` clause.
+///
+/// When using the `pg_mapper_derive` crate's `PostgresFilter` proc-macro, this
+/// will automatically be implemented on types. Each field filters on the
+/// column of the same name using `=`, unless overridden with
+/// `#[pg_filter(column = "foo", op = ">=")]`. Columns are quoted like those of
+/// every other generated statement, and the operator must be one of `=`,
+/// `<>`, `!=`, `<`, `<=`, `>`, `>=`, `LIKE`, `ILIKE`, `NOT LIKE` or
+/// `NOT ILIKE`, any other failing the derive.
+///
+/// Example:
+///
+/// ```
+///     #[derive(PostgresFilter)]
+///     pub struct OrderFilter {
+///         pub status: Option<String>,
+///         pub customer_id: Option<i64>,
+///         #[pg_filter(column = "created_at", op = ">=")]
+///         pub created_after: Option<SystemTime>,
+///         #[pg_filter(column = "created_at", op = "<")]
+///         pub created_before: Option<SystemTime>,
+///     }
+/// ```
+pub trait TokioPostgresFilter {
+    /// Get the `WHERE` clause AND-ing together every `Some` field, along with
+    /// the matching query parameters.
+    ///
+    /// Placeholders are numbered starting from `first_placeholder`, so a
+    /// filter with `status` and `created_after` set and a `first_placeholder`
+    /// of 1 returns the String " WHERE status = $1 AND created_at >= $2 ".
+    /// Note the extra spaces on either side to avoid incorrect formatting.
+    ///
+    /// Returns an empty clause, without `WHERE`, if no field is set.
+    fn where_clause(
+        &self,
+        first_placeholder: usize,
+    ) -> (String, Vec<&(dyn ToSql + Sync)>);
+}
+
+/// Columns whose values differ between two instances of a mapped type, as
+/// returned by [`TokioPostgresDiff::diff`].
+///
+/// [`TokioPostgresDiff::diff`]: trait.TokioPostgresDiff.html#tymethod.diff
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ChangedColumns {
+    columns: Vec<&'static str>,
+}
+
+impl ChangedColumns {
+    /// Creates a new set of changed columns, in field declaration order.
+    pub fn new(columns: Vec<&'static str>) -> Self {
+        Self { columns }
+    }
+
+    /// Get the changed column names, in field declaration order.
+    pub fn columns(&self) -> &[&'static str] {
+        &self.columns
+    }
+
+    /// Returns whether the given column changed.
+    pub fn contains(&self, column: &str) -> bool {
+        self.columns.contains(&column)
+    }
+
+    /// Returns whether no column changed, in which case the `UPDATE` should
+    /// be skipped.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Get the `SET` clause assigning every changed column.
+    ///
+    /// Placeholders are numbered starting from `first_placeholder`, matching
+    /// the order of [`params`], so changed `name` and `email` columns with a
+    /// `first_placeholder` of 1 return "name = $1, email = $2".
+    ///
+    /// [`params`]: #method.params
+    pub fn set_clause(&self, first_placeholder: usize) -> String {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                format!("{} = ${}", quote_identifier(column), first_placeholder + i)
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Get the query parameters for [`set_clause`], referencing the fields
+    /// of the given (usually the updated) instance.
+    ///
+    /// [`set_clause`]: #method.set_clause
+    pub fn params<'a, T: TokioPostgresDiff>(
+        &self,
+        value: &'a T,
+    ) -> Vec<&'a (dyn ToSql + Sync)> {
+        value.changed_params(self)
+    }
+}
+
+/// Trait for comparing two instances of a mapped type column by column, in
+/// order to persist only what changed.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(diff)]` container attribute, this will be
+/// implemented on types. Every field must then be `PartialEq`.
+///
+/// Fields annotated with `#[pg_mapper(primary_key)]` or
+/// `#[pg_mapper(readonly)]` are never reported as changed.
+pub trait TokioPostgresDiff {
+    /// Get the columns whose values differ between `self` and `other`.
+    fn diff(&self, other: &Self) -> ChangedColumns;
+
+    /// Get references to the fields of the given changed columns, in field
+    /// declaration order.
+    fn changed_params(&self, changed: &ChangedColumns) -> Vec<&(dyn ToSql + Sync)>;
+}
+
+/// Trait for converting from a borrowed `tokio-postgres` Row into a mapped
+/// type whose fields borrow from that `Row`.
+///
+/// This avoids allocating for every `&'a str` or `&'a [u8]` field, at the cost
+/// of the mapped value being unable to outlive the `Row` it was built from.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(borrowed)]` container attribute, this will be
+/// implemented instead of [`FromTokioPostgresRow`].
+///
+/// Example:
+///
+/// ```
+///     #[derive(PostgresMapper)]
+///     #[pg_mapper(borrowed)]
+///     pub struct LogLine<'a> {
+///         pub host: &'a str,
+///         pub payload: &'a [u8],
+///     }
+/// ```
+pub trait FromTokioPostgresRowBorrowed<'a>: Sized {
+    /// Converts from a `tokio-postgres` `Row` into a mapped type, borrowing
+    /// the given `Row` for as long as the mapped type lives.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the column in a mapping was not
+    /// found.
+    ///
+    /// Returns [`Error::ColumnConversion`] if there was an error converting the row
+    /// column into the requested type.
+    ///
+    /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+    /// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+    fn from_row_borrowed(row: &'a TokioRow) -> Result<Self, Error>;
+}
+
+/// Gets the value of a named column on behalf of derive-generated code,
+/// reporting a missing column as [`Error::ColumnNotFound`] for the mapped type,
+/// a NULL value for a non-`Option` field as [`Error::UnexpectedNull`] and any
+/// other failed conversion as [`Error::ColumnConversion`].
+///
+/// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+/// [`Error::UnexpectedNull`]: enum.Error.html#variant.UnexpectedNull
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[doc(hidden)]
+pub fn try_get_column<'a, T: FromSql<'a>>(
+    row: &'a TokioRow,
+    column: &str,
+    field: &'static str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    let idx = match row.columns().iter().position(|c| c.name() == column) {
+        Some(idx) => idx,
+        None => {
+            return Err(Error::ColumnNotFound {
+                column: column.to_string(),
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            })
+        }
+    };
+
+    try_get_column_at(row, idx, column, field, type_name)
+}
+
+/// Gets the value of the column at the given position on behalf of
+/// derive-generated code, reporting errors like [`try_get_column`] under the
+/// given column name.
+///
+/// [`try_get_column`]: fn.try_get_column.html
+#[doc(hidden)]
+pub fn try_get_column_at<'a, T: FromSql<'a>>(
+    row: &'a TokioRow,
+    idx: usize,
+    column: &str,
+    field: &'static str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    if idx >= row.len() {
+        return Err(Error::ColumnNotFound {
+            column: column.to_string(),
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        });
+    }
+
+    row.try_get(idx).map_err(|err| {
+        // NULL elements of arrays fail with `WasNull` too, and are reported as
+        // conversion errors of the column.
+        if err.source().is_some_and(|source| source.is::<WasNull>())
+            && row
+                .try_get::<_, Option<AnyValue>>(idx)
+                .is_ok_and(|value| value.is_none())
+        {
+            return Error::UnexpectedNull {
+                column: column.to_string(),
+                field,
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            };
+        }
+
+        // Unwrap the conversion error itself instead of tokio-postgres'
+        // "error deserializing column" wrapper around it.
+        let source = if err.source().is_some() {
+            err.into_source().expect("error source checked above")
+        } else {
+            Box::new(err)
+        };
+
+        Error::ColumnConversion {
+            column: column.to_string(),
+            rust_type: std::any::type_name::<T>(),
+            pg_type: Some(row.columns()[idx].type_().clone()),
+            source,
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        }
+    })
+}
+
+/// A value of any type, to tell NULL columns apart without decoding them.
+pub(crate) struct AnyValue;
+
+impl<'a> FromSql<'a> for AnyValue {
+    fn from_sql(_: &Type, _: &'a [u8]) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(AnyValue)
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// Resolves the positions of the mapped columns in a row, so that the rest of
+/// a batch of rows with the same columns can be mapped by position, on behalf
+/// of derive-generated code.
+///
+/// Returns `None` if any column is missing, leaving the error to the mapping
+/// by name.
+#[doc(hidden)]
+pub fn resolve_column_indices(row: &TokioRow, columns: &[&str]) -> Option<Vec<usize>> {
+    columns
+        .iter()
+        .map(|column| row.columns().iter().position(|c| c.name() == *column))
+        .collect()
+}
+
+/// Panics in debug builds if the columns of a row aren't in the order of the
+/// columns mapped by a type, on behalf of derive-generated `from_row_indexed`
+/// implementations.
+#[doc(hidden)]
+pub fn debug_assert_column_order(row: &TokioRow, columns: &[&str], type_name: &str) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    for (idx, (expected, column)) in columns.iter().zip(row.columns()).enumerate() {
+        assert!(
+            *expected == column.name(),
+            "column {} of the row mapped to {} is \"{}\" instead of \"{}\"",
+            idx,
+            type_name,
+            column.name(),
+            expected
+        );
+    }
+}
+
+/// Logs the columns of a row which aren't mapped by a type with the `tracing`
+/// feature, on behalf of derive-generated code.
+#[doc(hidden)]
+#[inline]
+pub fn trace_unmapped_columns(
+    row: &TokioRow,
+    columns: &[&str],
+    type_name: &'static str,
+) {
+    #[cfg(feature = "tracing")]
+    for column in row.columns() {
+        if !columns.contains(&column.name()) {
+            tracing::debug!(
+                type_name,
+                column = column.name(),
+                "column is not mapped by the type and was ignored"
+            );
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = (row, columns, type_name);
+}
+
+/// Runs the mapping of the given number of rows into a type in a `pg_map`
+/// span, logging its error, with the `tracing` feature, on behalf of
+/// derive-generated code.
+#[doc(hidden)]
+#[inline]
+pub fn trace_mapping<T>(
+    type_name: &'static str,
+    rows: usize,
+    map: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    #[cfg(feature = "tracing")]
+    {
+        // The span is at the error level, so that it surrounds the error
+        // event of a failed mapping whenever that is enabled.
+        let span = tracing::error_span!(
+            "pg_map",
+            r#type = type_name,
+            rows,
+            row = tracing::field::Empty
+        );
+        let _entered = span.enter();
+
+        let result = map();
+        if let Err(ref err) = result {
+            let (column, field) = match *err {
+                Error::ColumnNotFound { ref column, .. }
+                | Error::ColumnConversion { ref column, .. } => {
+                    (Some(column.as_str()), None)
+                }
+                Error::UnexpectedNull {
+                    ref column, field, ..
+                } => (Some(column.as_str()), Some(field)),
+                _ => (None, None),
+            };
+
+            tracing::error!(column, field, error = %err, "mapping a row failed");
+        }
+
+        result
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (type_name, rows);
+
+        map()
+    }
+}
+
+/// Records the position of the row of a batch which failed to be mapped on
+/// the `pg_map` span, with the `tracing` feature, on behalf of
+/// derive-generated code.
+#[doc(hidden)]
+#[inline]
+pub fn trace_failed_row(index: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("row", index);
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = index;
+}
+
+/// Logs a mapped column missing from a row, whose field was left unchanged,
+/// with the `tracing` feature, on behalf of derive-generated code.
+#[doc(hidden)]
+#[inline]
+pub fn trace_missing_column(column: &str, type_name: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        type_name,
+        column,
+        "column is missing from the row, the field was left unchanged"
+    );
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = (column, type_name);
+}
+
+/// Maps an empty or whitespace-only string to `None`, on behalf of
+/// derive-generated code for `#[pg_mapper(empty_as_none)]` fields.
+#[doc(hidden)]
+#[inline]
+pub fn empty_as_none<T: AsRef<str>>(value: Option<T>) -> Option<T> {
+    value.filter(|value| !value.as_ref().trim().is_empty())
+}
+
+/// Trims the whitespace of a string, or an `Option` of one, on behalf of
+/// derive-generated code for `#[pg_mapper(trim)]` fields.
+#[doc(hidden)]
+#[inline]
+pub fn trim_whitespace<T: TrimWhitespace>(value: T, both: bool) -> T {
+    value.trim_whitespace(both)
+}
+
+/// Strings whose whitespace can be trimmed by [`trim_whitespace`].
+///
+/// [`trim_whitespace`]: fn.trim_whitespace.html
+#[doc(hidden)]
+pub trait TrimWhitespace {
+    /// Trims trailing whitespace, and leading whitespace too if `both`.
+    fn trim_whitespace(self, both: bool) -> Self;
+}
+
+impl TrimWhitespace for String {
+    fn trim_whitespace(mut self, both: bool) -> Self {
+        self.truncate(self.trim_end().len());
+
+        if both {
+            let leading = self.len() - self.trim_start().len();
+            self.drain(..leading);
+        }
+
+        self
+    }
+}
+
+impl<T: TrimWhitespace> TrimWhitespace for Option<T> {
+    fn trim_whitespace(self, both: bool) -> Self {
+        self.map(|value| value.trim_whitespace(both))
+    }
+}
+
+/// Captures a backtrace for a new error with the `backtrace` feature, which
+/// `Backtrace::capture` makes a no-op unless enabled through the environment.
+fn capture_backtrace() -> Option<Box<Backtrace>> {
+    #[cfg(feature = "backtrace")]
+    {
+        let backtrace = Backtrace::capture();
+
+        if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+            return Some(Box::new(backtrace));
+        }
+    }
+
+    None
+}
+
+/// General error type returned throughout the library.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A column in a row was not found.
+    ColumnNotFound {
+        /// The name of the missing column.
+        column: String,
+        /// The name of the mapped type, when the error comes from
+        /// derive-generated code.
+        type_name: Option<&'static str>,
+        /// Where the error was created, if captured with the `backtrace`
+        /// feature.
+        backtrace: Option<Box<Backtrace>>,
+    },
+    /// An error from the `tokio-postgres` crate while converting a type.
+    Conversion(Box<dyn StdError + Send + Sync>),
+    /// An error while converting a column of a row into the type of the
+    /// mapped field.
+    ColumnConversion {
+        /// The name of the column being converted.
+        column: String,
+        /// The name of the Rust type requested for the column.
+        rust_type: &'static str,
+        /// The Postgres type of the column, if known.
+        pg_type: Option<Type>,
+        /// The underlying conversion error.
+        source: Box<dyn StdError + Send + Sync>,
+        /// The name of the mapped type, when the error comes from
+        /// derive-generated code.
+        type_name: Option<&'static str>,
+        /// Where the error was created, if captured with the `backtrace`
+        /// feature.
+        backtrace: Option<Box<Backtrace>>,
+    },
+    /// A column of a row was NULL, but the mapped field is not an `Option`.
+    ///
+    /// Fields annotated with `#[pg_mapper(default_on_null)]` are set to
+    /// their `Default` instead, or to the result of the function given as
+    /// `#[pg_mapper(default_on_null = "path::to::function")]`.
+    UnexpectedNull {
+        /// The name of the NULL column.
+        column: String,
+        /// The name of the mapped field.
+        field: &'static str,
+        /// The name of the mapped type, when the error comes from
+        /// derive-generated code.
+        type_name: Option<&'static str>,
+        /// Where the error was created, if captured with the `backtrace`
+        /// feature.
+        backtrace: Option<Box<Backtrace>>,
+    },
+    /// An error from the `tokio-postgres` crate, such as a failed query.
+    Db(tokio_postgres::Error),
+    /// A query expected to return exactly one row returned none.
+    NoRows,
+    /// A query expected to return at most one row returned more.
+    TooManyRows,
+    /// An error acquiring a connection from a pool, such as a timeout.
+    Pool(Box<dyn StdError + Send + Sync>),
+    /// A batch insert of a mapped type without columns to insert.
+    NoInsertColumns {
+        /// The name of the mapped type.
+        type_name: &'static str,
+    },
+    /// An update of a mapped type without columns to update.
+    NoUpdateColumns {
+        /// The name of the mapped type.
+        type_name: &'static str,
+    },
+    /// An update of a mapped type with a `#[pg_mapper(version)]` column
+    /// which matched no row, as the row was changed or deleted since the
+    /// updated value was read.
+    StaleVersion {
+        /// The name of the mapped type.
+        type_name: &'static str,
+    },
+    /// An error while running one chunk of a batch, such as a batch insert.
+    ///
+    /// The kind, mapped type and database details of the error are those of
+    /// the wrapped error.
+    Batch {
+        /// The index of the failed chunk.
+        chunk: usize,
+        /// The index of the first row of the failed chunk.
+        offset: usize,
+        /// The index of the failed row in the batch, if it could be located.
+        row: Option<usize>,
+        /// The error the chunk failed with.
+        source: Box<Error>,
+    },
+    /// An error mapping one row of a batch mapped in parallel.
+    ///
+    /// The kind, mapped type and database details of the error are those of
+    /// the wrapped error.
+    Row {
+        /// The index of the failed row in the batch.
+        index: usize,
+        /// The error the row failed with.
+        source: Box<Error>,
+    },
+}
+
+/// The kind of an [`Error`], for matching on without inspecting its payload.
+///
+/// [`Error`]: enum.Error.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// See [`Error::ColumnNotFound`].
+    ///
+    /// [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+    ColumnNotFound,
+    /// See [`Error::Conversion`] and [`Error::ColumnConversion`].
+    ///
+    /// [`Error::Conversion`]: enum.Error.html#variant.Conversion
+    /// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+    Conversion,
+    /// See [`Error::UnexpectedNull`].
+    ///
+    /// [`Error::UnexpectedNull`]: enum.Error.html#variant.UnexpectedNull
+    UnexpectedNull,
+    /// See [`Error::Db`].
+    ///
+    /// [`Error::Db`]: enum.Error.html#variant.Db
+    Db,
+    /// See [`Error::NoRows`].
+    ///
+    /// [`Error::NoRows`]: enum.Error.html#variant.NoRows
+    NoRows,
+    /// See [`Error::TooManyRows`].
+    ///
+    /// [`Error::TooManyRows`]: enum.Error.html#variant.TooManyRows
+    TooManyRows,
+    /// See [`Error::Pool`].
+    ///
+    /// [`Error::Pool`]: enum.Error.html#variant.Pool
+    Pool,
+    /// See [`Error::NoInsertColumns`].
+    ///
+    /// [`Error::NoInsertColumns`]: enum.Error.html#variant.NoInsertColumns
+    NoInsertColumns,
+    /// See [`Error::NoUpdateColumns`].
+    ///
+    /// [`Error::NoUpdateColumns`]: enum.Error.html#variant.NoUpdateColumns
+    NoUpdateColumns,
+    /// See [`Error::StaleVersion`].
+    ///
+    /// [`Error::StaleVersion`]: enum.Error.html#variant.StaleVersion
+    StaleVersion,
+}
+
+impl Error {
+    /// Attaches the name of the given mapped type to the error, as returned
+    /// by `std::any::type_name`.
+    ///
+    /// See [`with_type_name`].
+    ///
+    /// [`with_type_name`]: #method.with_type_name
+    pub fn for_type<T: ?Sized>(err: Error) -> Error {
+        err.with_type_name(std::any::type_name::<T>())
+    }
+
+    /// Attaches the name of the mapped type to the error, so that `Display`
+    /// reads "mapping User: column \"email\" not found".
+    ///
+    /// Errors which already name a mapped type, and errors which aren't
+    /// about a specific column, are returned unchanged.
+    pub fn with_type_name(mut self, name: &'static str) -> Error {
+        self.set_type_name(name);
+
+        self
+    }
+
+    fn set_type_name(&mut self, name: &'static str) {
+        match *self {
+            Error::ColumnNotFound {
+                ref mut type_name, ..
+            }
+            | Error::ColumnConversion {
+                ref mut type_name, ..
+            }
+            | Error::UnexpectedNull {
+                ref mut type_name, ..
+            } => {
+                type_name.get_or_insert(name);
+            }
+            Error::Batch { ref mut source, .. } | Error::Row { ref mut source, .. } => {
+                source.set_type_name(name)
+            }
+            Error::Conversion(_)
+            | Error::Db(_)
+            | Error::NoRows
+            | Error::TooManyRows
+            | Error::Pool(_)
+            | Error::NoInsertColumns { .. }
+            | Error::NoUpdateColumns { .. }
+            | Error::StaleVersion { .. } => {}
+        }
+    }
+
+    /// Get the name of the mapped type the error occurred for, if known.
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            Error::ColumnNotFound { type_name, .. }
+            | Error::ColumnConversion { type_name, .. }
+            | Error::UnexpectedNull { type_name, .. } => *type_name,
+            Error::NoInsertColumns { type_name }
+            | Error::NoUpdateColumns { type_name }
+            | Error::StaleVersion { type_name } => Some(type_name),
+            Error::Batch { source, .. } | Error::Row { source, .. } => {
+                source.type_name()
+            }
+            Error::Conversion(_)
+            | Error::Db(_)
+            | Error::NoRows
+            | Error::TooManyRows
+            | Error::Pool(_) => None,
+        }
+    }
+
+    /// Returns whether a column's Postgres type can't be converted into the
+    /// Rust type of its mapped field, as reported by `tokio-postgres` with a
+    /// `WrongType` error.
+    pub fn is_wrong_type(&self) -> bool {
+        match self {
+            Error::ColumnConversion { source, .. } => source.is::<WrongType>(),
+            _ => false,
+        }
+    }
+
+    /// Get the Rust type a column was requested as, if the conversion failed
+    /// because of mismatched types.
+    ///
+    /// See [`is_wrong_type`].
+    ///
+    /// [`is_wrong_type`]: #method.is_wrong_type
+    pub fn expected_rust_type(&self) -> Option<&'static str> {
+        match self {
+            Error::ColumnConversion { rust_type, .. } if self.is_wrong_type() => {
+                Some(rust_type)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the Postgres type of a column, if the conversion failed because
+    /// of mismatched types.
+    ///
+    /// See [`is_wrong_type`].
+    ///
+    /// [`is_wrong_type`]: #method.is_wrong_type
+    pub fn actual_pg_type(&self) -> Option<Type> {
+        match self {
+            Error::ColumnConversion { pg_type, .. } if self.is_wrong_type() => {
+                pg_type.clone()
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the backtrace of where the error was created.
+    ///
+    /// Backtraces are only captured for errors about a specific column, with
+    /// the `backtrace` feature enabled and `RUST_BACKTRACE` or
+    /// `RUST_LIB_BACKTRACE` set.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        match self {
+            Error::ColumnNotFound { backtrace, .. }
+            | Error::ColumnConversion { backtrace, .. }
+            | Error::UnexpectedNull { backtrace, .. } => backtrace.as_deref(),
+            Error::Batch { source, .. } | Error::Row { source, .. } => {
+                source.backtrace()
+            }
+            Error::Conversion(_)
+            | Error::Db(_)
+            | Error::NoRows
+            | Error::TooManyRows
+            | Error::Pool(_)
+            | Error::NoInsertColumns { .. }
+            | Error::NoUpdateColumns { .. }
+            | Error::StaleVersion { .. } => None,
+        }
+    }
+
+    /// Get the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::ColumnNotFound { .. } => ErrorKind::ColumnNotFound,
+            Error::Conversion(_) | Error::ColumnConversion { .. } => {
+                ErrorKind::Conversion
+            }
+            Error::UnexpectedNull { .. } => ErrorKind::UnexpectedNull,
+            Error::Db(_) => ErrorKind::Db,
+            Error::NoRows => ErrorKind::NoRows,
+            Error::TooManyRows => ErrorKind::TooManyRows,
+            Error::Pool(_) => ErrorKind::Pool,
+            Error::NoInsertColumns { .. } => ErrorKind::NoInsertColumns,
+            Error::NoUpdateColumns { .. } => ErrorKind::NoUpdateColumns,
+            Error::StaleVersion { .. } => ErrorKind::StaleVersion,
+            Error::Batch { source, .. } | Error::Row { source, .. } => source.kind(),
+        }
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::ColumnNotFound`].
+    ///
+    /// [`ErrorKind::ColumnNotFound`]: enum.ErrorKind.html#variant.ColumnNotFound
+    pub fn is_column_not_found(&self) -> bool {
+        self.kind() == ErrorKind::ColumnNotFound
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::Conversion`].
+    ///
+    /// [`ErrorKind::Conversion`]: enum.ErrorKind.html#variant.Conversion
+    pub fn is_conversion(&self) -> bool {
+        self.kind() == ErrorKind::Conversion
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::UnexpectedNull`].
+    ///
+    /// [`ErrorKind::UnexpectedNull`]: enum.ErrorKind.html#variant.UnexpectedNull
+    pub fn is_unexpected_null(&self) -> bool {
+        self.kind() == ErrorKind::UnexpectedNull
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::Db`].
+    ///
+    /// [`ErrorKind::Db`]: enum.ErrorKind.html#variant.Db
+    pub fn is_db(&self) -> bool {
+        self.kind() == ErrorKind::Db
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::NoRows`].
+    ///
+    /// [`ErrorKind::NoRows`]: enum.ErrorKind.html#variant.NoRows
+    pub fn is_no_rows(&self) -> bool {
+        self.kind() == ErrorKind::NoRows
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::TooManyRows`].
+    ///
+    /// [`ErrorKind::TooManyRows`]: enum.ErrorKind.html#variant.TooManyRows
+    pub fn is_too_many_rows(&self) -> bool {
+        self.kind() == ErrorKind::TooManyRows
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::Pool`].
+    ///
+    /// [`ErrorKind::Pool`]: enum.ErrorKind.html#variant.Pool
+    pub fn is_pool(&self) -> bool {
+        self.kind() == ErrorKind::Pool
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::NoInsertColumns`].
+    ///
+    /// [`ErrorKind::NoInsertColumns`]: enum.ErrorKind.html#variant.NoInsertColumns
+    pub fn is_no_insert_columns(&self) -> bool {
+        self.kind() == ErrorKind::NoInsertColumns
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::NoUpdateColumns`].
+    ///
+    /// [`ErrorKind::NoUpdateColumns`]: enum.ErrorKind.html#variant.NoUpdateColumns
+    pub fn is_no_update_columns(&self) -> bool {
+        self.kind() == ErrorKind::NoUpdateColumns
+    }
+
+    /// Returns whether the error is of kind [`ErrorKind::StaleVersion`].
+    ///
+    /// [`ErrorKind::StaleVersion`]: enum.ErrorKind.html#variant.StaleVersion
+    pub fn is_stale_version(&self) -> bool {
+        self.kind() == ErrorKind::StaleVersion
+    }
+
+    /// Get the SQLSTATE code of the error, if it was reported by the
+    /// database.
+    pub fn sqlstate(&self) -> Option<&SqlState> {
+        match self {
+            Error::Db(err) => err.code(),
+            Error::Batch { source, .. } | Error::Row { source, .. } => source.sqlstate(),
+            _ => None,
+        }
+    }
+
+    /// Get the error reported by the database, if any, with its severity,
+    /// message, constraint and other details.
+    pub fn as_db_error(&self) -> Option<&DbError> {
+        match self {
+            Error::Db(err) => err.as_db_error(),
+            Error::Batch { source, .. } | Error::Row { source, .. } => {
+                source.as_db_error()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns whether the database rejected a statement for violating a
+    /// unique constraint, SQLSTATE `23505`.
+    pub fn is_unique_violation(&self) -> bool {
+        self.sqlstate() == Some(&SqlState::UNIQUE_VIOLATION)
+    }
+
+    /// Returns whether the database rejected a statement for violating a
+    /// foreign key constraint, SQLSTATE `23503`.
+    pub fn is_foreign_key_violation(&self) -> bool {
+        self.sqlstate() == Some(&SqlState::FOREIGN_KEY_VIOLATION)
+    }
+
+    /// Get the name of the constraint violated by a statement, if reported
+    /// by the database.
+    pub fn constraint(&self) -> Option<&str> {
+        self.as_db_error().and_then(DbError::constraint)
+    }
+}
+
+impl From<tokio_postgres::Error> for Error {
+    fn from(err: tokio_postgres::Error) -> Self {
+        Error::Db(err)
+    }
+}
+
+impl From<Box<dyn StdError + Send + Sync>> for Error {
+    fn from(err: Box<dyn StdError + Send + Sync>) -> Self {
+        Error::Conversion(err)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        // The wrapped error of a batch or row names the mapped type itself.
+        if let (Some(type_name), false) = (
+            self.type_name(),
+            matches!(self, Error::Batch { .. } | Error::Row { .. }),
+        )
+        {
+            write!(f, "mapping {}: ", type_name)?;
+        }
+
+        match self {
+            Error::ColumnNotFound { column, .. } => {
+                write!(f, "column \"{}\" not found", column)
+            }
+            Error::UnexpectedNull { column, field, .. } => write!(
+                f,
+                "column \"{}\" is NULL but field `{}` is not an Option; \
+                 consider wrapping the field type in Option",
+                column, field
+            ),
+            Error::Db(_) => f.write_str("database error"),
+            Error::Conversion(_) => f.write_str("error converting a value"),
+            Error::NoRows => f.write_str("query returned no rows"),
+            Error::TooManyRows => f.write_str("query returned more than one row"),
+            Error::Pool(_) => f.write_str("error getting a connection from the pool"),
+            Error::NoInsertColumns { .. } => f.write_str("no columns to insert"),
+            Error::NoUpdateColumns { .. } => f.write_str("no columns to update"),
+            Error::StaleVersion { .. } => {
+                f.write_str("the updated row was changed or deleted since it was read")
+            }
+            Error::Batch {
+                chunk,
+                row: Some(row),
+                ..
+            } => write!(f, "error in chunk {} of the batch, at row {}", chunk, row),
+            Error::Batch { chunk, offset, .. } => write!(
+                f,
+                "error in chunk {} of the batch, starting at row {}",
+                chunk, offset
+            ),
+            Error::Row { index, .. } => {
+                write!(f, "error mapping row {} of the batch", index)
+            }
+            Error::ColumnConversion {
+                column,
+                rust_type,
+                pg_type,
+                ..
+            } => {
+                write!(f, "error converting column \"{}\"", column)?;
+                if let Some(pg_type) = pg_type {
+                    write!(f, " of Postgres type {}", pg_type)?;
+                }
+                write!(f, " to Rust type {}", rust_type)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let kind = match self.kind() {
+            ErrorKind::ColumnNotFound => "column_not_found",
+            ErrorKind::Conversion => "conversion",
+            ErrorKind::UnexpectedNull => "unexpected_null",
+            ErrorKind::Db => "db",
+            ErrorKind::NoRows => "no_rows",
+            ErrorKind::TooManyRows => "too_many_rows",
+            ErrorKind::Pool => "pool",
+            ErrorKind::NoInsertColumns => "no_insert_columns",
+            ErrorKind::NoUpdateColumns => "no_update_columns",
+            ErrorKind::StaleVersion => "stale_version",
+        };
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", kind)?;
+
+        match self {
+            Error::ColumnNotFound {
+                column, type_name, ..
+            } => {
+                map.serialize_entry("column", column)?;
+                map.serialize_entry("type_name", type_name)?;
+            }
+            Error::ColumnConversion {
+                column,
+                rust_type,
+                pg_type,
+                type_name,
+                ..
+            } => {
+                map.serialize_entry("column", column)?;
+                map.serialize_entry("rust_type", rust_type)?;
+                map.serialize_entry("pg_type", &pg_type.as_ref().map(Type::name))?;
+                map.serialize_entry("type_name", type_name)?;
+            }
+            Error::UnexpectedNull {
+                column,
+                field,
+                type_name,
+                ..
+            } => {
+                map.serialize_entry("column", column)?;
+                map.serialize_entry("field", field)?;
+                map.serialize_entry("type_name", type_name)?;
+            }
+            Error::Db(_) => {
+                map.serialize_entry("sqlstate", &self.sqlstate().map(SqlState::code))?;
+            }
+            Error::NoInsertColumns { type_name }
+            | Error::NoUpdateColumns { type_name }
+            | Error::StaleVersion { type_name } => {
+                map.serialize_entry("type_name", type_name)?;
+            }
+            Error::Batch {
+                chunk, offset, row, ..
+            } => {
+                map.serialize_entry("chunk", chunk)?;
+                map.serialize_entry("offset", offset)?;
+                map.serialize_entry("row", row)?;
+            }
+            Error::Row { index, .. } => {
+                map.serialize_entry("row", index)?;
+            }
+            Error::Conversion(_)
+            | Error::NoRows
+            | Error::TooManyRows
+            | Error::Pool(_) => {}
+        }
+
+        map.serialize_entry("message", &self.to_string())?;
+        map.serialize_entry("source", &self.source().map(ToString::to_string))?;
+        map.end()
     }
 }
 
+// Wrapped errors are exposed through `source` rather than repeated in
+// `Display`, so error reporters print every message of the chain once.
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
-            Error::Conversion(ref inner) => inner.source(),
-            _ => None
+            Error::Conversion(ref inner) => Some(inner.as_ref()),
+            Error::Pool(ref inner) => Some(inner.as_ref()),
+            Error::Batch { ref source, .. } => Some(source.as_ref()),
+            Error::Row { ref source, .. } => Some(source.as_ref()),
+            Error::ColumnConversion { ref source, .. } => Some(source.as_ref()),
+            Error::Db(ref err) => Some(err),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,184 @@
+//! The `PgLtree` and `PgLquery` field types.
+
+use bytes::{BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// The version of the binary format of `ltree` and `lquery`, sent since
+/// Postgres 13.
+const VERSION: u8 = 1;
+
+fn read_text<'a>(
+    raw: &'a [u8],
+    name: &str,
+) -> Result<&'a str, Box<dyn StdError + Sync + Send>> {
+    match raw.split_first() {
+        Some((&VERSION, text)) => Ok(std::str::from_utf8(text)?),
+        _ => Err(format!("unsupported {} encoding version", name).into()),
+    }
+}
+
+fn write_text(out: &mut BytesMut, text: &str) {
+    out.put_u8(VERSION);
+    out.put_slice(text.as_bytes());
+}
+
+/// Mapping of `ltree` columns and parameters to their labels, such as
+/// `["Top", "Science", "Astronomy"]` for `Top.Science.Astronomy`.
+///
+/// `ltree` is created by an extension, so it's recognized by its name rather
+/// than an OID fixed across databases. Its binary format requires Postgres 13
+/// or later.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgLtree(pub Vec<String>);
+
+impl PgLtree {
+    /// Get the labels of the path.
+    pub fn labels(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Get the number of labels of the path, as returned by `nlevel`.
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Get the path without its last label, or `None` for an empty path.
+    pub fn parent(&self) -> Option<PgLtree> {
+        self.0
+            .split_last()
+            .map(|(_, parent)| PgLtree(parent.to_vec()))
+    }
+
+    /// Whether this path is an ancestor of another one, or equal to it, like
+    /// the `@>` operator.
+    pub fn is_ancestor_of(&self, other: &PgLtree) -> bool {
+        other.0.starts_with(&self.0)
+    }
+
+    /// Whether this path is a descendant of another one, or equal to it, like
+    /// the `<@` operator.
+    pub fn is_descendant_of(&self, other: &PgLtree) -> bool {
+        other.is_ancestor_of(self)
+    }
+}
+
+impl Display for PgLtree {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(&self.0.join("."))
+    }
+}
+
+impl FromStr for PgLtree {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        if text.is_empty() {
+            return Ok(PgLtree(Vec::new()));
+        }
+
+        text.split('.')
+            .map(|label| {
+                if label.is_empty() {
+                    Err(format!("invalid ltree value \"{}\"", text))
+                } else {
+                    Ok(label.to_string())
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(PgLtree)
+    }
+}
+
+impl From<Vec<String>> for PgLtree {
+    fn from(labels: Vec<String>) -> Self {
+        PgLtree(labels)
+    }
+}
+
+impl<'a> FromSql<'a> for PgLtree {
+    fn from_sql(
+        _: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(read_text(raw, "ltree")?.parse()?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "ltree"
+    }
+}
+
+impl ToSql for PgLtree {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        write_text(out, &self.to_string());
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "ltree"
+    }
+
+    to_sql_checked!();
+}
+
+/// Mapping of `lquery` columns and parameters, such as `*.Science.*`, kept as
+/// their text.
+///
+/// Like [`PgLtree`], it's recognized by its name and requires Postgres 13 or
+/// later.
+///
+/// [`PgLtree`]: struct.PgLtree.html
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgLquery(pub String);
+
+impl Display for PgLquery {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(&self.0)
+    }
+}
+
+impl From<String> for PgLquery {
+    fn from(query: String) -> Self {
+        PgLquery(query)
+    }
+}
+
+impl<'a> FromSql<'a> for PgLquery {
+    fn from_sql(
+        _: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(PgLquery(read_text(raw, "lquery")?.to_string()))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "lquery"
+    }
+}
+
+impl ToSql for PgLquery {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        write_text(out, &self.0);
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "lquery"
+    }
+
+    to_sql_checked!();
+}
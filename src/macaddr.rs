@@ -0,0 +1,127 @@
+//! The `PgMacAddr` and `PgMacAddr8` field types.
+
+use bytes::{BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::convert::TryInto;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+macro_rules! impl_mac_addr {
+    ($name:ident, $len:expr, $ty:expr, $pg_name:expr) => {
+        impl $name {
+            /// Get the bytes of the address.
+            pub fn octets(&self) -> [u8; $len] {
+                self.0
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(octets: [u8; $len]) -> Self {
+                $name(octets)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter) -> FmtResult {
+                let text = self
+                    .0
+                    .iter()
+                    .map(|octet| format!("{:02x}", octet))
+                    .collect::<Vec<_>>()
+                    .join(":");
+
+                f.pad(&text)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            /// Parses an address of hexadecimal octets separated by colons or
+            /// hyphens, such as `08:00:2b:01:02:03`.
+            fn from_str(text: &str) -> Result<Self, String> {
+                let invalid = || format!("invalid {} value \"{}\"", $pg_name, text);
+
+                let octets = text
+                    .split(|c| c == ':' || c == '-')
+                    .map(|octet| {
+                        if octet.len() != 2 {
+                            return Err(invalid());
+                        }
+
+                        u8::from_str_radix(octet, 16).map_err(|_| invalid())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                octets.try_into().map($name).map_err(|_| invalid())
+            }
+        }
+
+        impl<'a> FromSql<'a> for $name {
+            fn from_sql(
+                _: &Type,
+                raw: &'a [u8],
+            ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+                raw.try_into()
+                    .map($name)
+                    .map_err(|_| concat!("invalid ", $pg_name, " length").into())
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                *ty == $ty
+            }
+        }
+
+        impl ToSql for $name {
+            fn to_sql(
+                &self,
+                _: &Type,
+                out: &mut BytesMut,
+            ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+                out.put_slice(&self.0);
+
+                Ok(IsNull::No)
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                *ty == $ty
+            }
+
+            to_sql_checked!();
+        }
+    };
+}
+
+/// Mapping of `macaddr` columns and parameters.
+///
+/// `Display` and `FromStr` use colon-separated hexadecimal octets, such as
+/// `08:00:2b:01:02:03`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgMacAddr(pub [u8; 6]);
+
+impl_mac_addr!(PgMacAddr, 6, Type::MACADDR, "macaddr");
+
+/// Mapping of `macaddr8` columns and parameters.
+///
+/// `Display` and `FromStr` use colon-separated hexadecimal octets, such as
+/// `08:00:2b:01:02:03:04:05`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgMacAddr8(pub [u8; 8]);
+
+impl_mac_addr!(PgMacAddr8, 8, Type::MACADDR8, "macaddr8");
+
+#[cfg(feature = "eui48")]
+impl From<PgMacAddr> for eui48::MacAddress {
+    fn from(addr: PgMacAddr) -> Self {
+        eui48::MacAddress::new(addr.0)
+    }
+}
+
+#[cfg(feature = "eui48")]
+impl From<eui48::MacAddress> for PgMacAddr {
+    fn from(addr: eui48::MacAddress) -> Self {
+        PgMacAddr(addr.to_array())
+    }
+}
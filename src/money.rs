@@ -0,0 +1,100 @@
+//! The `PgMoney` field type.
+
+use crate::PgNumeric;
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::error::Error as StdError;
+
+/// Mapping of `money` columns and parameters to their raw amount, in the
+/// smallest unit of the currency.
+///
+/// Postgres stores `money` as a 64-bit integer whose scale depends on the
+/// `lc_monetary` setting of the server: with `en_US.UTF-8` an amount of
+/// `$12.34` is stored as `1234`, while with a locale whose currency has no
+/// fractional digits, such as `ja_JP.UTF-8`, `¥1234` is stored as `1234` too.
+/// The same raw amount therefore means different values on servers with
+/// different locales, and [`to_decimal`] must be given the scale of the
+/// server's locale.
+///
+/// [`to_decimal`]: #method.to_decimal
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgMoney(pub i64);
+
+impl PgMoney {
+    /// Creates an amount from its value in cents, or whichever smallest unit
+    /// the server's locale uses.
+    pub fn from_cents(cents: i64) -> Self {
+        PgMoney(cents)
+    }
+
+    /// Get the amount in cents, or whichever smallest unit the server's
+    /// locale uses.
+    pub fn cents(self) -> i64 {
+        self.0
+    }
+
+    /// Converts the amount to a decimal with the given number of fractional
+    /// digits, which is 2 for most locales: `PgMoney(1234).to_decimal(2)` is
+    /// `12.34`.
+    pub fn to_decimal(self, scale: u16) -> PgNumeric {
+        let mut digits = self.0.unsigned_abs().to_string();
+        let scale = scale as usize;
+
+        if digits.len() <= scale {
+            digits.insert_str(0, &"0".repeat(scale - digits.len() + 1));
+        }
+        if scale > 0 {
+            digits.insert(digits.len() - scale, '.');
+        }
+        if self.0 < 0 {
+            digits.insert(0, '-');
+        }
+
+        digits
+            .parse()
+            .expect("money amounts are formatted as plain decimal numbers")
+    }
+}
+
+impl From<i64> for PgMoney {
+    fn from(cents: i64) -> Self {
+        PgMoney(cents)
+    }
+}
+
+impl<'a> FromSql<'a> for PgMoney {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if raw.len() != 8 {
+            return Err("invalid money length".into());
+        }
+
+        Ok(PgMoney(raw.get_i64()))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::MONEY
+    }
+}
+
+impl ToSql for PgMoney {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        out.put_i64(self.0);
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::MONEY
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,105 @@
+//! Mapping of `LISTEN`/`NOTIFY` payloads, enabled with the `json` feature.
+//!
+//! Notifications sent as `pg_notify('users_changed', row_to_json(NEW)::text)`
+//! carry a JSON object whose keys are column names, which
+//! [`FromNotificationPayload`] maps like the columns of a row.
+//!
+//! [`FromNotificationPayload`]: trait.FromNotificationPayload.html
+
+use crate::{capture_backtrace, Error};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use tokio_postgres::Notification;
+
+/// Trait for converting from the JSON object payload of a notification into
+/// a mapped type.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(notification)]` container attribute, this will be
+/// implemented on types. Every field must then be `Deserialize`.
+///
+/// Like for rows, keys which aren't mapped are ignored, while missing keys
+/// are errors.
+pub trait FromNotificationPayload: Sized {
+    /// Converts from a JSON object, as returned by `row_to_json`, into a
+    /// mapped type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Conversion`] if the payload isn't a JSON object.
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the key of a column in a mapping
+    /// was not found.
+    ///
+    /// Returns [`Error::UnexpectedNull`] if the value of a column mapped to a
+    /// non-`Option` field was `null`.
+    ///
+    /// Returns [`Error::ColumnConversion`] if there was an error deserializing
+    /// a value into the requested type.
+    ///
+    /// [`Error::Conversion`]: ../enum.Error.html#variant.Conversion
+    /// [`Error::ColumnNotFound`]: ../enum.Error.html#variant.ColumnNotFound
+    /// [`Error::UnexpectedNull`]: ../enum.Error.html#variant.UnexpectedNull
+    /// [`Error::ColumnConversion`]: ../enum.Error.html#variant.ColumnConversion
+    fn from_notification_payload(payload: &str) -> Result<Self, Error>;
+
+    /// Converts from the payload of a notification into a mapped type.
+    ///
+    /// See [`from_notification_payload`].
+    ///
+    /// [`from_notification_payload`]: #tymethod.from_notification_payload
+    fn from_notification(notification: &Notification) -> Result<Self, Error> {
+        Self::from_notification_payload(notification.payload())
+    }
+}
+
+/// Parses a notification payload into a JSON object on behalf of
+/// derive-generated code.
+#[doc(hidden)]
+pub fn parse_notification_payload(payload: &str) -> Result<Map<String, Value>, Error> {
+    serde_json::from_str(payload).map_err(|err| Error::Conversion(Box::new(err)))
+}
+
+/// Deserializes the value of a named key of a notification payload on behalf
+/// of derive-generated code, reporting errors like [`try_get_column`].
+///
+/// [`try_get_column`]: ../fn.try_get_column.html
+#[doc(hidden)]
+pub fn try_get_json_field<T: DeserializeOwned>(
+    object: &Map<String, Value>,
+    column: &str,
+    field: &'static str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    let value = match object.get(column) {
+        Some(value) => value,
+        None => {
+            return Err(Error::ColumnNotFound {
+                column: column.to_string(),
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            })
+        }
+    };
+
+    T::deserialize(value).map_err(|err| {
+        if value.is_null() {
+            return Error::UnexpectedNull {
+                column: column.to_string(),
+                field,
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            };
+        }
+
+        Error::ColumnConversion {
+            column: column.to_string(),
+            rust_type: std::any::type_name::<T>(),
+            pg_type: None,
+            source: Box::new(err),
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        }
+    })
+}
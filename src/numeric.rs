@@ -0,0 +1,339 @@
+//! The `PgNumeric` field type.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+use std::str::FromStr;
+
+const SIGN_POSITIVE: u16 = 0x0000;
+const SIGN_NEGATIVE: u16 = 0x4000;
+const SIGN_NAN: u16 = 0xC000;
+const SIGN_POSITIVE_INFINITY: u16 = 0xD000;
+const SIGN_NEGATIVE_INFINITY: u16 = 0xF000;
+
+/// Lossless mapping of `numeric` columns and parameters.
+///
+/// The value is kept in the base-10000 digits of the binary protocol, so
+/// `to_string` returns exactly the value stored by Postgres, including the
+/// trailing zeros of its scale: `12.50` in a `numeric(10, 2)` column is
+/// displayed as `12.50`. Values can be parsed from decimal strings such as
+/// `-1234.5678` with `str::parse`.
+///
+/// `NaN` and infinite values can't be represented and fail to map with a
+/// [`NumericError`].
+///
+/// With the `rust_decimal` feature, `PgNumeric` converts to and from
+/// `rust_decimal::Decimal`, and with the `bigdecimal` feature to and from
+/// `bigdecimal::BigDecimal`.
+///
+/// [`NumericError`]: enum.NumericError.html
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PgNumeric {
+    negative: bool,
+    weight: i16,
+    scale: u16,
+    digits: Vec<i16>,
+}
+
+impl PgNumeric {
+    /// Get the number of digits after the decimal point.
+    pub fn scale(&self) -> u16 {
+        self.scale
+    }
+
+    /// Whether the value is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Whether the value is zero.
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Strips the leading and trailing zero digits, which don't change the
+    /// value.
+    fn normalize(mut self) -> Self {
+        let leading = self.digits.iter().take_while(|&&d| d == 0).count();
+        self.digits.drain(..leading);
+        self.weight -= leading as i16;
+
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+
+        if self.digits.is_empty() {
+            self.negative = false;
+            self.weight = 0;
+        }
+
+        self
+    }
+
+    fn digit(&self, idx: i32) -> i16 {
+        if idx < 0 {
+            return 0;
+        }
+
+        self.digits.get(idx as usize).copied().unwrap_or(0)
+    }
+}
+
+impl Display for PgNumeric {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let mut text = String::new();
+
+        if self.negative {
+            text.push('-');
+        }
+
+        if self.weight < 0 {
+            text.push('0');
+        } else {
+            write!(text, "{}", self.digit(0))?;
+
+            for idx in 1..=self.weight as i32 {
+                write!(text, "{:04}", self.digit(idx))?;
+            }
+        }
+
+        if self.scale > 0 {
+            let mut fraction = String::new();
+            let mut idx = self.weight as i32 + 1;
+
+            while fraction.len() < self.scale as usize {
+                write!(fraction, "{:04}", self.digit(idx))?;
+                idx += 1;
+            }
+
+            fraction.truncate(self.scale as usize);
+            text.push('.');
+            text.push_str(&fraction);
+        }
+
+        f.pad(&text)
+    }
+}
+
+impl FromStr for PgNumeric {
+    type Err = NumericError;
+
+    fn from_str(text: &str) -> Result<Self, NumericError> {
+        let invalid = || NumericError::InvalidText(text.to_string());
+
+        let (negative, unsigned) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (integer, fraction) = match unsigned.find('.') {
+            Some(idx) => (&unsigned[..idx], &unsigned[idx + 1..]),
+            None => (unsigned, ""),
+        };
+
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        if !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let integer = integer.trim_start_matches('0');
+        let scale = u16::try_from(fraction.len()).map_err(|_| NumericError::OutOfRange)?;
+
+        // Align both parts on groups of 4 decimal digits.
+        let mut padded = "0".repeat((4 - integer.len() % 4) % 4);
+        padded.push_str(integer);
+        let integer_groups = padded.len() / 4;
+        padded.push_str(fraction);
+        padded.push_str(&"0".repeat((4 - fraction.len() % 4) % 4));
+
+        let digits = padded
+            .as_bytes()
+            .chunks(4)
+            .map(|group| {
+                group
+                    .iter()
+                    .fold(0i16, |acc, digit| acc * 10 + i16::from(digit - b'0'))
+            })
+            .collect::<Vec<_>>();
+
+        let weight = i16::try_from(integer_groups as i64 - 1)
+            .map_err(|_| NumericError::OutOfRange)?;
+        if i16::try_from(digits.len()).is_err() {
+            return Err(NumericError::OutOfRange);
+        }
+
+        Ok(PgNumeric {
+            negative,
+            weight,
+            scale,
+            digits,
+        }
+        .normalize())
+    }
+}
+
+impl<'a> FromSql<'a> for PgNumeric {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        if raw.len() < 8 {
+            return Err("invalid numeric length".into());
+        }
+
+        let ndigits = raw.get_i16();
+        let weight = raw.get_i16();
+        let sign = raw.get_u16();
+        let scale = raw.get_u16();
+
+        let negative = match sign {
+            SIGN_POSITIVE => false,
+            SIGN_NEGATIVE => true,
+            SIGN_NAN => return Err(NumericError::NaN.into()),
+            SIGN_POSITIVE_INFINITY | SIGN_NEGATIVE_INFINITY => {
+                return Err(NumericError::Infinite.into())
+            }
+            _ => return Err("invalid numeric sign".into()),
+        };
+
+        if ndigits < 0 || raw.len() != ndigits as usize * 2 {
+            return Err("invalid numeric length".into());
+        }
+
+        let mut digits = Vec::with_capacity(ndigits as usize);
+        for _ in 0..ndigits {
+            let digit = raw.get_i16();
+            if !(0..10000).contains(&digit) {
+                return Err("invalid numeric digit".into());
+            }
+            digits.push(digit);
+        }
+
+        Ok(PgNumeric {
+            negative,
+            weight,
+            scale,
+            digits,
+        }
+        .normalize())
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+impl ToSql for PgNumeric {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        out.put_i16(self.digits.len() as i16);
+        out.put_i16(self.weight);
+        out.put_u16(if self.negative {
+            SIGN_NEGATIVE
+        } else {
+            SIGN_POSITIVE
+        });
+        out.put_u16(self.scale);
+
+        for &digit in &self.digits {
+            out.put_i16(digit);
+        }
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(feature = "rust_decimal")]
+impl TryFrom<PgNumeric> for rust_decimal::Decimal {
+    type Error = NumericError;
+
+    /// Converts into a `Decimal`, failing if the value doesn't fit in its 96
+    /// bits or 28 digits of scale without rounding.
+    fn try_from(value: PgNumeric) -> Result<Self, NumericError> {
+        let text = value.to_string();
+        let decimal = text
+            .parse::<rust_decimal::Decimal>()
+            .map_err(|_| NumericError::OutOfRange)?;
+
+        if decimal.scale() != u32::from(value.scale) {
+            return Err(NumericError::OutOfRange);
+        }
+
+        Ok(decimal)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl From<rust_decimal::Decimal> for PgNumeric {
+    fn from(value: rust_decimal::Decimal) -> Self {
+        value
+            .to_string()
+            .parse()
+            .expect("Decimal is displayed as a plain decimal number")
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl From<PgNumeric> for bigdecimal::BigDecimal {
+    fn from(value: PgNumeric) -> Self {
+        value
+            .to_string()
+            .parse()
+            .expect("PgNumeric is displayed as a plain decimal number")
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl TryFrom<bigdecimal::BigDecimal> for PgNumeric {
+    type Error = NumericError;
+
+    /// Converts from a `BigDecimal`, failing if it has more digits than
+    /// `numeric` can hold.
+    fn try_from(value: bigdecimal::BigDecimal) -> Result<Self, NumericError> {
+        value.to_plain_string().parse()
+    }
+}
+
+/// An error converting a `numeric` value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum NumericError {
+    /// The value was `NaN`.
+    NaN,
+    /// The value was `Infinity` or `-Infinity`.
+    Infinite,
+    /// The value is out of the range of the target type, or would have to be
+    /// rounded.
+    OutOfRange,
+    /// The text isn't a plain decimal number.
+    InvalidText(String),
+}
+
+impl Display for NumericError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            NumericError::NaN => f.write_str("numeric value is NaN"),
+            NumericError::Infinite => f.write_str("numeric value is infinite"),
+            NumericError::OutOfRange => f.write_str("numeric value is out of range"),
+            NumericError::InvalidText(ref text) => {
+                write!(f, "invalid numeric value \"{}\"", text)
+            }
+        }
+    }
+}
+
+impl StdError for NumericError {}
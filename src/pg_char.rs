@@ -0,0 +1,124 @@
+//! The `PgChar` field type.
+
+use bytes::{BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Deref;
+
+/// Mapping of single-character columns and parameters, such as `char(1)` or
+/// `"char"` status codes, to a `char`.
+///
+/// Values are read from `"char"`, `char`, `varchar` and `text` columns, and
+/// must hold exactly one character: anything else results in an
+/// [`Error::ColumnConversion`] naming the column, with an
+/// [`InvalidChar`] source holding the offending text, rather than being
+/// truncated. Only ASCII characters fit in a `"char"`.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+/// [`InvalidChar`]: struct.InvalidChar.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgChar(pub char);
+
+impl Deref for PgChar {
+    type Target = char;
+
+    fn deref(&self) -> &char {
+        &self.0
+    }
+}
+
+impl Display for PgChar {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl From<char> for PgChar {
+    fn from(c: char) -> Self {
+        PgChar(c)
+    }
+}
+
+impl From<PgChar> for char {
+    fn from(c: PgChar) -> Self {
+        c.0
+    }
+}
+
+fn accepts_char(ty: &Type) -> bool {
+    *ty == Type::CHAR || *ty == Type::BPCHAR || *ty == Type::VARCHAR || *ty == Type::TEXT
+}
+
+impl<'a> FromSql<'a> for PgChar {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let text = if *ty == Type::CHAR {
+            String::from_utf8_lossy(raw)
+        } else {
+            std::str::from_utf8(raw)?.into()
+        };
+
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(PgChar(c)),
+            _ => Err(Box::new(InvalidChar {
+                text: text.into_owned(),
+            })),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts_char(ty)
+    }
+}
+
+impl ToSql for PgChar {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        if *ty == Type::CHAR && !self.0.is_ascii() {
+            return Err(format!("\"{}\" doesn't fit in a \"char\"", self.0).into());
+        }
+
+        let mut buf = [0; 4];
+        out.put_slice(self.0.encode_utf8(&mut buf).as_bytes());
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts_char(ty)
+    }
+
+    to_sql_checked!();
+}
+
+/// The source of an [`Error::ColumnConversion`] for a value which isn't a
+/// single character, holding the offending text.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[derive(Debug)]
+pub struct InvalidChar {
+    text: String,
+}
+
+impl InvalidChar {
+    /// Get the text which isn't a single character.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for InvalidChar {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "expected a single character, got \"{}\"", self.text)
+    }
+}
+
+impl StdError for InvalidChar {}
@@ -0,0 +1,192 @@
+//! Pivoting of entity-attribute-value rows into a struct, for
+//! `#[pg_mapper(pivot(key = "key", value = "value"))]` types.
+//!
+//! Every field is read from the row whose key column is the field's column
+//! name, parsing the text of its value column:
+//!
+//! ```
+//!     #[derive(PostgresMapper)]
+//!     #[pg_mapper(pivot(key = "key", value = "value_text"))]
+//!     pub struct Settings {
+//!         pub theme: String,
+//!         #[pg_mapper(rename = "page-size")]
+//!         pub page_size: u32,
+//!         pub beta: Option<bool>,
+//!         #[pg_mapper(json_text)]
+//!         pub features: Vec<String>,
+//!     }
+//!
+//!     let rows = client
+//!         .query("SELECT key, value_text FROM settings WHERE entity_id = $1", &[&id])
+//!         .await?;
+//!     let settings = Settings::from_kv_rows(&rows)?;
+//! ```
+//!
+//! Values are parsed with `FromStr`, or deserialized with `serde_json` for
+//! `json_text` fields. Rows with other keys are ignored.
+//!
+//! Missing keys result in an [`Error::ColumnNotFound`] naming the key, and
+//! NULL values in an [`Error::UnexpectedNull`], unless the field is an
+//! `Option` or `default_on_null`. A key found in more than one row results
+//! in an [`Error::ColumnConversion`] naming the key, with a
+//! [`DuplicatePivotKey`] source.
+//!
+//! [`Error::ColumnNotFound`]: enum.Error.html#variant.ColumnNotFound
+//! [`Error::UnexpectedNull`]: enum.Error.html#variant.UnexpectedNull
+//! [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+//! [`DuplicatePivotKey`]: struct.DuplicatePivotKey.html
+
+use crate::{capture_backtrace, try_get_column, Error};
+
+use tokio_postgres::row::Row as TokioRow;
+
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// The parsing of the text of a pivoted value into the type of its field.
+#[doc(hidden)]
+pub type PivotParse<T> = fn(&str) -> Result<T, Box<dyn StdError + Send + Sync>>;
+
+/// Collects the values of the given keys from key/value rows, on behalf of
+/// derive-generated code: `None` for missing keys, and `Some(None)` for NULL
+/// values.
+#[doc(hidden)]
+pub fn pivot_values<'a>(
+    rows: &'a [TokioRow],
+    key_column: &str,
+    value_column: &str,
+    keys: &[&str],
+    type_name: &'static str,
+) -> Result<Vec<Option<Option<&'a str>>>, Error> {
+    let mut values = vec![None; keys.len()];
+
+    for row in rows {
+        let key: &str = try_get_column(row, key_column, "", type_name)?;
+        let idx = match keys.iter().position(|candidate| *candidate == key) {
+            Some(idx) => idx,
+            None => continue,
+        };
+
+        if values[idx].is_some() {
+            return Err(Error::ColumnConversion {
+                column: key.to_string(),
+                rust_type: std::any::type_name::<&str>(),
+                pg_type: None,
+                source: Box::new(DuplicatePivotKey {
+                    key: key.to_string(),
+                }),
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            });
+        }
+
+        values[idx] = Some(try_get_column(row, value_column, "", type_name)?);
+    }
+
+    Ok(values)
+}
+
+/// Parses the pivoted value of a field which can't be missing or NULL, on
+/// behalf of derive-generated code.
+#[doc(hidden)]
+pub fn pivot_field<T>(
+    value: Option<Option<&str>>,
+    parse: PivotParse<T>,
+    key: &str,
+    field: &'static str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    match value {
+        Some(Some(text)) => parse_pivot_value(text, parse, key, type_name),
+        Some(None) => Err(Error::UnexpectedNull {
+            column: key.to_string(),
+            field,
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        }),
+        None => Err(Error::ColumnNotFound {
+            column: key.to_string(),
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        }),
+    }
+}
+
+/// Parses the pivoted value of a field which can be missing or NULL, on
+/// behalf of derive-generated code.
+#[doc(hidden)]
+pub fn pivot_optional_field<T>(
+    value: Option<Option<&str>>,
+    parse: PivotParse<T>,
+    key: &str,
+    type_name: &'static str,
+) -> Result<Option<T>, Error> {
+    match value {
+        Some(Some(text)) => parse_pivot_value(text, parse, key, type_name).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn parse_pivot_value<T>(
+    text: &str,
+    parse: PivotParse<T>,
+    key: &str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    parse(text).map_err(|source| Error::ColumnConversion {
+        column: key.to_string(),
+        rust_type: std::any::type_name::<T>(),
+        pg_type: None,
+        source,
+        type_name: Some(type_name),
+        backtrace: capture_backtrace(),
+    })
+}
+
+/// Parses a pivoted value with `FromStr`, on behalf of derive-generated code.
+#[doc(hidden)]
+pub fn parse_pivot_text<T>(text: &str) -> Result<T, Box<dyn StdError + Send + Sync>>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn StdError + Send + Sync>>,
+{
+    text.parse().map_err(Into::into)
+}
+
+/// Deserializes a pivoted value with `serde_json`, on behalf of
+/// derive-generated code for `json_text` fields.
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub fn parse_pivot_json<T>(text: &str) -> Result<T, Box<dyn StdError + Send + Sync>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    use crate::simple_query::FromSqlText;
+
+    crate::JsonText::<T>::from_sql_text(text).map(crate::JsonText::into_inner)
+}
+
+/// The source of an [`Error::ColumnConversion`] for a key found in more than
+/// one of the rows pivoted into a struct.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+#[derive(Clone, Debug)]
+pub struct DuplicatePivotKey {
+    key: String,
+}
+
+impl DuplicatePivotKey {
+    /// Get the repeated key.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl Display for DuplicatePivotKey {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "key {:?} is found in more than one row", self.key)
+    }
+}
+
+impl StdError for DuplicatePivotKey {}
@@ -0,0 +1,248 @@
+//! A minimal `SELECT` builder whose columns are checked at compile time,
+//! enabled with the `query` feature.
+//!
+//! With the `#[pg_mapper(query)]` container attribute, the derive generates
+//! an enum naming every mapped column, such as `UserColumn::Email` for the
+//! `email` field of `User`, and implements [`TokioPostgresQuery`]. Filters and
+//! orderings then take a variant instead of a string, so a misspelled or
+//! removed column is a compile error:
+//!
+//! ```
+//!     use tokio_pg_mapper::query::{Operator, TokioPostgresQuery};
+//!     use tokio_pg_mapper::SortDirection;
+//!
+//!     #[derive(PostgresMapper)]
+//!     #[pg_mapper(table = "user", query)]
+//!     pub struct User {
+//!         pub id: i64,
+//!         pub email: Option<String>,
+//!     }
+//!
+//!     let select = User::select()
+//!         .filter(UserColumn::Email, Operator::Eq)
+//!         .order_by(UserColumn::Id, SortDirection::Desc)
+//!         .limit_param();
+//!
+//!     // "SELECT  id ,  email  FROM \"user\" WHERE email = $1 ORDER BY id DESC LIMIT $2"
+//!     let rows = client.query(&select.sql(), &[&email, &10i64]).await?;
+//!     assert_eq!(select.param_count(), 2);
+//! ```
+//!
+//! Only single table `SELECT`s are built. Anything else, such as
+//! `FOR UPDATE`, can be appended with [`Select::suffix`].
+//!
+//! [`TokioPostgresQuery`]: trait.TokioPostgresQuery.html
+//! [`Select::suffix`]: struct.Select.html#method.suffix
+
+use crate::ident::quote_identifier;
+use crate::{FromTokioPostgresRow, SortDirection};
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// A column of a mapped type, as generated by the derive for
+/// `#[pg_mapper(query)]`.
+pub trait Column: Copy {
+    /// Get the column name, as returned by
+    /// [`FromTokioPostgresRow::columns`].
+    ///
+    /// [`FromTokioPostgresRow::columns`]: ../trait.FromTokioPostgresRow.html#tymethod.columns
+    fn name(self) -> &'static str;
+}
+
+/// Trait for mapped types with a generated column enum, providing the
+/// [`Select`] builder.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(query)]` container attribute, this will be
+/// implemented on types, along with a public `{Type}Column` enum with a
+/// variant per field, named after the field in upper camel case.
+///
+/// [`Select`]: struct.Select.html
+pub trait TokioPostgresQuery: FromTokioPostgresRow {
+    /// The generated column enum.
+    type Column: Column;
+
+    /// Starts a `SELECT` of every mapped column from the table.
+    fn select() -> Select<Self::Column> {
+        Select::new(Self::sql_fields(), Self::sql_table())
+    }
+}
+
+/// Comparison of a column in a [`Select::filter`].
+///
+/// [`Select::filter`]: struct.Select.html#method.filter
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Operator {
+    /// `column = $n`.
+    Eq,
+    /// `column <> $n`.
+    Ne,
+    /// `column < $n`.
+    Lt,
+    /// `column <= $n`.
+    Le,
+    /// `column > $n`.
+    Gt,
+    /// `column >= $n`.
+    Ge,
+    /// `column LIKE $n`.
+    Like,
+    /// `column ILIKE $n`.
+    ILike,
+    /// `column = ANY($n)`, taking an array parameter.
+    Any,
+    /// `column IS NULL`, taking no parameter.
+    IsNull,
+    /// `column IS NOT NULL`, taking no parameter.
+    IsNotNull,
+}
+
+impl Operator {
+    fn condition(self, column: &str, placeholder: usize) -> String {
+        let operator = match self {
+            Operator::Eq => "=",
+            Operator::Ne => "<>",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Like => "LIKE",
+            Operator::ILike => "ILIKE",
+            Operator::Any => return format!("{} = ANY(${})", column, placeholder),
+            Operator::IsNull => return format!("{} IS NULL", column),
+            Operator::IsNotNull => return format!("{} IS NOT NULL", column),
+        };
+
+        format!("{} {} ${}", column, operator, placeholder)
+    }
+
+    fn takes_param(self) -> bool {
+        !matches!(self, Operator::IsNull | Operator::IsNotNull)
+    }
+}
+
+/// Builder of a `SELECT` of a mapped type, started by
+/// [`TokioPostgresQuery::select`].
+///
+/// Placeholders are numbered in the order the parameterized clauses are
+/// added, so the query parameters must be given in that order.
+///
+/// [`TokioPostgresQuery::select`]: trait.TokioPostgresQuery.html#method.select
+#[derive(Clone, Debug)]
+pub struct Select<C> {
+    fields: String,
+    table: String,
+    filters: Vec<(C, Operator, usize)>,
+    order_by: Vec<(C, SortDirection)>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    suffix: Option<(String, usize)>,
+    param_count: usize,
+}
+
+impl<C: Column> Select<C> {
+    fn new(fields: String, table: String) -> Self {
+        Self {
+            fields,
+            table,
+            filters: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+            suffix: None,
+            param_count: 0,
+        }
+    }
+
+    /// Adds a condition on a column, AND-ed with the previous ones.
+    pub fn filter(mut self, column: C, operator: Operator) -> Self {
+        if operator.takes_param() {
+            self.param_count += 1;
+        }
+        self.filters.push((column, operator, self.param_count));
+
+        self
+    }
+
+    /// Orders by a column, after the previous orderings.
+    pub fn order_by(mut self, column: C, direction: SortDirection) -> Self {
+        self.order_by.push((column, direction));
+
+        self
+    }
+
+    /// Adds a `LIMIT` taking the next placeholder.
+    pub fn limit_param(mut self) -> Self {
+        self.param_count += 1;
+        self.limit = Some(self.param_count);
+
+        self
+    }
+
+    /// Adds an `OFFSET` taking the next placeholder.
+    pub fn offset_param(mut self) -> Self {
+        self.param_count += 1;
+        self.offset = Some(self.param_count);
+
+        self
+    }
+
+    /// Appends raw SQL after every other clause, such as `FOR UPDATE`,
+    /// replacing any previous suffix.
+    ///
+    /// The suffix isn't checked. Its placeholders must follow those of every
+    /// other clause, and `params` is how many it takes.
+    pub fn suffix(mut self, sql: &str, params: usize) -> Self {
+        self.suffix = Some((sql.to_string(), params));
+
+        self
+    }
+
+    /// Get the number of query parameters the statement expects.
+    pub fn param_count(&self) -> usize {
+        let suffix = self.suffix.as_ref().map(|(_, params)| *params);
+
+        self.param_count + suffix.unwrap_or(0)
+    }
+
+    /// Get the statement.
+    pub fn sql(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<C: Column> Display for Select<C> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "SELECT {} FROM {}", self.fields, self.table)?;
+
+        for (i, (column, operator, placeholder)) in self.filters.iter().enumerate() {
+            let keyword = if i == 0 { "WHERE" } else { "AND" };
+            let condition =
+                operator.condition(&quote_identifier(column.name()), *placeholder);
+            write!(f, " {} {}", keyword, condition)?;
+        }
+
+        for (i, (column, direction)) in self.order_by.iter().enumerate() {
+            let keyword = if i == 0 { " ORDER BY" } else { "," };
+            write!(
+                f,
+                "{} {} {}",
+                keyword,
+                quote_identifier(column.name()),
+                direction
+            )?;
+        }
+
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT ${}", limit)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " OFFSET ${}", offset)?;
+        }
+        if let Some((ref suffix, _)) = self.suffix {
+            write!(f, " {}", suffix)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! The `PgRange` field type.
+
+use bytes::{BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, Kind, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::ops::{Bound, Range, RangeFrom, RangeInclusive};
+
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LOWER_INCLUSIVE: u8 = 0x02;
+const RANGE_UPPER_INCLUSIVE: u8 = 0x04;
+const RANGE_LOWER_INFINITE: u8 = 0x08;
+const RANGE_UPPER_INFINITE: u8 = 0x10;
+
+/// Mapping of range columns and parameters, such as `int4range`,
+/// `int8range`, `numrange` or `tstzrange`, whose bounds are mapped as `T`.
+///
+/// An infinite bound is `Bound::Unbounded`. Postgres normalizes the bounds of
+/// discrete ranges, so `[1,3]` in an `int4range` column is read as
+/// `Included(1)` and `Excluded(4)`.
+///
+/// With the `chrono` feature, `PgRange<DateTime<Utc>>` maps `tstzrange` and
+/// `PgRange<NaiveDateTime>` maps `tsrange`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PgRange<T> {
+    /// A range containing no values, such as `empty` or `[1,1)`.
+    Empty,
+    /// A range between two bounds.
+    Bounds {
+        /// The lower bound of the range.
+        lower: Bound<T>,
+        /// The upper bound of the range.
+        upper: Bound<T>,
+    },
+}
+
+impl<T> PgRange<T> {
+    /// Creates a range between two bounds.
+    pub fn new(lower: Bound<T>, upper: Bound<T>) -> Self {
+        PgRange::Bounds { lower, upper }
+    }
+
+    /// Whether the range is the empty range.
+    pub fn is_empty(&self) -> bool {
+        matches!(*self, PgRange::Empty)
+    }
+
+    /// Get the lower bound, or `None` for an empty range.
+    pub fn lower(&self) -> Option<Bound<&T>> {
+        match *self {
+            PgRange::Empty => None,
+            PgRange::Bounds { ref lower, .. } => Some(bound_ref(lower)),
+        }
+    }
+
+    /// Get the upper bound, or `None` for an empty range.
+    pub fn upper(&self) -> Option<Bound<&T>> {
+        match *self {
+            PgRange::Empty => None,
+            PgRange::Bounds { ref upper, .. } => Some(bound_ref(upper)),
+        }
+    }
+}
+
+fn bound_ref<T>(bound: &Bound<T>) -> Bound<&T> {
+    match *bound {
+        Bound::Included(ref value) => Bound::Included(value),
+        Bound::Excluded(ref value) => Bound::Excluded(value),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl<T> From<Range<T>> for PgRange<T> {
+    fn from(range: Range<T>) -> Self {
+        PgRange::new(Bound::Included(range.start), Bound::Excluded(range.end))
+    }
+}
+
+impl<T> From<RangeInclusive<T>> for PgRange<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let (start, end) = range.into_inner();
+
+        PgRange::new(Bound::Included(start), Bound::Included(end))
+    }
+}
+
+impl<T> From<RangeFrom<T>> for PgRange<T> {
+    fn from(range: RangeFrom<T>) -> Self {
+        PgRange::new(Bound::Included(range.start), Bound::Unbounded)
+    }
+}
+
+fn element_type(ty: &Type) -> Option<&Type> {
+    match ty.kind() {
+        Kind::Range(element) => Some(element),
+        _ => None,
+    }
+}
+
+fn read_bound<'a, T: FromSql<'a>>(
+    raw: &mut &'a [u8],
+    ty: &Type,
+    infinite: bool,
+    inclusive: bool,
+) -> Result<Bound<T>, Box<dyn StdError + Sync + Send>> {
+    if infinite {
+        return Ok(Bound::Unbounded);
+    }
+
+    if raw.len() < 4 {
+        return Err("invalid range bound length".into());
+    }
+    let (len, rest) = raw.split_at(4);
+    let len = i32::from_be_bytes([len[0], len[1], len[2], len[3]]);
+    let len = usize::try_from(len).map_err(|_| "unexpected NULL range bound")?;
+    if rest.len() < len {
+        return Err("invalid range bound length".into());
+    }
+    let (value, rest) = rest.split_at(len);
+    *raw = rest;
+
+    let value = T::from_sql(ty, value)?;
+
+    Ok(if inclusive {
+        Bound::Included(value)
+    } else {
+        Bound::Excluded(value)
+    })
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for PgRange<T> {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let element = element_type(ty).ok_or("not a range type")?;
+        let (&flags, mut raw) = raw.split_first().ok_or("invalid range length")?;
+
+        if flags & RANGE_EMPTY != 0 {
+            return Ok(PgRange::Empty);
+        }
+
+        let lower = read_bound(
+            &mut raw,
+            element,
+            flags & RANGE_LOWER_INFINITE != 0,
+            flags & RANGE_LOWER_INCLUSIVE != 0,
+        )?;
+        let upper = read_bound(
+            &mut raw,
+            element,
+            flags & RANGE_UPPER_INFINITE != 0,
+            flags & RANGE_UPPER_INCLUSIVE != 0,
+        )?;
+
+        if !raw.is_empty() {
+            return Err("invalid range length".into());
+        }
+
+        Ok(PgRange::Bounds { lower, upper })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        element_type(ty).is_some_and(T::accepts)
+    }
+}
+
+fn write_bound<T: ToSql>(
+    out: &mut BytesMut,
+    ty: &Type,
+    bound: &Bound<T>,
+) -> Result<(), Box<dyn StdError + Sync + Send>> {
+    let value = match *bound {
+        Bound::Included(ref value) | Bound::Excluded(ref value) => value,
+        Bound::Unbounded => return Ok(()),
+    };
+
+    // Reserve the length, written once the value is encoded.
+    let start = out.len();
+    out.put_i32(0);
+
+    if let IsNull::Yes = value.to_sql(ty, out)? {
+        return Err("range bounds can't be NULL".into());
+    }
+
+    let len = i32::try_from(out.len() - start - 4).map_err(|_| "range bound too long")?;
+    out[start..start + 4].copy_from_slice(&len.to_be_bytes());
+
+    Ok(())
+}
+
+impl<T: ToSql> ToSql for PgRange<T> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        let element = element_type(ty).ok_or("not a range type")?;
+
+        let (lower, upper) = match *self {
+            PgRange::Empty => {
+                out.put_u8(RANGE_EMPTY);
+                return Ok(IsNull::No);
+            }
+            PgRange::Bounds {
+                ref lower,
+                ref upper,
+            } => (lower, upper),
+        };
+
+        let mut flags = 0;
+        match *lower {
+            Bound::Included(_) => flags |= RANGE_LOWER_INCLUSIVE,
+            Bound::Excluded(_) => {}
+            Bound::Unbounded => flags |= RANGE_LOWER_INFINITE,
+        }
+        match *upper {
+            Bound::Included(_) => flags |= RANGE_UPPER_INCLUSIVE,
+            Bound::Excluded(_) => {}
+            Bound::Unbounded => flags |= RANGE_UPPER_INFINITE,
+        }
+        out.put_u8(flags);
+
+        write_bound(out, element, lower)?;
+        write_bound(out, element, upper)?;
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        element_type(ty).is_some_and(T::accepts)
+    }
+
+    to_sql_checked!();
+}
@@ -0,0 +1,156 @@
+//! Data access through a single value per mapped type, enabled with the
+//! `client` feature.
+//!
+//! A [`Repository`] runs the statements of the traits derived for a type on
+//! one client, so that cross-cutting concerns, such as metrics or routing
+//! reads to a replica, can be added by wrapping it rather than every call
+//! site:
+//!
+//! ```
+//!     use tokio_pg_mapper::Repository;
+//!
+//!     let users = Repository::<User, _>::new(&client);
+//!
+//!     let user = users.insert(&new_user).await?;
+//!     let found: Option<User> = users.find(&user.id).await?;
+//!     let active: Vec<User> = users
+//!         .query("SELECT id, email FROM users WHERE active = $1", &[&true])
+//!         .await?;
+//! ```
+//!
+//! [`Repository`]: struct.Repository.html
+
+use crate::{
+    ClientExt, Error, FromTokioPostgresRow, TokioPostgresCrud, TokioPostgresInsert,
+    TokioPostgresPrimaryKey,
+};
+
+use tokio_postgres::types::ToSql;
+use tokio_postgres::GenericClient;
+
+use std::marker::PhantomData;
+
+/// The data access of a mapped type on a client, such as a `Client`, a
+/// `Transaction`, or the `Client` of a pooled connection.
+///
+/// Every method is available as soon as `T` implements the trait whose
+/// statement it runs, so a repository of a type deriving only
+/// [`TokioPostgresPrimaryKey`] can still `list` its rows.
+///
+/// [`TokioPostgresPrimaryKey`]: ../trait.TokioPostgresPrimaryKey.html
+#[derive(Debug)]
+pub struct Repository<'a, T, C> {
+    client: &'a C,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, C> Clone for Repository<'_, T, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, C> Copy for Repository<'_, T, C> {}
+
+impl<'a, T, C> Repository<'a, T, C>
+where
+    C: GenericClient + Sync,
+{
+    /// Creates a repository running its statements on the given client.
+    pub fn new(client: &'a C) -> Self {
+        Self {
+            client,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the client the statements are run on.
+    pub fn client(&self) -> &'a C {
+        self.client
+    }
+
+    /// Fetches the row with the given primary key.
+    ///
+    /// See [`TokioPostgresCrud::find`].
+    ///
+    /// [`TokioPostgresCrud::find`]: ../trait.TokioPostgresCrud.html#method.find
+    pub async fn find(&self, key: &T::PrimaryKey) -> Result<Option<T>, Error>
+    where
+        T: TokioPostgresCrud,
+    {
+        T::find(self.client, key).await
+    }
+
+    /// Inserts a row, and maps the stored row.
+    ///
+    /// See [`TokioPostgresCrud::insert`], which this runs without requiring
+    /// a primary key.
+    ///
+    /// [`TokioPostgresCrud::insert`]: ../trait.TokioPostgresCrud.html#method.insert
+    pub async fn insert(&self, value: &T) -> Result<T, Error>
+    where
+        T: TokioPostgresInsert,
+    {
+        let statement = T::insert_returning_stmt();
+
+        self.client
+            .query_one_as(statement.as_str(), &value.to_params())
+            .await
+    }
+
+    /// Updates the row with the primary key of the given value, returning
+    /// whether it existed.
+    ///
+    /// See [`TokioPostgresCrud::update`].
+    ///
+    /// [`TokioPostgresCrud::update`]: ../trait.TokioPostgresCrud.html#method.update
+    pub async fn update(&self, value: &T) -> Result<bool, Error>
+    where
+        T: TokioPostgresCrud + Sync,
+    {
+        T::update(self.client, value).await
+    }
+
+    /// Deletes the row with the given primary key, returning whether it
+    /// existed.
+    ///
+    /// See [`TokioPostgresCrud::delete`].
+    ///
+    /// [`TokioPostgresCrud::delete`]: ../trait.TokioPostgresCrud.html#method.delete
+    pub async fn delete(&self, key: &T::PrimaryKey) -> Result<bool, Error>
+    where
+        T: TokioPostgresCrud,
+    {
+        T::delete(self.client, key).await
+    }
+
+    /// Fetches every row, ordered by primary key.
+    ///
+    /// See [`TokioPostgresPrimaryKey::select_all_stmt`].
+    ///
+    /// [`TokioPostgresPrimaryKey::select_all_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.select_all_stmt
+    pub async fn list(&self) -> Result<Vec<T>, Error>
+    where
+        T: TokioPostgresPrimaryKey,
+    {
+        let statement = T::select_all_stmt();
+
+        self.client.query_as(statement.as_str(), &[]).await
+    }
+
+    /// Runs a query and maps every returned row.
+    ///
+    /// See [`ClientExt::query_as`].
+    ///
+    /// [`ClientExt::query_as`]: ../trait.ClientExt.html#tymethod.query_as
+    pub async fn query(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+    {
+        self.client.query_as(statement, params).await
+    }
+}
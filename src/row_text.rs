@@ -0,0 +1,423 @@
+//! Best-effort rendering of any row as text, for `row_to_text`.
+
+use crate::{
+    capture_backtrace, try_get_column_at, Error, PgBits, PgChar, PgInterval, PgMacAddr,
+    PgMacAddr8, PgNumeric, PgTsQuery, PgTsVector, PgXml,
+};
+
+use bytes::Buf;
+use tokio_postgres::row::Row as TokioRow;
+use tokio_postgres::types::{FromSql, Kind, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Days from the Unix epoch to the Postgres epoch, 2000-01-01.
+const POSTGRES_EPOCH_DAYS: i64 = 10_957;
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Renders every column of a row as text, paired with its name, without
+/// defining a type to map it to: for debugging, or exporting the results of
+/// arbitrary queries to CSV.
+///
+/// NULL values are `None`, and other values are rendered by column type:
+///
+/// - booleans as `true` or `false`
+/// - integers, floats and `numeric` in decimal, with `Infinity`,
+///   `-Infinity` and `NaN` for special floats
+/// - text, enums, `json`, `jsonb` and `xml` as-is
+/// - `uuid` as hyphenated lowercase hexadecimal
+/// - `bytea` as hexadecimal, in Postgres' `\x` format
+/// - `date`, `time`, `timestamp` and `timestamptz` in ISO 8601, with a space
+///   between the date and the time like Postgres, and timestamps with a time
+///   zone in UTC with a `+00` suffix
+/// - `interval`, `inet`, `cidr`, `macaddr`, `bit`, `tsvector` and `tsquery`
+///   like Postgres does
+/// - arrays of any of the above in array-literal syntax, such as
+///   `{1,2,NULL}` or `{{"a b",c},{d,e}}`
+/// - domains like their underlying type
+///
+/// Values of other types, such as composites, ranges or geometric types,
+/// are rendered as a placeholder naming the type, such as
+/// `<unsupported type point>`, so that one unsupported column doesn't fail
+/// the whole row.
+///
+/// ```
+///     let rows = client.query("SELECT * FROM events", &[]).await?;
+///
+///     for row in &rows {
+///         for (column, value) in tokio_pg_mapper::row_to_text(row)? {
+///             println!("{} = {}", column, value.as_deref().unwrap_or("NULL"));
+///         }
+///     }
+/// ```
+///
+/// # Errors
+///
+/// Returns an [`Error::ColumnConversion`] naming the column if a value of a
+/// supported type can't be decoded.
+///
+/// [`Error::ColumnConversion`]: enum.Error.html#variant.ColumnConversion
+pub fn row_to_text(row: &TokioRow) -> Result<Vec<(String, Option<String>)>, Error> {
+    let type_name = std::any::type_name::<Vec<(String, Option<String>)>>();
+
+    row.columns()
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| {
+            let raw: Option<RawValue> =
+                try_get_column_at(row, idx, column.name(), "", type_name)?;
+            let ty = column.type_();
+
+            let text = match raw {
+                Some(RawValue(raw)) => {
+                    let text =
+                        render(ty, raw).map_err(|source| Error::ColumnConversion {
+                            column: column.name().to_string(),
+                            rust_type: std::any::type_name::<String>(),
+                            pg_type: Some(ty.clone()),
+                            source,
+                            type_name: Some(type_name),
+                            backtrace: capture_backtrace(),
+                        })?;
+
+                    Some(
+                        text.unwrap_or_else(|| {
+                            format!("<unsupported type {}>", ty.name())
+                        }),
+                    )
+                }
+                None => None,
+            };
+
+            Ok((column.name().to_string(), text))
+        })
+        .collect()
+}
+
+/// The undecoded value of a column of any type.
+struct RawValue<'a>(&'a [u8]);
+
+impl<'a> FromSql<'a> for RawValue<'a> {
+    fn from_sql(
+        _: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(RawValue(raw))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// Renders a value, or returns `None` if its type isn't supported.
+fn render(
+    ty: &Type,
+    raw: &[u8],
+) -> Result<Option<String>, Box<dyn StdError + Sync + Send>> {
+    macro_rules! display {
+        ($($rust:ty),*) => {
+            $(
+                if <$rust as FromSql>::accepts(ty) {
+                    return Ok(Some(<$rust as FromSql>::from_sql(ty, raw)?.to_string()));
+                }
+            )*
+        };
+    }
+
+    match *ty.kind() {
+        Kind::Array(ref member) => return render_array(member, raw),
+        Kind::Domain(ref inner) => return render(inner, raw),
+        Kind::Enum(_) => return Ok(Some(std::str::from_utf8(raw)?.to_string())),
+        _ => {}
+    }
+
+    if <&str as FromSql>::accepts(ty) {
+        return Ok(Some(<&str as FromSql>::from_sql(ty, raw)?.to_string()));
+    }
+
+    display!(bool, PgChar, i16, i32, i64, u32);
+    display!(PgNumeric, PgInterval, PgBits, PgXml, PgMacAddr, PgMacAddr8);
+    display!(PgTsVector, PgTsQuery);
+
+    let text = if *ty == Type::FLOAT4 {
+        render_float(f64::from(f32::from_sql(ty, raw)?))
+    } else if *ty == Type::FLOAT8 {
+        render_float(f64::from_sql(ty, raw)?)
+    } else if *ty == Type::JSON {
+        std::str::from_utf8(raw)?.to_string()
+    } else if *ty == Type::JSONB {
+        match raw.split_first() {
+            Some((1, json)) => std::str::from_utf8(json)?.to_string(),
+            _ => return Err("unsupported jsonb encoding version".into()),
+        }
+    } else if *ty == Type::UUID {
+        render_uuid(raw)?
+    } else if *ty == Type::BYTEA {
+        raw.iter().fold(String::from("\\x"), |mut text, byte| {
+            let _ = write!(text, "{:02x}", byte);
+            text
+        })
+    } else if *ty == Type::INET || *ty == Type::CIDR {
+        render_inet(raw, *ty == Type::CIDR)?
+    } else if *ty == Type::DATE {
+        render_date(i64::from(i32::from_sql(ty, raw)?))
+    } else if *ty == Type::TIME {
+        render_time(i64::from_sql(ty, raw)?)
+    } else if *ty == Type::TIMESTAMP {
+        render_timestamp(i64::from_sql(ty, raw)?, "")
+    } else if *ty == Type::TIMESTAMPTZ {
+        render_timestamp(i64::from_sql(ty, raw)?, "+00")
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(text))
+}
+
+fn render_float(value: f64) -> String {
+    match value {
+        _ if value.is_nan() => "NaN".to_string(),
+        _ if value == f64::INFINITY => "Infinity".to_string(),
+        _ if value == f64::NEG_INFINITY => "-Infinity".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn render_uuid(raw: &[u8]) -> Result<String, Box<dyn StdError + Sync + Send>> {
+    if raw.len() != 16 {
+        return Err("invalid uuid length".into());
+    }
+
+    let mut text = String::with_capacity(36);
+    for (idx, byte) in raw.iter().enumerate() {
+        if matches!(idx, 4 | 6 | 8 | 10) {
+            text.push('-');
+        }
+        let _ = write!(text, "{:02x}", byte);
+    }
+
+    Ok(text)
+}
+
+fn render_inet(
+    raw: &[u8],
+    is_cidr: bool,
+) -> Result<String, Box<dyn StdError + Sync + Send>> {
+    let (address, bits) = match *raw {
+        [2, bits, _, 4, ref octets @ ..] => {
+            let octets =
+                <[u8; 4]>::try_from(octets).map_err(|_| "invalid inet length")?;
+
+            (IpAddr::V4(Ipv4Addr::from(octets)), bits)
+        }
+        [3, bits, _, 16, ref octets @ ..] => {
+            let octets =
+                <[u8; 16]>::try_from(octets).map_err(|_| "invalid inet length")?;
+
+            (IpAddr::V6(Ipv6Addr::from(octets)), bits)
+        }
+        _ => return Err("invalid inet encoding".into()),
+    };
+
+    // Like Postgres, `inet` values only show their netmask if it doesn't
+    // cover the whole address.
+    let full = if address.is_ipv4() { 32 } else { 128 };
+    if is_cidr || bits != full {
+        Ok(format!("{}/{}", address, bits))
+    } else {
+        Ok(address.to_string())
+    }
+}
+
+/// Renders a date, counted in days since the Postgres epoch.
+fn render_date(days: i64) -> String {
+    match days {
+        _ if days == i64::from(i32::MAX) => "infinity".to_string(),
+        _ if days == i64::from(i32::MIN) => "-infinity".to_string(),
+        _ => {
+            let (date, era) = date_parts(days);
+
+            format!("{}{}", date, era)
+        }
+    }
+}
+
+/// Renders a time of day, counted in microseconds since midnight.
+fn render_time(micros: i64) -> String {
+    let seconds = micros / 1_000_000;
+    let mut text = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+
+    let fraction = micros % 1_000_000;
+    if fraction != 0 {
+        let digits = format!("{:06}", fraction);
+        text.push('.');
+        text.push_str(digits.trim_end_matches('0'));
+    }
+
+    text
+}
+
+/// Renders a timestamp, counted in microseconds since the Postgres epoch,
+/// followed by the given time zone suffix.
+fn render_timestamp(micros: i64, zone: &str) -> String {
+    match micros {
+        i64::MAX => "infinity".to_string(),
+        i64::MIN => "-infinity".to_string(),
+        _ => {
+            let (date, era) = date_parts(micros.div_euclid(MICROS_PER_DAY));
+            let time = render_time(micros.rem_euclid(MICROS_PER_DAY));
+
+            format!("{} {}{}{}", date, time, zone, era)
+        }
+    }
+}
+
+/// Splits a date, counted in days since the Postgres epoch, into its
+/// `YYYY-MM-DD` text and its ` BC` suffix, if any.
+fn date_parts(days: i64) -> (String, &'static str) {
+    // The conversion of days to a proleptic Gregorian date from Howard
+    // Hinnant's `civil_from_days`.
+    let days = days + POSTGRES_EPOCH_DAYS + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    // There's no year 0: 1 BC directly precedes 1 AD.
+    if year > 0 {
+        (format!("{:04}-{:02}-{:02}", year, month, day), "")
+    } else {
+        (format!("{:04}-{:02}-{:02}", 1 - year, month, day), " BC")
+    }
+}
+
+/// Renders an array in array-literal syntax, or returns `None` if the type
+/// of its elements isn't supported.
+fn render_array(
+    member: &Type,
+    mut raw: &[u8],
+) -> Result<Option<String>, Box<dyn StdError + Sync + Send>> {
+    let dimensions = read_i32(&mut raw)?;
+    // Skip the NULL flag and the OID of the elements.
+    read_i32(&mut raw)?;
+    read_i32(&mut raw)?;
+
+    let mut lengths = Vec::new();
+    for _ in 0..dimensions {
+        let len = read_i32(&mut raw)?;
+        lengths.push(usize::try_from(len).map_err(|_| "invalid array length")?);
+        // Skip the lower bound.
+        read_i32(&mut raw)?;
+    }
+
+    if lengths.is_empty() {
+        return Ok(Some("{}".to_string()));
+    }
+
+    let count = lengths.iter().product::<usize>();
+    let mut elements = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len = read_i32(&mut raw)?;
+        let len = match usize::try_from(len) {
+            Ok(len) if len <= raw.len() => len,
+            Ok(_) => return Err("invalid array element length".into()),
+            // A negative length is a NULL element.
+            Err(_) => {
+                elements.push(None);
+                continue;
+            }
+        };
+
+        let (element, rest) = raw.split_at(len);
+        raw = rest;
+
+        match render(member, element)? {
+            Some(text) => elements.push(Some(text)),
+            None => return Ok(None),
+        }
+    }
+
+    let mut text = String::new();
+    write_array(&lengths, &mut elements.into_iter(), &mut text);
+
+    Ok(Some(text))
+}
+
+fn write_array(
+    lengths: &[usize],
+    elements: &mut impl Iterator<Item = Option<String>>,
+    text: &mut String,
+) {
+    text.push('{');
+
+    for idx in 0..lengths[0] {
+        if idx > 0 {
+            text.push(',');
+        }
+
+        if lengths.len() > 1 {
+            write_array(&lengths[1..], elements, text);
+            continue;
+        }
+
+        match elements.next() {
+            Some(Some(element)) => write_array_element(&element, text),
+            _ => text.push_str("NULL"),
+        }
+    }
+
+    text.push('}');
+}
+
+/// Writes an element of an array literal, quoting it like Postgres does if
+/// it's empty, could be read as NULL, or contains delimiters or whitespace.
+fn write_array_element(element: &str, text: &mut String) {
+    let quoted = element.is_empty()
+        || element.eq_ignore_ascii_case("NULL")
+        || element
+            .chars()
+            .any(|c| matches!(c, '{' | '}' | ',' | '"' | '\\') || c.is_whitespace());
+
+    if !quoted {
+        text.push_str(element);
+        return;
+    }
+
+    text.push('"');
+    for c in element.chars() {
+        if matches!(c, '"' | '\\') {
+            text.push('\\');
+        }
+        text.push(c);
+    }
+    text.push('"');
+}
+
+fn read_i32(raw: &mut &[u8]) -> Result<i32, Box<dyn StdError + Sync + Send>> {
+    if raw.len() < 4 {
+        return Err("invalid array header".into());
+    }
+
+    Ok(raw.get_i32())
+}
@@ -0,0 +1,119 @@
+//! The `FromTokioPostgresRows` trait, for values built from a whole result.
+
+use crate::{try_get_column_at, Error, FromTokioPostgresRow};
+
+use tokio_postgres::row::Row as TokioRow;
+use tokio_postgres::types::FromSql;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Trait converting all the rows of a result into a single value, such as a
+/// report aggregating line items of a grouped query, or settings loaded from
+/// a key/value table.
+///
+/// It is implemented for:
+///
+/// - every [`FromTokioPostgresRow`] type, from exactly one row
+/// - `Option<T>` of them, from zero or one row
+/// - `Vec<T>` of them, from any number of rows
+/// - `HashMap<K, V>`, from the first two columns of every row, as the key
+///   and value
+///
+/// so that generic helpers, such as [`ClientExt::query_rows_as`], can take a
+/// single bound covering every result shape. Other shapes can implement it
+/// by hand:
+///
+/// ```
+///     use tokio_pg_mapper::{Error, FromTokioPostgresRow, FromTokioPostgresRows};
+///     use tokio_postgres::Row;
+///
+///     pub struct Report {
+///         pub total: i64,
+///         pub items: Vec<LineItem>,
+///     }
+///
+///     impl FromTokioPostgresRows for Report {
+///         fn from_rows(rows: &[Row]) -> Result<Self, Error> {
+///             let items = LineItem::from_row_refs(rows)?;
+///             let total = items.iter().map(|item| item.amount).sum();
+///
+///             Ok(Report { total, items })
+///         }
+///     }
+/// ```
+///
+/// Its method shares the name of [`FromTokioPostgresRow::from_rows`], so
+/// with both traits in scope, calls on a concrete type must name the trait,
+/// as in `<User as FromTokioPostgresRows>::from_rows(&rows)`.
+///
+/// [`FromTokioPostgresRow`]: trait.FromTokioPostgresRow.html
+/// [`FromTokioPostgresRow::from_rows`]: trait.FromTokioPostgresRow.html#method.from_rows
+/// [`ClientExt::query_rows_as`]: client/trait.ClientExt.html#tymethod.query_rows_as
+pub trait FromTokioPostgresRows: Sized {
+    /// Converts the rows of a result into a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoRows`] or [`Error::TooManyRows`] if there are
+    /// fewer or more rows than the value is built from, or the first error
+    /// encountered converting the rows.
+    ///
+    /// [`Error::NoRows`]: enum.Error.html#variant.NoRows
+    /// [`Error::TooManyRows`]: enum.Error.html#variant.TooManyRows
+    fn from_rows(rows: &[TokioRow]) -> Result<Self, Error>;
+}
+
+impl<T: FromTokioPostgresRow> FromTokioPostgresRows for T {
+    fn from_rows(rows: &[TokioRow]) -> Result<Self, Error> {
+        match rows {
+            [row] => T::from_row_ref(row),
+            [] => Err(Error::NoRows),
+            _ => Err(Error::TooManyRows),
+        }
+    }
+}
+
+impl<T: FromTokioPostgresRow> FromTokioPostgresRows for Option<T> {
+    fn from_rows(rows: &[TokioRow]) -> Result<Self, Error> {
+        match rows {
+            [row] => T::from_row_ref(row).map(Some),
+            [] => Ok(None),
+            _ => Err(Error::TooManyRows),
+        }
+    }
+}
+
+impl<T: FromTokioPostgresRow> FromTokioPostgresRows for Vec<T> {
+    fn from_rows(rows: &[TokioRow]) -> Result<Self, Error> {
+        T::from_row_refs(rows)
+    }
+}
+
+/// Rows are inserted in order, so the value of a repeated key is that of its
+/// last row.
+impl<K, V> FromTokioPostgresRows for HashMap<K, V>
+where
+    K: for<'a> FromSql<'a> + Eq + Hash,
+    V: for<'a> FromSql<'a>,
+{
+    fn from_rows(rows: &[TokioRow]) -> Result<Self, Error> {
+        let type_name = std::any::type_name::<Self>();
+
+        let mut map = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let (key, value) = match row.columns() {
+                [key, value, ..] => (key.name(), value.name()),
+                [key] => (key.name(), "1"),
+                [] => ("0", "1"),
+            };
+
+            map.insert(
+                try_get_column_at(row, 0, key, "", type_name)?,
+                try_get_column_at(row, 1, value, "", type_name)?,
+            );
+        }
+
+        Ok(map)
+    }
+}
@@ -0,0 +1,556 @@
+//! Metadata of the mapped columns, and their validation against the schema
+//! of a live database.
+//!
+//! With the `client` feature, [`validate`] compares the columns of a mapped
+//! type with those of its table, such as in a deployment health check:
+//!
+//! ```
+//!     use tokio_pg_mapper::validate;
+//!
+//!     let issues = validate::<User>(&client).await?;
+//!     for issue in &issues {
+//!         tracing::warn!("{}", issue);
+//!     }
+//! ```
+//!
+//! [`validate`]: fn.validate.html
+
+use tokio_postgres::types::Type;
+
+#[cfg(feature = "client")]
+use crate::ident::quote_identifier;
+#[cfg(feature = "client")]
+use crate::{Error, FromTokioPostgresRow};
+#[cfg(feature = "client")]
+use tokio_postgres::GenericClient;
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Metadata of a mapped column, as returned by
+/// [`FromTokioPostgresRow::column_metadata`].
+///
+/// [`FromTokioPostgresRow::column_metadata`]: trait.FromTokioPostgresRow.html#method.column_metadata
+#[derive(Clone, Copy, Debug)]
+pub struct ColumnMetadata {
+    /// The name of the column.
+    pub name: &'static str,
+    /// The name of the Rust type of the field, as given by
+    /// `std::any::type_name`, if known.
+    pub rust_type: Option<&'static str>,
+    /// Whether the field maps NULL values, such as an `Option`.
+    pub nullable: bool,
+    /// The `FromSql::accepts` function of the field type, if known.
+    pub accepts: Option<fn(&Type) -> bool>,
+    /// The Postgres type declared for the column, given by a `pg_type`
+    /// attribute or inferred from the field type, if known.
+    pub pg_type: Option<&'static str>,
+    /// Whether the column is part of the primary key.
+    pub primary_key: bool,
+}
+
+impl ColumnMetadata {
+    /// Creates the metadata of a column whose field type isn't known, which
+    /// is assumed to accept NULL values and any Postgres type.
+    pub fn untyped(name: &'static str) -> Self {
+        Self {
+            name,
+            rust_type: None,
+            nullable: true,
+            accepts: None,
+            pg_type: None,
+            primary_key: false,
+        }
+    }
+
+    /// Get the Postgres type of the column named by [`pg_type`], or `None`
+    /// if it isn't known or isn't a built-in type with a fixed OID, such as
+    /// the types of extensions.
+    ///
+    /// Names are matched like Postgres does, ignoring case and type
+    /// modifiers, so `VARCHAR(255)` is `Type::VARCHAR`, and
+    /// `timestamp(3) with time zone` is `Type::TIMESTAMPTZ`. A `[]` suffix
+    /// names the array type of the element type.
+    ///
+    /// [`pg_type`]: #structfield.pg_type
+    pub fn postgres_type(&self) -> Option<Type> {
+        self.pg_type.and_then(pg_type_named)
+    }
+}
+
+/// Get the built-in Postgres type of the given SQL name.
+fn pg_type_named(name: &str) -> Option<Type> {
+    // Drop the type modifiers, such as the length of `varchar(255)`.
+    let mut base = String::with_capacity(name.len());
+    let mut depth = 0;
+    for c in name.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth == 0 => base.push(c.to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+    let base = base.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    // Arrays of any number of dimensions have the same type.
+    let element = base.trim_end_matches("[]").trim_end();
+    let is_array = element.len() != base.len();
+
+    let (scalar, array) = match element {
+        "bool" | "boolean" => (Type::BOOL, Type::BOOL_ARRAY),
+        "\"char\"" => (Type::CHAR, Type::CHAR_ARRAY),
+        "char" | "character" | "bpchar" => (Type::BPCHAR, Type::BPCHAR_ARRAY),
+        "varchar" | "character varying" => (Type::VARCHAR, Type::VARCHAR_ARRAY),
+        "text" => (Type::TEXT, Type::TEXT_ARRAY),
+        "name" => (Type::NAME, Type::NAME_ARRAY),
+        "smallint" | "int2" | "smallserial" | "serial2" => {
+            (Type::INT2, Type::INT2_ARRAY)
+        }
+        "integer" | "int" | "int4" | "serial" | "serial4" => {
+            (Type::INT4, Type::INT4_ARRAY)
+        }
+        "bigint" | "int8" | "bigserial" | "serial8" => (Type::INT8, Type::INT8_ARRAY),
+        "oid" => (Type::OID, Type::OID_ARRAY),
+        "real" | "float4" => (Type::FLOAT4, Type::FLOAT4_ARRAY),
+        "double precision" | "float8" => (Type::FLOAT8, Type::FLOAT8_ARRAY),
+        "numeric" | "decimal" => (Type::NUMERIC, Type::NUMERIC_ARRAY),
+        "money" => (Type::MONEY, Type::MONEY_ARRAY),
+        "bytea" => (Type::BYTEA, Type::BYTEA_ARRAY),
+        "timestamptz" | "timestamp with time zone" => {
+            (Type::TIMESTAMPTZ, Type::TIMESTAMPTZ_ARRAY)
+        }
+        "timestamp" | "timestamp without time zone" => {
+            (Type::TIMESTAMP, Type::TIMESTAMP_ARRAY)
+        }
+        "date" => (Type::DATE, Type::DATE_ARRAY),
+        "time" | "time without time zone" => (Type::TIME, Type::TIME_ARRAY),
+        "timetz" | "time with time zone" => (Type::TIMETZ, Type::TIMETZ_ARRAY),
+        "interval" => (Type::INTERVAL, Type::INTERVAL_ARRAY),
+        "inet" => (Type::INET, Type::INET_ARRAY),
+        "cidr" => (Type::CIDR, Type::CIDR_ARRAY),
+        "macaddr" => (Type::MACADDR, Type::MACADDR_ARRAY),
+        "macaddr8" => (Type::MACADDR8, Type::MACADDR8_ARRAY),
+        "uuid" => (Type::UUID, Type::UUID_ARRAY),
+        "json" => (Type::JSON, Type::JSON_ARRAY),
+        "jsonb" => (Type::JSONB, Type::JSONB_ARRAY),
+        "xml" => (Type::XML, Type::XML_ARRAY),
+        "bit" => (Type::BIT, Type::BIT_ARRAY),
+        "varbit" | "bit varying" => (Type::VARBIT, Type::VARBIT_ARRAY),
+        "point" => (Type::POINT, Type::POINT_ARRAY),
+        "box" => (Type::BOX, Type::BOX_ARRAY),
+        "path" => (Type::PATH, Type::PATH_ARRAY),
+        _ => return None,
+    };
+
+    Some(if is_array { array } else { scalar })
+}
+
+/// A difference between a mapped type and the schema of its table, as
+/// reported by [`validate`].
+///
+/// [`validate`]: fn.validate.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SchemaIssue {
+    /// The table doesn't exist.
+    MissingTable {
+        /// The name of the table, as returned by `sql_table`.
+        table: String,
+    },
+    /// A mapped column doesn't exist in the table.
+    MissingColumn {
+        /// The name of the table.
+        table: String,
+        /// The name of the missing column.
+        column: &'static str,
+    },
+    /// A nullable column is mapped to a field which doesn't accept NULL
+    /// values, such as a non-`Option` field.
+    NullableColumn {
+        /// The name of the table.
+        table: String,
+        /// The name of the nullable column.
+        column: &'static str,
+    },
+    /// The Postgres type of a column isn't accepted by the Rust type of its
+    /// field.
+    TypeMismatch {
+        /// The name of the table.
+        table: String,
+        /// The name of the column.
+        column: &'static str,
+        /// The name of the Postgres type of the column.
+        pg_type: String,
+        /// The name of the Rust type of the field.
+        rust_type: &'static str,
+    },
+    /// The Postgres type of a column isn't the one given by the `pg_type`
+    /// hint of its field.
+    HintMismatch {
+        /// The name of the table.
+        table: String,
+        /// The name of the column.
+        column: &'static str,
+        /// The name of the Postgres type of the column.
+        pg_type: String,
+        /// The `pg_type` hint of the field.
+        hint: &'static str,
+    },
+}
+
+impl Display for SchemaIssue {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            SchemaIssue::MissingTable { table } => {
+                write!(f, "table {} doesn't exist", table)
+            }
+            SchemaIssue::MissingColumn { table, column } => {
+                write!(f, "column {}.{} doesn't exist", table, column)
+            }
+            SchemaIssue::NullableColumn { table, column } => write!(
+                f,
+                "column {}.{} is nullable but its field doesn't accept NULL",
+                table, column
+            ),
+            SchemaIssue::TypeMismatch {
+                table,
+                column,
+                pg_type,
+                rust_type,
+            } => write!(
+                f,
+                "column {}.{} of type {} can't be mapped to {}",
+                table, column, pg_type, rust_type
+            ),
+            SchemaIssue::HintMismatch {
+                table,
+                column,
+                pg_type,
+                hint,
+            } => write!(
+                f,
+                "column {}.{} of type {} doesn't match its pg_type hint {}",
+                table, column, pg_type, hint
+            ),
+        }
+    }
+}
+
+/// Compares the columns of a mapped type with those of its table, as named
+/// by [`sql_table`], returning every difference found.
+///
+/// A column is reported as [`SchemaIssue::TypeMismatch`] if its type is
+/// built into Postgres and isn't accepted by the `FromSql` implementation of
+/// the field type, which is the compatibility table of `tokio-postgres`:
+/// `int8` to `i64`, `text` or `varchar` to `String`, and so on. The types
+/// created with `CREATE TYPE` aren't checked.
+///
+/// The type of a column whose field has a `pg_type` hint, as returned by
+/// [`column_types`], is compared with the hint instead, reporting a
+/// [`SchemaIssue::HintMismatch`]. Hints which aren't the name of a type in
+/// the database are ignored.
+///
+/// # Errors
+///
+/// Returns [`Error::Db`] if the query of the table's columns failed.
+///
+/// [`sql_table`]: trait.FromTokioPostgresRow.html#tymethod.sql_table
+/// [`SchemaIssue::TypeMismatch`]: enum.SchemaIssue.html#variant.TypeMismatch
+/// [`column_types`]: trait.FromTokioPostgresRow.html#method.column_types
+/// [`SchemaIssue::HintMismatch`]: enum.SchemaIssue.html#variant.HintMismatch
+/// [`Error::Db`]: enum.Error.html#variant.Db
+#[cfg(feature = "client")]
+pub async fn validate<T: FromTokioPostgresRow>(
+    client: &impl GenericClient,
+) -> Result<Vec<SchemaIssue>, Error> {
+    let table = T::sql_table_trimmed();
+
+    // `to_regclass` resolves the table name like a query would, following
+    // the search path and quoting rules.
+    let rows = client
+        .query(
+            "SELECT column_name::text, is_nullable = 'YES', \
+                 format('%I.%I', udt_schema, udt_name)::regtype::oid, udt_name::text \
+             FROM information_schema.columns \
+             WHERE format('%I.%I', table_schema, table_name)::regclass \
+                 = to_regclass($1) \
+             ORDER BY ordinal_position",
+            &[&table],
+        )
+        .await?;
+
+    if rows.is_empty() {
+        return Ok(vec![SchemaIssue::MissingTable { table }]);
+    }
+
+    // Resolve the hints to the types they name, if any.
+    let hints = T::column_types()
+        .iter()
+        .filter_map(|&(column, hint)| Some((column, hint?)))
+        .collect::<Vec<_>>();
+    let hint_types = if hints.is_empty() {
+        Vec::new()
+    } else {
+        let names = hints.iter().map(|&(_, hint)| hint).collect::<Vec<_>>();
+
+        client
+            .query(
+                "SELECT hint, to_regtype(hint)::oid FROM unnest($1::text[]) AS hint",
+                &[&names],
+            )
+            .await?
+    };
+
+    let mut issues = Vec::new();
+
+    for metadata in T::column_metadata() {
+        let row = rows
+            .iter()
+            .find(|row| row.get::<_, &str>(0) == metadata.name);
+        let row = match row {
+            Some(row) => row,
+            None => {
+                issues.push(SchemaIssue::MissingColumn {
+                    table: table.clone(),
+                    column: metadata.name,
+                });
+                continue;
+            }
+        };
+
+        if row.get::<_, bool>(1) && !metadata.nullable {
+            issues.push(SchemaIssue::NullableColumn {
+                table: table.clone(),
+                column: metadata.name,
+            });
+        }
+
+        let hint = hints
+            .iter()
+            .find(|&&(column, _)| column == metadata.name)
+            .map(|&(_, hint)| hint);
+        if let Some(hint) = hint {
+            let hint_type = hint_types
+                .iter()
+                .find(|row| row.get::<_, &str>(0) == hint)
+                .and_then(|row| row.get::<_, Option<u32>>(1));
+            if matches!(hint_type, Some(oid) if oid != row.get::<_, u32>(2)) {
+                issues.push(SchemaIssue::HintMismatch {
+                    table: table.clone(),
+                    column: metadata.name,
+                    pg_type: row.get(3),
+                    hint,
+                });
+            }
+            continue;
+        }
+
+        let pg_type = Type::from_oid(row.get(2));
+        if let (Some(accepts), Some(pg_type)) = (metadata.accepts, pg_type) {
+            if !accepts(&pg_type) {
+                issues.push(SchemaIssue::TypeMismatch {
+                    table: table.clone(),
+                    column: metadata.name,
+                    pg_type: row.get(3),
+                    rust_type: metadata.rust_type.unwrap_or("?"),
+                });
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// A suggested fix of a [`SchemaIssue`], as part of a [`SchemaDiff`].
+///
+/// Suggestions are advisory: they cover the common cases and must be
+/// reviewed before being applied.
+///
+/// [`SchemaIssue`]: enum.SchemaIssue.html
+/// [`SchemaDiff`]: struct.SchemaDiff.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SchemaSuggestion {
+    /// A DDL statement changing the table to match the mapped type.
+    Ddl(String),
+    /// A change of the mapped type to match the table.
+    Rust(String),
+    /// A difference for which no change can be suggested, such as a column
+    /// of unknown or composite type.
+    Comment(String),
+}
+
+impl Display for SchemaSuggestion {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            SchemaSuggestion::Ddl(ddl) => write!(f, "{};", ddl),
+            SchemaSuggestion::Rust(change) => write!(f, "-- Rust: {}", change),
+            SchemaSuggestion::Comment(comment) => write!(f, "-- {}", comment),
+        }
+    }
+}
+
+/// The differences between a mapped type and the schema of its table, with
+/// suggested fixes, as returned by [`diff_schema`].
+///
+/// It is displayed as an SQL script, where every issue and every suggestion
+/// other than DDL statements is a comment:
+///
+/// ```text
+/// -- Advisory suggestions, review before applying.
+/// -- column users.email doesn't exist
+/// ALTER TABLE users ADD COLUMN email TEXT;
+/// -- column users.name is nullable but its field doesn't accept NULL
+/// ALTER TABLE users ALTER COLUMN name SET NOT NULL;
+/// -- Rust: wrap the field of column name in an Option
+/// ```
+///
+/// [`diff_schema`]: fn.diff_schema.html
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SchemaDiff {
+    entries: Vec<(SchemaIssue, Vec<SchemaSuggestion>)>,
+}
+
+impl SchemaDiff {
+    /// Get every issue found, with its suggested fixes.
+    pub fn entries(&self) -> &[(SchemaIssue, Vec<SchemaSuggestion>)] {
+        &self.entries
+    }
+
+    /// Get every issue found.
+    pub fn issues(&self) -> impl Iterator<Item = &SchemaIssue> {
+        self.entries.iter().map(|(issue, _)| issue)
+    }
+
+    /// Get every suggested DDL statement.
+    pub fn ddl(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .flat_map(|(_, suggestions)| suggestions)
+            .filter_map(|suggestion| match suggestion {
+                SchemaSuggestion::Ddl(ddl) => Some(ddl.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Returns whether the mapped type matches the schema of its table.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Display for SchemaDiff {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        if self.is_empty() {
+            return f.write_str("-- No differences found.");
+        }
+
+        f.write_str("-- Advisory suggestions, review before applying.")?;
+
+        for (issue, suggestions) in &self.entries {
+            write!(f, "\n-- {}", issue)?;
+
+            for suggestion in suggestions {
+                write!(f, "\n{}", suggestion)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares the columns of a mapped type with those of its table like
+/// [`validate`], suggesting a fix for every difference found.
+///
+/// The DDL suggested for a missing table is [`create_table_stmt`], and for a
+/// missing column an `ALTER TABLE ... ADD COLUMN`, unless the Postgres type
+/// of one of the columns is unknown. Nullable columns of non-`Option` fields
+/// get both a `SET NOT NULL` and the suggestion of an `Option`, and type
+/// mismatches only a change of the field type.
+///
+/// # Errors
+///
+/// Returns [`Error::Db`] if the query of the table's columns failed.
+///
+/// [`validate`]: fn.validate.html
+/// [`create_table_stmt`]: trait.FromTokioPostgresRow.html#method.create_table_stmt
+/// [`Error::Db`]: enum.Error.html#variant.Db
+#[cfg(feature = "client")]
+pub async fn diff_schema<T: FromTokioPostgresRow>(
+    client: &impl GenericClient,
+) -> Result<SchemaDiff, Error> {
+    let issues = validate::<T>(client).await?;
+    let columns = T::column_metadata();
+    let pg_type = |name: &str| {
+        columns
+            .iter()
+            .find(|column| column.name == name)
+            .and_then(|column| column.pg_type)
+    };
+
+    let entries = issues
+        .into_iter()
+        .map(|issue| {
+            let suggestions = match issue {
+                SchemaIssue::MissingTable { ref table } => {
+                    if columns.iter().all(|column| column.pg_type.is_some()) {
+                        vec![SchemaSuggestion::Ddl(T::create_table_stmt())]
+                    } else {
+                        vec![SchemaSuggestion::Comment(format!(
+                            "the Postgres types of some columns of {} are unknown",
+                            table
+                        ))]
+                    }
+                }
+                SchemaIssue::MissingColumn { ref table, column } => {
+                    match pg_type(column) {
+                        Some(pg_type) => vec![SchemaSuggestion::Ddl(format!(
+                            "ALTER TABLE {} ADD COLUMN {} {}",
+                            table,
+                            quote_identifier(column),
+                            pg_type
+                        ))],
+                        None => vec![SchemaSuggestion::Comment(format!(
+                            "the Postgres type of column {} is unknown",
+                            column
+                        ))],
+                    }
+                }
+                SchemaIssue::NullableColumn { ref table, column } => vec![
+                    SchemaSuggestion::Ddl(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} SET NOT NULL",
+                        table,
+                        quote_identifier(column)
+                    )),
+                    SchemaSuggestion::Rust(format!(
+                        "wrap the field of column {} in an Option",
+                        column
+                    )),
+                ],
+                SchemaIssue::TypeMismatch {
+                    column,
+                    ref pg_type,
+                    ..
+                } => vec![SchemaSuggestion::Rust(format!(
+                    "change the field of column {} to a type accepting {}",
+                    column, pg_type
+                ))],
+                SchemaIssue::HintMismatch {
+                    ref table,
+                    column,
+                    hint,
+                    ..
+                } => vec![SchemaSuggestion::Ddl(format!(
+                    "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
+                    table,
+                    quote_identifier(column),
+                    hint
+                ))],
+            };
+
+            (issue, suggestions)
+        })
+        .collect();
+
+    Ok(SchemaDiff { entries })
+}
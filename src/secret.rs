@@ -0,0 +1,108 @@
+//! The `Secret` field type.
+
+use bytes::BytesMut;
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+/// Bound on the values of a [`Secret`], which must implement `Zeroize` when
+/// the `zeroize` feature is enabled.
+///
+/// [`Secret`]: struct.Secret.html
+#[cfg(not(feature = "zeroize"))]
+pub trait SecretValue {}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T: ?Sized> SecretValue for T {}
+
+/// Bound on the values of a [`Secret`], which must implement `Zeroize` when
+/// the `zeroize` feature is enabled.
+///
+/// [`Secret`]: struct.Secret.html
+#[cfg(feature = "zeroize")]
+pub trait SecretValue: zeroize::Zeroize {}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize + ?Sized> SecretValue for T {}
+
+/// Wrapper for values such as password hashes or tokens which mustn't end up
+/// in logs, mapped like the wrapped value.
+///
+/// `Debug` and `Display` print `[REDACTED]`, so the value can only be read
+/// through [`expose_secret`]. With the `zeroize` feature, the value is zeroed
+/// when dropped.
+///
+/// [`expose_secret`]: #method.expose_secret
+#[derive(Clone, Default)]
+pub struct Secret<T: SecretValue>(T);
+
+impl<T: SecretValue> Secret<T> {
+    /// Wraps a value.
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// Get the wrapped value.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: SecretValue> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: SecretValue> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<T: SecretValue> Display for Secret<T> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: SecretValue> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<'a, T: FromSql<'a> + SecretValue> FromSql<'a> for Secret<T> {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        T::from_sql(ty, raw).map(Secret)
+    }
+
+    fn from_sql_null(ty: &Type) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        T::from_sql_null(ty).map(Secret)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        T::accepts(ty)
+    }
+}
+
+impl<T: ToSql + SecretValue> ToSql for Secret<T> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        self.0.to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        T::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
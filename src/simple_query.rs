@@ -0,0 +1,263 @@
+//! Mapping of the text rows returned by `simple_query`.
+//!
+//! The simple query protocol returns every value as text, without type
+//! information, so fields are parsed through [`FromSqlText`] rather than
+//! `FromSql`.
+//!
+//! [`FromSqlText`]: trait.FromSqlText.html
+
+use crate::{capture_backtrace, Error};
+
+use tokio_postgres::types::WasNull;
+use tokio_postgres::{SimpleQueryMessage, SimpleQueryRow};
+
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Trait for converting from a `tokio-postgres` `SimpleQueryRow`, as returned
+/// by `simple_query`, into a mapped type.
+///
+/// When using the `pg_mapper_derive` crate's `TokioPostgresMapper` proc-macro
+/// with the `#[pg_mapper(simple_query)]` container attribute, this will be
+/// implemented on types. Every field must then implement [`FromSqlText`].
+///
+/// [`FromSqlText`]: trait.FromSqlText.html
+pub trait FromSimpleQueryRow: Sized {
+    /// Converts from a `tokio-postgres` `SimpleQueryRow` into a mapped type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnNotFound`] if the column in a mapping was not
+    /// found.
+    ///
+    /// Returns [`Error::UnexpectedNull`] if a column mapped to a non-`Option`
+    /// field was NULL.
+    ///
+    /// Returns [`Error::ColumnConversion`] if the text of a column couldn't be
+    /// parsed into the requested type, with an [`InvalidText`] source holding
+    /// the offending text.
+    ///
+    /// [`Error::ColumnNotFound`]: ../enum.Error.html#variant.ColumnNotFound
+    /// [`Error::UnexpectedNull`]: ../enum.Error.html#variant.UnexpectedNull
+    /// [`Error::ColumnConversion`]: ../enum.Error.html#variant.ColumnConversion
+    /// [`InvalidText`]: struct.InvalidText.html
+    fn from_simple_query_row(row: &SimpleQueryRow) -> Result<Self, Error>;
+
+    /// Converts every row of the messages returned by `simple_query` into
+    /// mapped types, skipping other messages such as command completions.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by [`from_simple_query_row`].
+    ///
+    /// [`from_simple_query_row`]: #tymethod.from_simple_query_row
+    fn from_simple_query_messages(
+        messages: &[SimpleQueryMessage],
+    ) -> Result<Vec<Self>, Error> {
+        messages
+            .iter()
+            .filter_map(|message| match message {
+                SimpleQueryMessage::Row(row) => Some(Self::from_simple_query_row(row)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Trait for parsing the text representation of a Postgres value.
+///
+/// It is implemented for:
+///
+/// - `bool`, from `t` or `f`
+/// - `i16`, `i32`, `i64`, `u32`, `f32` and `f64`, including the `NaN` and
+///   `Infinity` floats
+/// - `String`
+/// - `Option<T>`, mapping NULL to `None`
+/// - with the `chrono` feature, `NaiveDate` from `2024-01-31`,
+///   `NaiveDateTime` from `2024-01-31 12:34:56.789` and `DateTime<Utc>` or
+///   `DateTime<FixedOffset>` from `2024-01-31 12:34:56.789+00`, as output
+///   with the default `DateStyle` of `ISO, MDY`
+pub trait FromSqlText: Sized {
+    /// Parses a non-NULL value.
+    fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>>;
+
+    /// Creates a value from a NULL, which fails with `WasNull` unless
+    /// overridden.
+    fn from_sql_text_null() -> Result<Self, Box<dyn StdError + Send + Sync>> {
+        Err(Box::new(WasNull))
+    }
+}
+
+macro_rules! impl_from_sql_text_for_from_str {
+    ($($ty:ty),*) => {
+        $(
+            impl FromSqlText for $ty {
+                fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+                    Ok(text.parse()?)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_sql_text_for_from_str!(i16, i32, i64, u32, f32, f64, String);
+
+impl FromSqlText for bool {
+    fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+        match text {
+            "t" => Ok(true),
+            "f" => Ok(false),
+            _ => Err("expected `t` or `f`".into()),
+        }
+    }
+}
+
+impl<T: FromSqlText> FromSqlText for Option<T> {
+    fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+        T::from_sql_text(text).map(Some)
+    }
+
+    fn from_sql_text_null() -> Result<Self, Box<dyn StdError + Send + Sync>> {
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use super::FromSqlText;
+
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+
+    use std::error::Error as StdError;
+
+    impl FromSqlText for NaiveDate {
+        fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+            Ok(NaiveDate::parse_from_str(text, "%Y-%m-%d")?)
+        }
+    }
+
+    impl FromSqlText for NaiveDateTime {
+        fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+            Ok(NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")?)
+        }
+    }
+
+    impl FromSqlText for DateTime<FixedOffset> {
+        fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+            Ok(DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f%#z")?)
+        }
+    }
+
+    impl FromSqlText for DateTime<Utc> {
+        fn from_sql_text(text: &str) -> Result<Self, Box<dyn StdError + Send + Sync>> {
+            let value = DateTime::<FixedOffset>::from_sql_text(text)?;
+
+            Ok(value.with_timezone(&Utc))
+        }
+    }
+}
+
+/// The source of an [`Error::ColumnConversion`] for a text value which
+/// couldn't be parsed, holding the offending text.
+///
+/// [`Error::ColumnConversion`]: ../enum.Error.html#variant.ColumnConversion
+#[derive(Debug)]
+pub struct InvalidText {
+    text: String,
+    source: Box<dyn StdError + Send + Sync>,
+}
+
+impl InvalidText {
+    /// Get the text which couldn't be parsed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for InvalidText {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "invalid text value \"{}\"", self.text)
+    }
+}
+
+impl StdError for InvalidText {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Parses the value of a named text column on behalf of derive-generated
+/// code, reporting errors like [`try_get_column`].
+///
+/// [`try_get_column`]: ../fn.try_get_column.html
+#[doc(hidden)]
+pub fn try_get_text_column<T: FromSqlText>(
+    row: &SimpleQueryRow,
+    column: &str,
+    field: &'static str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    get_text_column(row, column, field, type_name, false)
+}
+
+/// Parses the value of a named text column like [`try_get_text_column`], but
+/// parses an empty or whitespace-only text as NULL, on behalf of
+/// derive-generated code for `#[pg_mapper(empty_as_none)]` fields.
+///
+/// [`try_get_text_column`]: fn.try_get_text_column.html
+#[doc(hidden)]
+pub fn try_get_text_column_empty_as_null<T: FromSqlText>(
+    row: &SimpleQueryRow,
+    column: &str,
+    field: &'static str,
+    type_name: &'static str,
+) -> Result<T, Error> {
+    get_text_column(row, column, field, type_name, true)
+}
+
+fn get_text_column<T: FromSqlText>(
+    row: &SimpleQueryRow,
+    column: &str,
+    field: &'static str,
+    type_name: &'static str,
+    empty_as_null: bool,
+) -> Result<T, Error> {
+    let idx = match row.columns().iter().position(|c| c.name() == column) {
+        Some(idx) => idx,
+        None => {
+            return Err(Error::ColumnNotFound {
+                column: column.to_string(),
+                type_name: Some(type_name),
+                backtrace: capture_backtrace(),
+            })
+        }
+    };
+
+    let text = row
+        .get(idx)
+        .filter(|text| !(empty_as_null && text.trim().is_empty()));
+    let value = match text {
+        Some(text) => T::from_sql_text(text),
+        None => T::from_sql_text_null(),
+    };
+
+    value.map_err(|err| match text {
+        None => Error::UnexpectedNull {
+            column: column.to_string(),
+            field,
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        },
+        Some(text) => Error::ColumnConversion {
+            column: column.to_string(),
+            rust_type: std::any::type_name::<T>(),
+            pg_type: None,
+            source: Box::new(InvalidText {
+                text: text.to_string(),
+                source: err,
+            }),
+            type_name: Some(type_name),
+            backtrace: capture_backtrace(),
+        },
+    })
+}
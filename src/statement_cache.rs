@@ -0,0 +1,240 @@
+//! Caching of the prepared statements of mapped types, enabled with the
+//! `client` feature.
+//!
+//! A [`StatementCache`] owns a client, such as a `Client` or a pooled
+//! connection, and prepares each statement derived for a type the first time
+//! it's run on it, reusing the prepared `Statement` afterwards:
+//!
+//! ```
+//!     use tokio_pg_mapper::{Error, StatementCache, StatementKind};
+//!
+//!     let cached = StatementCache::new(client);
+//!
+//!     let mut user: User = cached.find(&1).await?.ok_or(Error::NoRows)?;
+//!     user.email = None;
+//!     let updated: bool = cached.update(&user).await?;
+//!
+//!     let statement = cached.prepare_for::<User>(StatementKind::SelectAll).await?;
+//! ```
+//!
+//! Prepared statements belong to the connection which prepared them, so a
+//! cache must live as long as its connection, such as in the connection
+//! state of a pool, rather than being shared between connections. Nothing
+//! is evicted: there are as many statements as mapped types and kinds.
+//!
+//! [`StatementCache`]: struct.StatementCache.html
+
+use crate::{ClientExt, Error, TokioPostgresCrud};
+
+use tokio_postgres::{GenericClient, Statement};
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// The statements derived for a mapped type which a [`StatementCache`]
+/// prepares.
+///
+/// [`StatementCache`]: struct.StatementCache.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StatementKind {
+    /// [`TokioPostgresPrimaryKey::select_by_primary_key_stmt`].
+    ///
+    /// [`TokioPostgresPrimaryKey::select_by_primary_key_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.select_by_primary_key_stmt
+    SelectByPrimaryKey,
+    /// [`TokioPostgresPrimaryKey::select_all_stmt`].
+    ///
+    /// [`TokioPostgresPrimaryKey::select_all_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.select_all_stmt
+    SelectAll,
+    /// [`TokioPostgresInsert::insert_returning_stmt`].
+    ///
+    /// [`TokioPostgresInsert::insert_returning_stmt`]: ../trait.TokioPostgresInsert.html#method.insert_returning_stmt
+    InsertReturning,
+    /// [`TokioPostgresCrud::update_stmt`].
+    ///
+    /// [`TokioPostgresCrud::update_stmt`]: ../crud/trait.TokioPostgresCrud.html#method.update_stmt
+    Update,
+    /// [`TokioPostgresPrimaryKey::delete_by_primary_key_stmt`].
+    ///
+    /// [`TokioPostgresPrimaryKey::delete_by_primary_key_stmt`]: ../trait.TokioPostgresPrimaryKey.html#method.delete_by_primary_key_stmt
+    DeleteByPrimaryKey,
+}
+
+impl StatementKind {
+    /// Get the SQL of the statement for the given type.
+    pub fn sql<T: TokioPostgresCrud>(self) -> String {
+        match self {
+            StatementKind::SelectByPrimaryKey => T::select_by_primary_key_stmt(),
+            StatementKind::SelectAll => T::select_all_stmt(),
+            StatementKind::InsertReturning => T::insert_returning_stmt(),
+            StatementKind::Update => T::update_stmt(),
+            StatementKind::DeleteByPrimaryKey => T::delete_by_primary_key_stmt(),
+        }
+    }
+}
+
+/// A client preparing the statements derived for mapped types once, keyed by
+/// type and [`StatementKind`].
+///
+/// [`StatementKind`]: enum.StatementKind.html
+#[derive(Debug)]
+pub struct StatementCache<C> {
+    client: C,
+    statements: Mutex<HashMap<(TypeId, StatementKind), Statement>>,
+}
+
+impl<C> StatementCache<C>
+where
+    C: GenericClient + Sync,
+{
+    /// Creates an empty cache of the statements prepared on the given
+    /// client.
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            statements: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the client the statements are prepared and run on.
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Unwraps the client, dropping the prepared statements.
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+
+    /// Get the number of prepared statements.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Get whether no statement has been prepared yet.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Get the statement of the given kind for `T`, preparing it if it's
+    /// the first time.
+    ///
+    /// Concurrent first calls may prepare the statement more than once, and
+    /// keep any one of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the statement couldn't be prepared.
+    ///
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    pub async fn prepare_for<T>(&self, kind: StatementKind) -> Result<Statement, Error>
+    where
+        T: TokioPostgresCrud + 'static,
+    {
+        let key = (TypeId::of::<T>(), kind);
+
+        let cached = self.lock().get(&key).cloned();
+        if let Some(statement) = cached {
+            return Ok(statement);
+        }
+
+        let statement = self.client.prepare(&kind.sql::<T>()).await?;
+        self.lock().insert(key, statement.clone());
+
+        Ok(statement)
+    }
+
+    /// Fetches the row with the given primary key.
+    ///
+    /// See [`TokioPostgresCrud::find`].
+    ///
+    /// [`TokioPostgresCrud::find`]: ../crud/trait.TokioPostgresCrud.html#method.find
+    pub async fn find<T>(&self, key: &T::PrimaryKey) -> Result<Option<T>, Error>
+    where
+        T: TokioPostgresCrud + 'static,
+    {
+        let statement = self
+            .prepare_for::<T>(StatementKind::SelectByPrimaryKey)
+            .await?;
+        let params = T::primary_key_params(key);
+
+        self.client.query_opt_as(&statement, &params).await
+    }
+
+    /// Fetches every row, ordered by primary key.
+    ///
+    /// See [`TokioPostgresCrud::list_all`].
+    ///
+    /// [`TokioPostgresCrud::list_all`]: ../crud/trait.TokioPostgresCrud.html#method.list_all
+    pub async fn list_all<T>(&self) -> Result<Vec<T>, Error>
+    where
+        T: TokioPostgresCrud + 'static,
+    {
+        let statement = self.prepare_for::<T>(StatementKind::SelectAll).await?;
+
+        self.client.query_as(&statement, &[]).await
+    }
+
+    /// Inserts a row, and maps the stored row.
+    ///
+    /// See [`TokioPostgresCrud::insert`].
+    ///
+    /// [`TokioPostgresCrud::insert`]: ../crud/trait.TokioPostgresCrud.html#method.insert
+    pub async fn insert<T>(&self, value: &T) -> Result<T, Error>
+    where
+        T: TokioPostgresCrud + Sync + 'static,
+    {
+        let statement = self
+            .prepare_for::<T>(StatementKind::InsertReturning)
+            .await?;
+
+        self.client
+            .query_one_as(&statement, &value.to_params())
+            .await
+    }
+
+    /// Updates the row with the primary key of the given value, returning
+    /// whether it existed.
+    ///
+    /// See [`TokioPostgresCrud::update`].
+    ///
+    /// [`TokioPostgresCrud::update`]: ../crud/trait.TokioPostgresCrud.html#method.update
+    pub async fn update<T>(&self, value: &T) -> Result<bool, Error>
+    where
+        T: TokioPostgresCrud + Sync + 'static,
+    {
+        let statement = self.prepare_for::<T>(StatementKind::Update).await?;
+
+        Ok(self
+            .client
+            .execute(&statement, &value.update_params())
+            .await?
+            > 0)
+    }
+
+    /// Deletes the row with the given primary key, returning whether it
+    /// existed.
+    ///
+    /// See [`TokioPostgresCrud::delete`].
+    ///
+    /// [`TokioPostgresCrud::delete`]: ../crud/trait.TokioPostgresCrud.html#method.delete
+    pub async fn delete<T>(&self, key: &T::PrimaryKey) -> Result<bool, Error>
+    where
+        T: TokioPostgresCrud + 'static,
+    {
+        let statement = self
+            .prepare_for::<T>(StatementKind::DeleteByPrimaryKey)
+            .await?;
+        let params = T::primary_key_params(key);
+
+        Ok(self.client.execute(&statement, &params).await? > 0)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<(TypeId, StatementKind), Statement>> {
+        // The map is never left half-updated, so a panic while it was locked
+        // doesn't invalidate it.
+        self.statements
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,164 @@
+//! Blocking `postgres` crate integration, enabled with the `sync` feature.
+//!
+//! `postgres::Row` is the same type as `tokio_postgres::Row`, so types
+//! deriving `PostgresMapper` map rows of either crate. [`ClientExt`] adds the
+//! blocking counterparts of [`client::ClientExt`] to every
+//! `postgres::GenericClient`:
+//!
+//! ```
+//!     use tokio_pg_mapper::sync::ClientExt;
+//!
+//!     let users: Vec<User> = client
+//!         .query_as("SELECT id, email FROM users WHERE active = $1", &[&true])?;
+//! ```
+//!
+//! Note that the `postgres` crate runs a `tokio` runtime internally, so the
+//! feature does depend on `tokio`, without requiring an async runtime from
+//! its users.
+//!
+//! [`ClientExt`]: trait.ClientExt.html
+//! [`client::ClientExt`]: ../client/trait.ClientExt.html
+
+use crate::{Error, FromTokioPostgresRow};
+
+use fallible_iterator::FallibleIterator;
+use postgres::types::ToSql;
+use postgres::{GenericClient, ToStatement};
+
+/// Extension trait running blocking queries and mapping the returned rows.
+pub trait ClientExt {
+    /// Runs a query, either a `&str` or a prepared `&Statement`, and maps
+    /// every returned row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the first error
+    /// encountered by [`FromTokioPostgresRow::from_rows`].
+    ///
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_rows`]: ../trait.FromTokioPostgresRow.html#method.from_rows
+    fn query_as<T, S>(
+        &mut self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement;
+
+    /// Runs a query expected to return exactly one row, and maps it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoRows`] if the query returned no rows, and
+    /// [`Error::TooManyRows`] if it returned more than one.
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the error encountered
+    /// by [`FromTokioPostgresRow::from_row`].
+    ///
+    /// [`Error::NoRows`]: ../enum.Error.html#variant.NoRows
+    /// [`Error::TooManyRows`]: ../enum.Error.html#variant.TooManyRows
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_row`]: ../trait.FromTokioPostgresRow.html#tymethod.from_row
+    fn query_one_as<T, S>(
+        &mut self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<T, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement,
+    {
+        self.query_opt_as(statement, params)?.ok_or(Error::NoRows)
+    }
+
+    /// Runs a query expected to return zero or one row, and maps it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TooManyRows`] if the query returned more than one
+    /// row.
+    ///
+    /// Returns [`Error::Db`] if the query failed, or the error encountered
+    /// by [`FromTokioPostgresRow::from_row`].
+    ///
+    /// [`Error::TooManyRows`]: ../enum.Error.html#variant.TooManyRows
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    /// [`FromTokioPostgresRow::from_row`]: ../trait.FromTokioPostgresRow.html#tymethod.from_row
+    fn query_opt_as<T, S>(
+        &mut self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement;
+
+    /// Runs a statement, returning the number of rows modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Db`] if the statement failed.
+    ///
+    /// [`Error::Db`]: ../enum.Error.html#variant.Db
+    fn exec<S>(
+        &mut self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error>
+    where
+        S: ?Sized + ToStatement;
+}
+
+impl<C: GenericClient> ClientExt for C {
+    fn query_as<T, S>(
+        &mut self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement,
+    {
+        let rows = self.query(statement, params)?;
+
+        T::from_rows(rows)
+    }
+
+    fn query_opt_as<T, S>(
+        &mut self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<T>, Error>
+    where
+        T: FromTokioPostgresRow,
+        S: ?Sized + ToStatement,
+    {
+        // Like `query_opt`, stop reading at the second row instead of
+        // buffering the whole result.
+        let params = params.iter().map(|param| *param as &dyn ToSql);
+        let mut rows = self.query_raw(statement, params)?;
+
+        let row = match rows.next()? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        if rows.next()?.is_some() {
+            return Err(Error::TooManyRows);
+        }
+
+        T::from_row(row).map(Some)
+    }
+
+    fn exec<S>(
+        &mut self,
+        statement: &S,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error>
+    where
+        S: ?Sized + ToStatement,
+    {
+        Ok(self.execute(statement, params)?)
+    }
+}
@@ -0,0 +1,227 @@
+//! Construction of in-memory rows for tests, enabled with the `test-util`
+//! feature.
+//!
+//! `tokio_postgres::Row` can't be constructed outside of `tokio-postgres`, so
+//! [`MockRowBuilder`] builds a genuine `Row` by answering a query of a real
+//! `Client` over an in-memory stream, with the given columns encoded through
+//! the binary protocol. Mappings can then be tested without a database:
+//!
+//! ```
+//!     use tokio_pg_mapper::test_util::MockRowBuilder;
+//!     use tokio_postgres::types::Type;
+//!
+//!     let row = MockRowBuilder::new()
+//!         .column("id", Type::INT8, 1i64)
+//!         .column("email", Type::TEXT, None::<String>)
+//!         .build();
+//!
+//!     let user = User::from_row(row)?;
+//! ```
+//!
+//! Only the types built into Postgres, those with a constant on
+//! `tokio_postgres::types::Type` such as `Type::INT8` or `Type::TEXT_ARRAY`,
+//! are supported. Enums, composites and other types created with
+//! `CREATE TYPE` would have to be looked up in a real database.
+//!
+//! [`MockRowBuilder`]: struct.MockRowBuilder.html
+
+use bytes::BytesMut;
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio_postgres::config::SslMode;
+use tokio_postgres::types::{IsNull, ToSql, Type};
+use tokio_postgres::{Config, NoTls, Row};
+
+use std::io;
+
+struct MockColumn {
+    name: String,
+    type_: Type,
+    value: Option<Vec<u8>>,
+}
+
+/// Builder of a `tokio_postgres::Row` holding the given columns.
+#[derive(Default)]
+pub struct MockRowBuilder {
+    columns: Vec<MockColumn>,
+}
+
+impl MockRowBuilder {
+    /// Creates a builder of a row without columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column with the given name, Postgres type and value, which is
+    /// NULL if `value.to_sql` reports it as such, as for `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type isn't built into Postgres, or if the value can't
+    /// be encoded as the given type.
+    pub fn column<T: ToSql>(mut self, name: &str, type_: Type, value: T) -> Self {
+        if Type::from_oid(type_.oid()).is_none() {
+            panic!(
+                "mock column \"{}\" has type {}, which isn't built into Postgres",
+                name, type_
+            );
+        }
+
+        let mut buf = BytesMut::new();
+        let value = match value.to_sql_checked(&type_, &mut buf) {
+            Ok(IsNull::No) => Some(buf.to_vec()),
+            Ok(IsNull::Yes) => None,
+            Err(err) => panic!(
+                "mock column \"{}\" can't be encoded as {}: {}",
+                name, type_, err
+            ),
+        };
+
+        self.columns.push(MockColumn {
+            name: name.to_string(),
+            type_,
+            value,
+        });
+
+        self
+    }
+
+    /// Builds the row.
+    ///
+    /// This runs a `Client` on its own thread and runtime, so it can be
+    /// called from both synchronous and asynchronous tests.
+    pub fn build(self) -> Row {
+        self.build_many(1)
+            .pop()
+            .expect("the mock server returned no row")
+    }
+
+    /// Builds the given number of identical rows, such as for benchmarks,
+    /// at the cost of building a single one.
+    pub fn build_many(self, count: usize) -> Vec<Row> {
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("failed to start the mock row runtime");
+
+            runtime.block_on(self.fetch(count))
+        })
+        .join()
+        .expect("failed to build the mock rows")
+    }
+
+    async fn fetch(self, count: usize) -> Vec<Row> {
+        let (client_stream, server_stream) = duplex(64 * 1024);
+        tokio::spawn(serve(server_stream, self.columns, count));
+
+        let (client, connection) = Config::new()
+            .user("mock")
+            .ssl_mode(SslMode::Disable)
+            .connect_raw(client_stream, NoTls)
+            .await
+            .expect("failed to connect to the mock server");
+        tokio::spawn(connection);
+
+        client
+            .query("SELECT", &[])
+            .await
+            .expect("failed to query the mock server")
+    }
+}
+
+/// Answers the messages of a `Client` as a server returning the same row the
+/// given number of times.
+async fn serve(
+    mut stream: DuplexStream,
+    columns: Vec<MockColumn>,
+    count: usize,
+) -> io::Result<()> {
+    // The startup message is the only one without a tag.
+    let len = stream.read_i32().await?;
+    let mut startup = vec![0; len as usize - 4];
+    stream.read_exact(&mut startup).await?;
+
+    let mut out = Vec::new();
+    write_message(&mut out, b'R', &0i32.to_be_bytes());
+    write_message(&mut out, b'Z', b"I");
+    stream.write_all(&out).await?;
+
+    loop {
+        let tag = match stream.read_u8().await {
+            Ok(tag) => tag,
+            Err(_) => return Ok(()),
+        };
+        let len = stream.read_i32().await?;
+        let mut body = vec![0; len as usize - 4];
+        stream.read_exact(&mut body).await?;
+
+        let mut out = Vec::new();
+        match tag {
+            // Parse
+            b'P' => write_message(&mut out, b'1', &[]),
+            // Describe
+            b'D' => {
+                write_message(&mut out, b't', &0i16.to_be_bytes());
+                write_message(&mut out, b'T', &row_description(&columns));
+            }
+            // Bind
+            b'B' => write_message(&mut out, b'2', &[]),
+            // Execute
+            b'E' => {
+                let row = data_row(&columns);
+                for _ in 0..count {
+                    write_message(&mut out, b'D', &row);
+                }
+                write_message(&mut out, b'C', format!("SELECT {}\0", count).as_bytes());
+            }
+            // Close
+            b'C' => write_message(&mut out, b'3', &[]),
+            // Sync
+            b'S' => write_message(&mut out, b'Z', b"I"),
+            // Terminate
+            b'X' => return Ok(()),
+            _ => {}
+        }
+        stream.write_all(&out).await?;
+    }
+}
+
+fn write_message(out: &mut Vec<u8>, tag: u8, body: &[u8]) {
+    out.push(tag);
+    out.extend_from_slice(&(body.len() as i32 + 4).to_be_bytes());
+    out.extend_from_slice(body);
+}
+
+fn row_description(columns: &[MockColumn]) -> Vec<u8> {
+    let mut body = (columns.len() as i16).to_be_bytes().to_vec();
+
+    for column in columns {
+        body.extend_from_slice(column.name.as_bytes());
+        body.push(0);
+        // Table OID and column number.
+        body.extend_from_slice(&0i32.to_be_bytes());
+        body.extend_from_slice(&0i16.to_be_bytes());
+        body.extend_from_slice(&column.type_.oid().to_be_bytes());
+        // Type size, type modifier and format code.
+        body.extend_from_slice(&(-1i16).to_be_bytes());
+        body.extend_from_slice(&(-1i32).to_be_bytes());
+        body.extend_from_slice(&1i16.to_be_bytes());
+    }
+
+    body
+}
+
+fn data_row(columns: &[MockColumn]) -> Vec<u8> {
+    let mut body = (columns.len() as i16).to_be_bytes().to_vec();
+
+    for column in columns {
+        match column.value {
+            Some(ref value) => {
+                body.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                body.extend_from_slice(value);
+            }
+            None => body.extend_from_slice(&(-1i32).to_be_bytes()),
+        }
+    }
+
+    body
+}
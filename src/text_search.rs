@@ -0,0 +1,312 @@
+//! The `PgTsVector` and `PgTsQuery` field types.
+
+use bytes::Buf;
+use tokio_postgres::types::{FromSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+
+/// The weight of a lexeme position in a `tsvector`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TsWeight {
+    /// The highest weight.
+    A,
+    /// The second highest weight.
+    B,
+    /// The third highest weight.
+    C,
+    /// The lowest and default weight.
+    D,
+}
+
+impl TsWeight {
+    fn from_bits(bits: u16) -> Self {
+        match bits {
+            3 => TsWeight::A,
+            2 => TsWeight::B,
+            1 => TsWeight::C,
+            _ => TsWeight::D,
+        }
+    }
+}
+
+/// A lexeme of a `tsvector` with its positions in the document.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TsLexeme {
+    /// The normalized word.
+    pub word: String,
+    /// The positions of the word, from 1, with their weights. Empty if the
+    /// `tsvector` was stripped of positions.
+    pub positions: Vec<(u16, TsWeight)>,
+}
+
+/// Read-only mapping of `tsvector` columns to their lexemes, for inspecting
+/// search documents.
+///
+/// `Display` formats the vector like Postgres, such as `'fat':2A 'rat':3`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PgTsVector {
+    /// The lexemes, sorted as stored by Postgres.
+    pub lexemes: Vec<TsLexeme>,
+}
+
+fn read_cstr<'a>(
+    raw: &mut &'a [u8],
+) -> Result<&'a str, Box<dyn StdError + Sync + Send>> {
+    let end = raw
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or("unterminated text search string")?;
+    let text = std::str::from_utf8(&raw[..end])?;
+    *raw = &raw[end + 1..];
+
+    Ok(text)
+}
+
+fn check_len(raw: &[u8], len: usize) -> Result<(), Box<dyn StdError + Sync + Send>> {
+    if raw.len() < len {
+        return Err("invalid text search value length".into());
+    }
+
+    Ok(())
+}
+
+/// Quotes a lexeme, doubling quotes and backslashes.
+fn push_quoted(out: &mut String, word: &str) {
+    out.push('\'');
+    for c in word.chars() {
+        if c == '\'' || c == '\\' {
+            out.push(c);
+        }
+        out.push(c);
+    }
+    out.push('\'');
+}
+
+impl<'a> FromSql<'a> for PgTsVector {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        check_len(raw, 4)?;
+        let count =
+            usize::try_from(raw.get_i32()).map_err(|_| "invalid tsvector length")?;
+
+        let mut lexemes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let word = read_cstr(&mut raw)?.to_string();
+
+            check_len(raw, 2)?;
+            let count = raw.get_u16() as usize;
+            check_len(raw, count * 2)?;
+
+            let positions = (0..count)
+                .map(|_| {
+                    let pos = raw.get_u16();
+                    (pos & 0x3fff, TsWeight::from_bits(pos >> 14))
+                })
+                .collect();
+
+            lexemes.push(TsLexeme { word, positions });
+        }
+
+        if !raw.is_empty() {
+            return Err("invalid tsvector length".into());
+        }
+
+        Ok(PgTsVector { lexemes })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "tsvector"
+    }
+}
+
+impl Display for PgTsVector {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        for (idx, lexeme) in self.lexemes.iter().enumerate() {
+            if idx > 0 {
+                f.write_char(' ')?;
+            }
+            let mut word = String::new();
+            push_quoted(&mut word, &lexeme.word);
+            f.write_str(&word)?;
+
+            for (idx, &(pos, weight)) in lexeme.positions.iter().enumerate() {
+                f.write_char(if idx == 0 { ':' } else { ',' })?;
+                write!(f, "{}", pos)?;
+
+                if weight != TsWeight::D {
+                    write!(f, "{:?}", weight)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+const QI_VAL: u8 = 1;
+const QI_OPR: u8 = 2;
+
+const OP_NOT: u8 = 1;
+const OP_AND: u8 = 2;
+const OP_OR: u8 = 3;
+const OP_PHRASE: u8 = 4;
+
+enum QueryItem {
+    Value {
+        weight: u8,
+        prefix: bool,
+        word: String,
+    },
+    Operator {
+        op: u8,
+        distance: i16,
+    },
+}
+
+/// Read-only mapping of `tsquery` columns, kept as their text, for
+/// inspecting search queries.
+///
+/// The text is rebuilt from the binary format like Postgres does, except
+/// that every nested `&`, `|` and `<->` operation is parenthesized.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgTsQuery(pub String);
+
+impl Display for PgTsQuery {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(&self.0)
+    }
+}
+
+/// Formats the query item at the given index, whose operands follow it with
+/// the right one first, returning the index after it.
+fn format_query(
+    items: &[QueryItem],
+    idx: usize,
+    nested: bool,
+    out: &mut String,
+) -> Result<usize, Box<dyn StdError + Sync + Send>> {
+    let item = items.get(idx).ok_or("invalid tsquery operands")?;
+
+    match *item {
+        QueryItem::Value {
+            weight,
+            prefix,
+            ref word,
+        } => {
+            push_quoted(out, word);
+
+            if weight != 0 || prefix {
+                out.push(':');
+            }
+            if prefix {
+                out.push('*');
+            }
+            for &(bit, letter) in &[(8, 'A'), (4, 'B'), (2, 'C'), (1, 'D')] {
+                if weight & bit != 0 {
+                    out.push(letter);
+                }
+            }
+
+            Ok(idx + 1)
+        }
+        QueryItem::Operator { op: OP_NOT, .. } => {
+            out.push('!');
+            format_query(items, idx + 1, true, out)
+        }
+        QueryItem::Operator { op, distance } => {
+            // Format the right operand first to find where the left one
+            // starts, then put them in order.
+            let mut right = String::new();
+            let left_idx = format_query(items, idx + 1, true, &mut right)?;
+            let mut left = String::new();
+            let end = format_query(items, left_idx, true, &mut left)?;
+
+            let op = match op {
+                OP_AND => " & ".to_string(),
+                OP_OR => " | ".to_string(),
+                OP_PHRASE if distance == 1 => " <-> ".to_string(),
+                OP_PHRASE => format!(" <{}> ", distance),
+                _ => return Err("invalid tsquery operator".into()),
+            };
+
+            if nested {
+                out.push('(');
+            }
+            out.push_str(&left);
+            out.push_str(&op);
+            out.push_str(&right);
+            if nested {
+                out.push(')');
+            }
+
+            Ok(end)
+        }
+    }
+}
+
+impl<'a> FromSql<'a> for PgTsQuery {
+    fn from_sql(
+        _: &Type,
+        mut raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        check_len(raw, 4)?;
+        let count =
+            usize::try_from(raw.get_i32()).map_err(|_| "invalid tsquery length")?;
+
+        let mut items = Vec::with_capacity(count);
+        for _ in 0..count {
+            check_len(raw, 1)?;
+
+            let item = match raw.get_u8() {
+                QI_VAL => {
+                    check_len(raw, 2)?;
+                    let weight = raw.get_u8();
+                    let prefix = raw.get_u8() != 0;
+                    let word = read_cstr(&mut raw)?.to_string();
+
+                    QueryItem::Value {
+                        weight,
+                        prefix,
+                        word,
+                    }
+                }
+                QI_OPR => {
+                    check_len(raw, 1)?;
+                    let op = raw.get_u8();
+                    let distance = if op == OP_PHRASE {
+                        check_len(raw, 2)?;
+                        raw.get_i16()
+                    } else {
+                        0
+                    };
+
+                    QueryItem::Operator { op, distance }
+                }
+                _ => return Err("invalid tsquery item type".into()),
+            };
+            items.push(item);
+        }
+
+        if !raw.is_empty() {
+            return Err("invalid tsquery length".into());
+        }
+
+        let mut text = String::new();
+        if !items.is_empty() {
+            let end = format_query(&items, 0, false, &mut text)?;
+            if end != items.len() {
+                return Err("invalid tsquery operands".into());
+            }
+        }
+
+        Ok(PgTsQuery(text))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "tsquery"
+    }
+}
@@ -0,0 +1,73 @@
+//! The `PgXml` field type.
+
+use bytes::{BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Deref;
+
+/// Mapping of `xml` columns and parameters to their text.
+///
+/// The document isn't parsed; Postgres checks that values written through
+/// `ToSql` are well-formed.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PgXml(pub String);
+
+impl PgXml {
+    /// Unwraps the inner text.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for PgXml {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for PgXml {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(&self.0)
+    }
+}
+
+impl From<String> for PgXml {
+    fn from(text: String) -> Self {
+        PgXml(text)
+    }
+}
+
+impl<'a> FromSql<'a> for PgXml {
+    fn from_sql(
+        _: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(PgXml(std::str::from_utf8(raw)?.to_string()))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::XML
+    }
+}
+
+impl ToSql for PgXml {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        out.put_slice(self.0.as_bytes());
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::XML
+    }
+
+    to_sql_checked!();
+}
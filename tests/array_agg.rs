@@ -0,0 +1,203 @@
+//! Checks the mapping of arrays into `Vec` fields, empty, NULL or holding
+//! NULL elements, both of built-in types and of composite types.
+
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::types::Type;
+use tokio_postgres::Client;
+
+mod common;
+
+use common::connect;
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "posts")]
+pub struct Post {
+    pub tags: Vec<String>,
+    pub scores: Option<Vec<Option<i32>>>,
+}
+
+fn post_row(tags: Option<Vec<Option<&str>>>, scores: Option<Vec<Option<i32>>>) -> Post {
+    let row = MockRowBuilder::new()
+        .column("tags", Type::TEXT_ARRAY, tags)
+        .column("scores", Type::INT4_ARRAY, scores)
+        .build();
+
+    Post::from_row(row).unwrap()
+}
+
+#[test]
+fn empty_arrays() {
+    assert_eq!(
+        post_row(Some(vec![]), Some(vec![])),
+        Post {
+            tags: vec![],
+            scores: Some(vec![]),
+        }
+    );
+}
+
+#[test]
+fn null_arrays() {
+    assert_eq!(post_row(Some(vec![Some("a")]), None).scores, None);
+
+    let row = MockRowBuilder::new()
+        .column("tags", Type::TEXT_ARRAY, None::<Vec<String>>)
+        .column("scores", Type::INT4_ARRAY, None::<Vec<i32>>)
+        .build();
+    assert!(Post::from_row(row).unwrap_err().is_unexpected_null());
+}
+
+#[test]
+fn null_elements() {
+    assert_eq!(
+        post_row(Some(vec![Some("a")]), Some(vec![Some(1), None, Some(3)])).scores,
+        Some(vec![Some(1), None, Some(3)])
+    );
+
+    let row = MockRowBuilder::new()
+        .column("tags", Type::TEXT_ARRAY, vec![Some("a"), None])
+        .column("scores", Type::INT4_ARRAY, None::<Vec<i32>>)
+        .build();
+    assert!(Post::from_row(row).unwrap_err().is_conversion());
+}
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "items", composite = "items")]
+pub struct Item {
+    pub id: i64,
+    pub order_id: i64,
+    pub sku: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "orders")]
+pub struct Order {
+    pub id: i64,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "orders")]
+pub struct SparseOrder {
+    pub id: i64,
+    pub items: Option<Vec<Option<Item>>>,
+}
+
+async fn orders(client: &Client) {
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE orders (id INT8 PRIMARY KEY);
+             CREATE TEMPORARY TABLE items (
+                 id INT8 PRIMARY KEY,
+                 order_id INT8 NOT NULL REFERENCES orders,
+                 sku TEXT NOT NULL,
+                 note TEXT
+             );
+             INSERT INTO orders VALUES (1), (2);
+             INSERT INTO items VALUES (1, 1, 'a', NULL), (2, 1, 'b', 'gift');",
+        )
+        .await
+        .unwrap();
+}
+
+fn items() -> Vec<Item> {
+    vec![
+        Item {
+            id: 1,
+            order_id: 1,
+            sku: "a".to_string(),
+            note: None,
+        },
+        Item {
+            id: 2,
+            order_id: 1,
+            sku: "b".to_string(),
+            note: Some("gift".to_string()),
+        },
+    ]
+}
+
+#[tokio::test]
+async fn composite_arrays() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    orders(&client).await;
+
+    // The query of the example, turning the items of an order without any
+    // into an empty array.
+    let rows = client
+        .query(
+            "SELECT o.id,
+                 COALESCE(array_agg(i ORDER BY i.id) FILTER (WHERE i.id IS NOT NULL), '{}')
+                     AS items
+             FROM orders o
+             LEFT JOIN items i ON i.order_id = o.id
+             GROUP BY o.id
+             ORDER BY o.id",
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Order::from_row_refs(&rows).unwrap(),
+        [
+            Order {
+                id: 1,
+                items: items(),
+            },
+            Order {
+                id: 2,
+                items: vec![],
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn composite_arrays_with_null_elements() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    orders(&client).await;
+
+    // Without `FILTER`, the order without items aggregates one NULL row.
+    let rows = client
+        .query(
+            "SELECT o.id, array_agg(i ORDER BY i.id) AS items
+             FROM orders o
+             LEFT JOIN items i ON i.order_id = o.id
+             GROUP BY o.id
+             ORDER BY o.id",
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        SparseOrder::from_row_refs(&rows).unwrap(),
+        [
+            SparseOrder {
+                id: 1,
+                items: Some(items().into_iter().map(Some).collect()),
+            },
+            SparseOrder {
+                id: 2,
+                items: Some(vec![None]),
+            },
+        ]
+    );
+    assert!(Order::from_row_ref(&rows[1]).is_err());
+
+    let row = client
+        .query_one("SELECT 3::INT8 AS id, NULL::items[] AS items", &[])
+        .await
+        .unwrap();
+    assert_eq!(SparseOrder::from_row_ref(&row).unwrap().items, None);
+    assert!(Order::from_row_ref(&row).unwrap_err().is_unexpected_null());
+}
@@ -0,0 +1,110 @@
+//! Checks the binary encoding of `PgBits` against the bytes sent by
+//! Postgres.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::PgBits;
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+/// `varbit_send(B'101100101')`, as captured from Postgres 15.
+const CAPTURED: &[u8] = b"\x00\x00\x00\x09\xb2\x80";
+
+fn expected() -> PgBits {
+    let mut bits = PgBits::new(9);
+    for idx in [0, 2, 3, 6, 8] {
+        bits.set(idx, true);
+    }
+
+    bits
+}
+
+#[test]
+fn decodes_captured_bytes() {
+    let bits = PgBits::from_sql(&Type::VARBIT, CAPTURED).unwrap();
+
+    assert_eq!(bits, expected());
+    assert_eq!(bits.to_string(), "101100101");
+    assert_eq!(bits.as_bytes(), b"\xb2\x80");
+    assert_eq!(bits.get(8), Some(true));
+    assert_eq!(bits.get(9), None);
+
+    let empty = PgBits::from_sql(&Type::VARBIT, b"\x00\x00\x00\x00").unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.to_string(), "");
+}
+
+#[test]
+fn round_trips() {
+    let mut buf = BytesMut::new();
+    expected().to_sql_checked(&Type::BIT, &mut buf).unwrap();
+
+    assert_eq!(&buf[..], CAPTURED);
+    assert_eq!(PgBits::from_sql(&Type::BIT, &buf).unwrap(), expected());
+}
+
+#[test]
+fn padding_bits_are_masked() {
+    // The 7 padding bits of the last byte are set.
+    let bits = PgBits::from_sql(&Type::VARBIT, b"\x00\x00\x00\x09\xb2\xff").unwrap();
+
+    assert_eq!(bits, expected());
+    assert_eq!(bits.as_bytes(), b"\xb2\x80");
+
+    // Whole bytes have no padding.
+    let bits = PgBits::from_sql(&Type::BIT, b"\x00\x00\x00\x08\xff").unwrap();
+    assert_eq!(bits.as_bytes(), b"\xff");
+}
+
+#[test]
+fn converts_integers() {
+    assert_eq!(PgBits::from_u64(0b1_0110_0101, 9), Some(expected()));
+    assert_eq!(expected().to_u64(), Some(0b1_0110_0101));
+
+    assert_eq!(PgBits::from_u64(0, 0), Some(PgBits::new(0)));
+    assert_eq!(PgBits::new(0).to_u64(), Some(0));
+
+    let max = PgBits::from_u64(u64::MAX, 64).unwrap();
+    assert_eq!(max.to_string(), "1".repeat(64));
+    assert_eq!(max.to_u64(), Some(u64::MAX));
+
+    let high = PgBits::from_u64(1 << 63, 64).unwrap();
+    assert_eq!(high.get(0), Some(true));
+    assert_eq!(high.get(63), Some(false));
+
+    // Too short to hold the value, or too long for an integer.
+    assert_eq!(PgBits::from_u64(0b100, 2), None);
+    assert_eq!(PgBits::from_u64(1, 0), None);
+    assert_eq!(PgBits::from_u64(0, 65), None);
+    assert_eq!(PgBits::new(65).to_u64(), None);
+}
+
+#[test]
+#[should_panic(expected = "bit index 9 out of bounds for 9 bits")]
+fn set_out_of_bounds() {
+    expected().set(9, true);
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    for len in [0, 3, 4, 5] {
+        assert!(
+            PgBits::from_sql(&Type::VARBIT, &CAPTURED[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    let mut trailing = CAPTURED.to_vec();
+    trailing.push(0);
+    assert!(PgBits::from_sql(&Type::VARBIT, &trailing).is_err());
+
+    assert!(PgBits::from_sql(&Type::VARBIT, b"\xff\xff\xff\xff").is_err());
+}
+
+#[test]
+fn accepts_bit_and_varbit() {
+    assert!(<PgBits as FromSql>::accepts(&Type::BIT));
+    assert!(<PgBits as FromSql>::accepts(&Type::VARBIT));
+    assert!(<PgBits as ToSql>::accepts(&Type::VARBIT));
+    assert!(!<PgBits as FromSql>::accepts(&Type::BYTEA));
+    assert!(!<PgBits as ToSql>::accepts(&Type::TEXT));
+}
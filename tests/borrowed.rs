@@ -0,0 +1,36 @@
+//! Checks that borrowed mappings read their fields from the `Row` without
+//! copying, and can't outlive it.
+
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{FromTokioPostgresRowBorrowed, PostgresMapper};
+use tokio_postgres::types::Type;
+
+#[derive(PostgresMapper)]
+#[pg_mapper(borrowed)]
+pub struct LogLine<'a> {
+    pub host: &'a str,
+    pub payload: &'a [u8],
+    pub level: Option<&'a str>,
+}
+
+#[test]
+fn fields_borrow_from_the_row() {
+    let row = MockRowBuilder::new()
+        .column("host", Type::TEXT, "db-1")
+        .column("payload", Type::BYTEA, &b"\x00\x01"[..])
+        .column("level", Type::TEXT, None::<&str>)
+        .build();
+
+    let line = LogLine::from_row_borrowed(&row).unwrap();
+
+    assert_eq!(line.host, "db-1");
+    assert_eq!(line.payload, b"\x00\x01");
+    assert_eq!(line.level, None);
+}
+
+#[test]
+fn lifetimes() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/borrowed_pass.rs");
+    t.compile_fail("tests/ui/borrowed_outlives_row.rs");
+}
@@ -0,0 +1,534 @@
+//! Runs the `client` feature against the Postgres server at `DATABASE_URL`,
+//! skipping every test when it isn't set.
+
+use tokio_pg_mapper::client::{insert_many, ClientExt};
+use tokio_pg_mapper::{Error, PostgresMapper, TokioPostgresCrud};
+use tokio_postgres::Client;
+
+mod common;
+
+use common::connect;
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "tasks")]
+pub struct Task {
+    #[pg_mapper(rename = "order")]
+    pub position: Option<i32>,
+    pub title: String,
+}
+
+async fn tasks(client: &Client, positions: &[Option<i32>]) {
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE tasks (\"order\" INT4, title TEXT NOT NULL)",
+        )
+        .await
+        .unwrap();
+
+    for position in positions {
+        client
+            .execute(
+                "INSERT INTO tasks (\"order\", title) VALUES ($1, 'task')",
+                &[position],
+            )
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn query_page_quotes_the_key_column() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    tasks(&client, &[Some(1), Some(2), Some(3)]).await;
+
+    let page = client
+        .query_page::<Task, i32>("SELECT * FROM tasks", &[], "order", None, 2)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 2);
+    assert!(page.has_more);
+    assert_eq!(page.next_cursor, Some(2));
+
+    let page = client
+        .query_page::<Task, i32>("SELECT * FROM tasks", &[], "order", Some(2), 2)
+        .await
+        .unwrap();
+    assert_eq!(page.items[0].position, Some(3));
+    assert!(!page.has_more);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[tokio::test]
+async fn query_page_without_rows_keeps_the_cursor() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    tasks(&client, &[Some(1), Some(2)]).await;
+
+    let page = client
+        .query_page::<Task, i32>("SELECT * FROM tasks", &[], "order", Some(1), 0)
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+    assert!(page.has_more);
+    assert_eq!(page.next_cursor, Some(1));
+
+    let page = client
+        .query_page::<Task, i32>("SELECT * FROM tasks", &[], "order", Some(2), 0)
+        .await
+        .unwrap();
+    assert!(!page.has_more);
+    assert_eq!(page.next_cursor, Some(2));
+}
+
+#[tokio::test]
+async fn query_page_names_the_cursor_field_in_errors() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    // NULL keys are ordered last, so the last key of the first page is NULL.
+    tasks(&client, &[Some(1), None, None]).await;
+
+    let err = client
+        .query_page::<Task, i32>("SELECT * FROM tasks", &[], "order", None, 2)
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::UnexpectedNull { column, field, .. } => {
+            assert_eq!(column, "order");
+            assert_eq!(field, "next_cursor");
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "emails", insert)]
+pub struct Email {
+    pub address: String,
+}
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "counters", insert)]
+pub struct Counter {
+    #[pg_mapper(primary_key, readonly)]
+    pub id: i64,
+}
+
+fn emails(addresses: &[&str]) -> Vec<Email> {
+    addresses
+        .iter()
+        .map(|address| Email {
+            address: address.to_string(),
+        })
+        .collect()
+}
+
+async fn count_emails(client: &Client) -> i64 {
+    client
+        .query_one("SELECT count(*) FROM emails", &[])
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[tokio::test]
+async fn insert_many_names_the_failed_row() {
+    let mut client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    client
+        .batch_execute("CREATE TEMPORARY TABLE emails (address TEXT PRIMARY KEY)")
+        .await
+        .unwrap();
+
+    let mut transaction = client.transaction().await.unwrap();
+    let values = emails(&["a@example.com", "b@example.com", "a@example.com"]);
+    let err = insert_many(&mut transaction, &values).await.unwrap_err();
+
+    match err {
+        Error::Batch {
+            chunk, offset, row, ..
+        } => {
+            assert_eq!((chunk, offset, row), (0, 0, Some(2)));
+        }
+        ref err => panic!("unexpected error: {}", err),
+    }
+    assert!(err.is_unique_violation());
+
+    let values = emails(&["a@example.com", "b@example.com"]);
+    assert_eq!(insert_many(&mut transaction, &values).await.unwrap(), 2);
+    transaction.commit().await.unwrap();
+    assert_eq!(count_emails(&client).await, 2);
+}
+
+#[tokio::test]
+async fn insert_many_keeps_the_transaction_usable_after_a_failed_chunk() {
+    let mut client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    client
+        .batch_execute("CREATE TEMPORARY TABLE emails (address TEXT PRIMARY KEY)")
+        .await
+        .unwrap();
+
+    let mut transaction = client.transaction().await.unwrap();
+    let values = emails(&["a@example.com"]);
+    insert_many(&mut transaction, &values).await.unwrap();
+
+    let values = emails(&["b@example.com", "a@example.com"]);
+    let err = insert_many(&mut transaction, &values).await.unwrap_err();
+
+    match err {
+        Error::Batch { row, .. } => assert_eq!(row, Some(1)),
+        ref err => panic!("unexpected error: {}", err),
+    }
+    // Only the failed chunk is rolled back, so committing keeps the values
+    // inserted before it.
+    transaction.commit().await.unwrap();
+    assert_eq!(count_emails(&client).await, 1);
+}
+
+#[tokio::test]
+async fn insert_many_leaves_the_transaction_to_the_caller() {
+    let mut client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    client
+        .batch_execute("CREATE TEMPORARY TABLE emails (address TEXT PRIMARY KEY)")
+        .await
+        .unwrap();
+
+    let mut transaction = client.transaction().await.unwrap();
+    let values = emails(&["a@example.com", "b@example.com"]);
+    assert_eq!(insert_many(&mut transaction, &values).await.unwrap(), 2);
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(count_emails(&client).await, 0);
+}
+
+#[tokio::test]
+async fn insert_many_without_columns_fails_up_front() {
+    let mut client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+
+    let mut transaction = client.transaction().await.unwrap();
+    let err = insert_many(&mut transaction, &[Counter { id: 1 }])
+        .await
+        .unwrap_err();
+
+    assert!(err.is_no_insert_columns(), "{}", err);
+    assert_eq!(err.type_name(), Some(std::any::type_name::<Counter>()));
+}
+
+async fn titles<C: ClientExt>(client: &C) -> Vec<String> {
+    client
+        .query_as::<Task, _>("SELECT * FROM tasks ORDER BY \"order\"", &[])
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|task| task.title)
+        .collect()
+}
+
+#[tokio::test]
+async fn transaction_commit_keeps_its_statements() {
+    let mut client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    tasks(&client, &[Some(1)]).await;
+
+    let transaction = client.transaction().await.unwrap();
+    let updated = transaction
+        .exec(
+            "UPDATE tasks SET title = 'done' WHERE \"order\" = $1",
+            &[&1],
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated, 1);
+
+    let task = transaction
+        .query_one_as::<Task, _>("SELECT * FROM tasks", &[])
+        .await
+        .unwrap();
+    assert_eq!(task.title, "done");
+
+    transaction.commit().await.unwrap();
+    assert_eq!(titles(&client).await, ["done"]);
+}
+
+#[tokio::test]
+async fn transaction_rollback_discards_its_statements() {
+    let mut client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    tasks(&client, &[Some(1)]).await;
+
+    let transaction = client.transaction().await.unwrap();
+    transaction
+        .exec("UPDATE tasks SET title = 'done'", &[])
+        .await
+        .unwrap();
+    transaction
+        .exec("INSERT INTO tasks VALUES (2, 'new')", &[])
+        .await
+        .unwrap();
+    assert_eq!(titles(&transaction).await, ["done", "new"]);
+
+    transaction.rollback().await.unwrap();
+    assert_eq!(titles(&client).await, ["task"]);
+}
+
+#[tokio::test]
+async fn transaction_dropped_without_commit_is_rolled_back() {
+    let mut client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    tasks(&client, &[Some(1)]).await;
+
+    {
+        let transaction = client.transaction().await.unwrap();
+        transaction.exec("DELETE FROM tasks", &[]).await.unwrap();
+    }
+
+    assert_eq!(titles(&client).await, ["task"]);
+}
+
+#[derive(Clone, Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "accounts", crud)]
+pub struct Account {
+    #[pg_mapper(primary_key)]
+    pub id: i64,
+    pub name: String,
+    #[pg_mapper(readonly)]
+    pub created_by: String,
+}
+
+async fn accounts(client: &Client) {
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE accounts (
+                 id INT8 PRIMARY KEY,
+                 name TEXT NOT NULL,
+                 created_by TEXT NOT NULL DEFAULT 'admin'
+             );
+             INSERT INTO accounts (id, name) VALUES (1, 'ada'), (2, 'bob');",
+        )
+        .await
+        .unwrap();
+}
+
+async fn all_accounts(client: &Client) -> Vec<Account> {
+    client
+        .query_as("SELECT * FROM accounts ORDER BY id", &[])
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn update_of_an_existing_row() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    accounts(&client).await;
+
+    let account = Account {
+        id: 1,
+        name: "ada lovelace".to_string(),
+        created_by: "ignored".to_string(),
+    };
+    assert!(Account::update(&client, &account).await.unwrap());
+
+    let stored = all_accounts(&client).await;
+    assert_eq!(stored[0].name, "ada lovelace");
+    // Readonly columns aren't assigned.
+    assert_eq!(stored[0].created_by, "admin");
+    assert_eq!(stored[1].name, "bob");
+}
+
+#[tokio::test]
+async fn update_of_a_missing_row_updates_nothing() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    accounts(&client).await;
+    let before = all_accounts(&client).await;
+
+    let account = Account {
+        id: 3,
+        name: "eve".to_string(),
+        created_by: "admin".to_string(),
+    };
+    assert!(!Account::update(&client, &account).await.unwrap());
+
+    assert_eq!(all_accounts(&client).await, before);
+    assert_eq!(Account::find(&client, &3).await.unwrap(), None);
+}
+
+#[derive(Clone, Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "documents", crud)]
+pub struct Document {
+    #[pg_mapper(primary_key)]
+    pub id: i64,
+    pub body: String,
+    #[pg_mapper(version)]
+    pub version: i32,
+}
+
+async fn documents(client: &Client) {
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE documents (
+                 id INT8 PRIMARY KEY,
+                 body TEXT NOT NULL,
+                 version INT4 NOT NULL
+             );
+             INSERT INTO documents VALUES (1, 'draft', 1);",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn update_of_the_current_version_increments_it() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    documents(&client).await;
+
+    let mut document = Document::find(&client, &1).await.unwrap().unwrap();
+    document.body = "final".to_string();
+    assert!(Document::update(&client, &document).await.unwrap());
+
+    let stored = Document::find(&client, &1).await.unwrap().unwrap();
+    assert_eq!((stored.body.as_str(), stored.version), ("final", 2));
+}
+
+#[tokio::test]
+async fn update_of_a_stale_version_fails() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    documents(&client).await;
+
+    let mut first = Document::find(&client, &1).await.unwrap().unwrap();
+    let mut second = first.clone();
+    first.body = "first".to_string();
+    second.body = "second".to_string();
+    assert!(Document::update(&client, &first).await.unwrap());
+
+    let err = Document::update(&client, &second).await.unwrap_err();
+    assert!(err.is_stale_version(), "{}", err);
+    assert_eq!(err.type_name(), Some(std::any::type_name::<Document>()));
+
+    let stored = Document::find(&client, &1).await.unwrap().unwrap();
+    assert_eq!((stored.body.as_str(), stored.version), ("first", 2));
+}
+
+#[tokio::test]
+async fn update_of_a_missing_versioned_row_fails() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    documents(&client).await;
+
+    let document = Document {
+        id: 2,
+        body: "missing".to_string(),
+        version: 1,
+    };
+    let err = Document::update(&client, &document).await.unwrap_err();
+    assert!(err.is_stale_version(), "{}", err);
+}
+
+#[derive(Clone, Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "memberships", crud)]
+pub struct Membership {
+    #[pg_mapper(primary_key)]
+    pub team_id: i64,
+    #[pg_mapper(primary_key)]
+    pub user_id: i64,
+    #[pg_mapper(readonly)]
+    pub joined_at: String,
+}
+
+#[tokio::test]
+async fn update_without_columns_fails_up_front() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+
+    let membership = Membership {
+        team_id: 1,
+        user_id: 2,
+        joined_at: "today".to_string(),
+    };
+    let err = Membership::update(&client, &membership).await.unwrap_err();
+
+    assert!(err.is_no_update_columns(), "{}", err);
+    assert_eq!(err.type_name(), Some(std::any::type_name::<Membership>()));
+}
+
+#[tokio::test]
+async fn select_by_primary_keys_stmt_pairs_the_columns_of_composite_keys() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE memberships (
+                 team_id INT8,
+                 user_id INT8,
+                 joined_at TEXT NOT NULL,
+                 PRIMARY KEY (team_id, user_id)
+             );
+             INSERT INTO memberships VALUES
+                 (1, 1, 'monday'), (1, 2, 'tuesday'), (2, 1, 'wednesday');",
+        )
+        .await
+        .unwrap();
+
+    let statement = format!(
+        "{} ORDER BY joined_at",
+        Membership::select_by_primary_keys_stmt()
+    );
+    let memberships: Vec<Membership> = client
+        .query_as(statement.as_str(), &[&vec![1i64, 2], &vec![2i64, 1]])
+        .await
+        .unwrap();
+
+    // (1, 1) and (2, 2) share columns with the keys, but aren't keys.
+    let days = memberships
+        .iter()
+        .map(|membership| membership.joined_at.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(days, ["tuesday", "wednesday"]);
+}
+
+#[test]
+fn load_many_needs_a_single_column_key() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/load_many_composite_key.rs");
+}
@@ -0,0 +1,22 @@
+//! Helpers shared by the tests running against a database.
+
+use tokio_postgres::{Client, NoTls};
+
+/// Connects to the database at `DATABASE_URL`, if set, so that tests can be
+/// skipped when it isn't.
+pub async fn connect() -> Option<Client> {
+    let url = match std::env::var("DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("DATABASE_URL isn't set, skipping");
+            return None;
+        }
+    };
+
+    let (client, connection) = tokio_postgres::connect(&url, NoTls)
+        .await
+        .expect("failed to connect to DATABASE_URL");
+    tokio::spawn(connection);
+
+    Some(client)
+}
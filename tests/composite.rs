@@ -0,0 +1,295 @@
+//! Checks the `FromSql` and `ToSql` implementations derived for structs
+//! mapped to composite types, against the bytes sent by Postgres.
+
+use tokio_pg_mapper::{CompositeAttributeError, FromTokioPostgresRow, PostgresMapper};
+use bytes::BytesMut;
+use tokio_postgres::types::{Field, FromSql, Kind, ToSql, Type, WasNull, WrongType};
+
+use std::error::Error as StdError;
+
+mod common;
+
+use common::connect;
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(composite = "item")]
+pub struct Item {
+    pub id: i64,
+    pub sku: String,
+    pub note: Option<String>,
+}
+
+/// `record_send(ROW(1, 'a', NULL)::item)`, as captured from Postgres 15.
+const CAPTURED: &[u8] = b"\
+    \x00\x00\x00\x03\
+    \x00\x00\x00\x14\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00\x00\x01\
+    \x00\x00\x00\x19\x00\x00\x00\x01a\
+    \x00\x00\x00\x19\xff\xff\xff\xff";
+
+fn item_type(fields: Vec<Field>) -> Type {
+    Type::new(
+        "item".to_string(),
+        16390,
+        Kind::Composite(fields),
+        "public".to_string(),
+    )
+}
+
+fn item_fields() -> Vec<Field> {
+    vec![
+        Field::new("id".to_string(), Type::INT8),
+        Field::new("sku".to_string(), Type::TEXT),
+        Field::new("note".to_string(), Type::TEXT),
+    ]
+}
+
+/// Get the attribute named by the error of a composite value, and the
+/// message of its source.
+fn attribute_error(err: &(dyn StdError + 'static)) -> (String, String) {
+    let err = err.downcast_ref::<CompositeAttributeError>().unwrap();
+
+    (
+        err.attribute().to_string(),
+        err.source().unwrap().to_string(),
+    )
+}
+
+#[test]
+fn decodes_captured_bytes() {
+    let item = Item::from_sql(&item_type(item_fields()), CAPTURED).unwrap();
+
+    assert_eq!(
+        item,
+        Item {
+            id: 1,
+            sku: "a".to_string(),
+            note: None,
+        }
+    );
+}
+
+#[test]
+fn attribute_count_mismatch() {
+    let mut fields = item_fields();
+    fields.push(Field::new("price".to_string(), Type::INT4));
+    let ty = item_type(fields);
+
+    assert!(!<Item as FromSql>::accepts(&ty));
+
+    let err = Item::from_sql(&ty, CAPTURED).unwrap_err();
+    assert_eq!(err.to_string(), "expected 4 composite attributes, got 3");
+
+    let mut raw = CAPTURED.to_vec();
+    raw[3] = 2;
+    let err = Item::from_sql(&item_type(item_fields()), &raw).unwrap_err();
+    assert_eq!(err.to_string(), "expected 3 composite attributes, got 2");
+}
+
+#[test]
+fn attribute_oid_mismatch() {
+    // `sku` sent as an `int4`.
+    let mut raw = CAPTURED.to_vec();
+    raw[23] = 0x17;
+
+    let err = Item::from_sql(&item_type(item_fields()), &raw).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "error converting composite attribute \"sku\""
+    );
+    assert_eq!(
+        attribute_error(err.as_ref()),
+        ("sku".to_string(), "unexpected type oid 23".to_string())
+    );
+}
+
+#[test]
+fn null_attribute_of_a_non_option_field() {
+    // `sku` sent as NULL.
+    let raw = b"\
+        \x00\x00\x00\x03\
+        \x00\x00\x00\x14\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00\x00\x01\
+        \x00\x00\x00\x19\xff\xff\xff\xff\
+        \x00\x00\x00\x19\xff\xff\xff\xff";
+
+    let err = Item::from_sql(&item_type(item_fields()), raw).unwrap_err();
+    let (attribute, _) = attribute_error(err.as_ref());
+
+    assert_eq!(attribute, "sku");
+    assert!(err.source().unwrap().is::<WasNull>());
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    let ty = item_type(item_fields());
+
+    for len in [0, 3, 4, 11, 20, CAPTURED.len() - 1] {
+        assert!(
+            Item::from_sql(&ty, &CAPTURED[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    let mut trailing = CAPTURED.to_vec();
+    trailing.push(0);
+    assert!(Item::from_sql(&ty, &trailing).is_err());
+}
+
+#[test]
+fn accepts_the_composite_type_by_name() {
+    assert!(<Item as FromSql>::accepts(&item_type(item_fields())));
+
+    let renamed = Type::new(
+        "items".to_string(),
+        16390,
+        Kind::Composite(item_fields()),
+        "public".to_string(),
+    );
+    assert!(!<Item as FromSql>::accepts(&renamed));
+
+    // `sku` as an `int4`.
+    let mut fields = item_fields();
+    fields[1] = Field::new("sku".to_string(), Type::INT4);
+    assert!(!<Item as FromSql>::accepts(&item_type(fields)));
+
+    assert!(!<Item as FromSql>::accepts(&Type::RECORD));
+}
+
+#[test]
+fn encodes_captured_bytes() {
+    let ty = item_type(item_fields());
+    let item = Item {
+        id: 1,
+        sku: "a".to_string(),
+        note: None,
+    };
+
+    let mut buf = BytesMut::new();
+    item.to_sql_checked(&ty, &mut buf).unwrap();
+    assert_eq!(&buf[..], CAPTURED);
+
+    let item = Item {
+        note: Some("gift".to_string()),
+        ..item
+    };
+    let mut buf = BytesMut::new();
+    item.to_sql_checked(&ty, &mut buf).unwrap();
+    assert_eq!(Item::from_sql(&ty, &buf).unwrap(), item);
+}
+
+#[test]
+fn encoding_checks_the_attributes() {
+    let item = Item {
+        id: 1,
+        sku: "a".to_string(),
+        note: None,
+    };
+
+    // `to_sql_checked` rejects a composite type of another shape.
+    let mut fields = item_fields();
+    fields.pop();
+    let ty = item_type(fields);
+    let err = item
+        .to_sql_checked(&ty, &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert!(err.is::<WrongType>());
+
+    let err = item.to_sql(&ty, &mut BytesMut::new()).err().unwrap();
+    assert_eq!(err.to_string(), "too many composite attributes");
+
+    let mut fields = item_fields();
+    fields.push(Field::new("price".to_string(), Type::INT4));
+    let err = item
+        .to_sql(&item_type(fields), &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "too few composite attributes");
+
+    // `sku` as an `int4`.
+    let mut fields = item_fields();
+    fields[1] = Field::new("sku".to_string(), Type::INT4);
+    let err = item
+        .to_sql(&item_type(fields), &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert_eq!(attribute_error(err.as_ref()).0, "sku");
+    assert!(err.source().unwrap().is::<WrongType>());
+
+    let err = item
+        .to_sql(&Type::TEXT, &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "text is not a composite type");
+}
+
+#[derive(Debug, PostgresMapper)]
+#[pg_mapper(table = "carts")]
+pub struct Cart {
+    pub id: i64,
+    pub item: Item,
+}
+
+#[tokio::test]
+async fn errors_name_the_column() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    client
+        .batch_execute("CREATE TYPE pg_temp.item AS (id INT8, sku TEXT, note TEXT)")
+        .await
+        .unwrap();
+
+    let row = client
+        .query_one(
+            "SELECT 1::INT8 AS id, ROW(1, NULL, NULL)::pg_temp.item AS item",
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let err = Cart::from_row(row).unwrap_err();
+    assert!(err.is_conversion());
+    assert!(
+        err.to_string().contains("error converting column \"item\""),
+        "{}",
+        err
+    );
+
+    let source = err.source().unwrap();
+    assert_eq!(attribute_error(source).0, "sku");
+    assert!(source.source().unwrap().is::<WasNull>());
+}
+
+#[tokio::test]
+async fn round_trips_through_postgres() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    client
+        .batch_execute("CREATE TYPE pg_temp.item AS (id INT8, sku TEXT, note TEXT)")
+        .await
+        .unwrap();
+
+    let item = Item {
+        id: 2,
+        sku: "b".to_string(),
+        note: None,
+    };
+    let row = client
+        .query_one("SELECT $1::pg_temp.item, ($1::pg_temp.item).sku", &[&item])
+        .await
+        .unwrap();
+
+    assert_eq!(row.get::<_, Item>(0), item);
+    assert_eq!(row.get::<_, String>(1), "b");
+}
+
+#[test]
+fn misuse_fails_to_compile() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/composite_capture_rest.rs");
+    t.compile_fail("tests/ui/composite_without_debug.rs");
+}
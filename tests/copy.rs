@@ -0,0 +1,67 @@
+//! The copy mapping of fields with conversion attributes, which used to be
+//! fetched as the field type rather than converted.
+
+use bytes::Bytes;
+use futures_util::TryStreamExt;
+use tokio_pg_mapper::{Error, FromBinaryCopyOutRow, MappedCopyOutStream, PostgresMapper};
+use tokio_postgres::binary_copy::BinaryCopyOutRow;
+use tokio_postgres::types::Type;
+
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime};
+
+mod common;
+
+use common::connect;
+
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "events", copy)]
+pub struct Event {
+    pub id: i64,
+    #[pg_mapper(rename = "Event Name", trim = "both")]
+    pub name: Cow<'static, str>,
+    pub payload: Option<Bytes>,
+    pub elapsed: Duration,
+    #[pg_mapper(epoch = "millis")]
+    pub created_at: SystemTime,
+    #[pg_mapper(default_on_null)]
+    pub attempts: i32,
+    #[pg_mapper(empty_as_none)]
+    pub note: Option<String>,
+}
+
+#[test]
+fn conversion_attributes_are_applied_to_copied_rows() {
+    let map: fn(&BinaryCopyOutRow) -> Result<Event, Error> =
+        Event::from_binary_copy_out_row;
+
+    let _ = map;
+}
+
+#[derive(Debug, PostgresMapper)]
+#[pg_mapper(table = "posts", copy)]
+pub struct Post {
+    pub tags: Vec<String>,
+}
+
+#[tokio::test]
+async fn null_elements_are_not_null_columns() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+
+    let stream = client
+        .copy_out("COPY (VALUES (ARRAY['a', NULL]), (NULL)) TO STDOUT (FORMAT binary)")
+        .await
+        .unwrap();
+    let posts = MappedCopyOutStream::<Post>::new(stream, &[Type::TEXT_ARRAY]);
+    futures_util::pin_mut!(posts);
+
+    let err = posts.try_next().await.unwrap_err();
+    assert!(err.is_conversion(), "{}", err);
+
+    let err = posts.try_next().await.unwrap_err();
+    assert!(err.is_unexpected_null(), "{}", err);
+}
@@ -0,0 +1,221 @@
+use tokio_pg_mapper::{Error, ErrorKind};
+use tokio_postgres::error::SqlState;
+use tokio_postgres::Client;
+
+use std::error::Error as StdError;
+
+mod common;
+
+use common::connect;
+
+/// Walks the chain of sources of an error, asserting that no message repeats
+/// one of the messages after it.
+fn messages(err: &(dyn StdError + 'static)) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut cause = Some(err);
+    while let Some(err) = cause {
+        messages.push(err.to_string());
+        cause = err.source();
+    }
+
+    for (idx, message) in messages.iter().enumerate() {
+        for source in &messages[idx + 1..] {
+            assert!(
+                !message.contains(source.as_str()),
+                "{:?} repeats its source {:?}",
+                message,
+                source
+            );
+        }
+    }
+
+    messages
+}
+
+#[test]
+fn conversion_message_appears_once_in_the_chain() {
+    let err = Error::Conversion("invalid digit found in string".into());
+
+    assert_eq!(
+        messages(&err),
+        vec!["error converting a value", "invalid digit found in string"]
+    );
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn db_message_appears_once_in_the_chain() {
+    use tokio_pg_mapper::test_util::MockRowBuilder;
+    use tokio_postgres::types::Type;
+
+    let row = MockRowBuilder::new().column("id", Type::INT8, 1i64).build();
+    let inner = row.try_get::<_, i64>("missing").unwrap_err();
+    let message = inner.to_string();
+
+    let err = Error::from(inner);
+
+    assert_eq!(messages(&err), vec!["database error".to_string(), message]);
+}
+
+#[cfg(all(feature = "test-util", feature = "derive"))]
+mod column_conversion {
+    use tokio_pg_mapper::test_util::MockRowBuilder;
+    use tokio_pg_mapper::{FromTokioPostgresRow, IntegerOutOfRange, PgU32, PostgresMapper};
+    use tokio_postgres::types::{Type, WrongType};
+
+    use std::error::Error as StdError;
+
+    #[allow(dead_code)]
+    #[derive(Debug, PostgresMapper)]
+    #[pg_mapper(table = "user")]
+    pub struct User {
+        pub id: PgU32,
+        pub name: String,
+    }
+
+    fn chain(err: tokio_pg_mapper::Error) -> Vec<String> {
+        anyhow::Error::from(err)
+            .chain()
+            .map(|cause| cause.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn source_is_the_wrong_type_error() {
+        let row = MockRowBuilder::new()
+            .column("id", Type::INT4, 1i32)
+            .column("name", Type::INT4, 2i32)
+            .build();
+
+        let err = User::from_row(row).unwrap_err();
+        assert!(err.is_wrong_type());
+        assert_eq!(
+            err.expected_rust_type(),
+            Some(std::any::type_name::<String>())
+        );
+        assert_eq!(err.actual_pg_type(), Some(Type::INT4));
+
+        let wrong_type = err
+            .source()
+            .unwrap()
+            .downcast_ref::<WrongType>()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            chain(err),
+            vec![
+                format!(
+                    "mapping User: error converting column \"name\" of Postgres type \
+                     int4 to Rust type {}",
+                    std::any::type_name::<String>()
+                ),
+                wrong_type,
+            ]
+        );
+    }
+
+    #[test]
+    fn source_is_the_error_of_from_sql() {
+        let row = MockRowBuilder::new()
+            .column("id", Type::INT4, -1i32)
+            .column("name", Type::TEXT, "Ada")
+            .build();
+
+        let err = User::from_row(row).unwrap_err();
+        assert!(err.source().unwrap().is::<IntegerOutOfRange>());
+        assert!(!err.is_wrong_type());
+        assert_eq!(err.actual_pg_type(), None);
+
+        assert_eq!(
+            chain(err),
+            vec![
+                format!(
+                    "mapping User: error converting column \"id\" of Postgres type \
+                     int4 to Rust type {}",
+                    std::any::type_name::<PgU32>()
+                ),
+                "-1 is out of range for u32".to_string(),
+            ]
+        );
+    }
+}
+
+async fn teams_and_users(client: &Client) {
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE teams (id INT8 PRIMARY KEY);
+             CREATE TEMPORARY TABLE users (
+                 email TEXT CONSTRAINT users_email_key UNIQUE,
+                 team_id INT8 CONSTRAINT users_team_id_fkey REFERENCES teams
+             );
+             INSERT INTO teams VALUES (1);
+             INSERT INTO users VALUES ('ada@example.com', 1);",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn unique_violation() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    teams_and_users(&client).await;
+
+    let err: Error = client
+        .execute("INSERT INTO users VALUES ('ada@example.com', 1)", &[])
+        .await
+        .unwrap_err()
+        .into();
+
+    assert_eq!(err.kind(), ErrorKind::Db);
+    assert_eq!(err.sqlstate(), Some(&SqlState::UNIQUE_VIOLATION));
+    assert!(err.is_unique_violation());
+    assert!(!err.is_foreign_key_violation());
+    assert_eq!(err.constraint(), Some("users_email_key"));
+    assert_eq!(err.as_db_error().unwrap().table(), Some("users"));
+}
+
+#[tokio::test]
+async fn foreign_key_violation() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    teams_and_users(&client).await;
+
+    let err: Error = client
+        .execute("INSERT INTO users VALUES ('bob@example.com', 2)", &[])
+        .await
+        .unwrap_err()
+        .into();
+
+    assert_eq!(err.sqlstate(), Some(&SqlState::FOREIGN_KEY_VIOLATION));
+    assert!(err.is_foreign_key_violation());
+    assert!(!err.is_unique_violation());
+    assert_eq!(err.constraint(), Some("users_team_id_fkey"));
+}
+
+#[tokio::test]
+async fn errors_without_a_constraint() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+
+    let err: Error = client
+        .execute("SELECT 1 / 0", &[])
+        .await
+        .unwrap_err()
+        .into();
+
+    assert_eq!(err.sqlstate(), Some(&SqlState::DIVISION_BY_ZERO));
+    assert!(!err.is_unique_violation());
+    assert!(!err.is_foreign_key_violation());
+    assert_eq!(err.constraint(), None);
+
+    assert_eq!(Error::NoRows.sqlstate(), None);
+    assert_eq!(Error::NoRows.constraint(), None);
+}
@@ -0,0 +1,35 @@
+use tokio_pg_mapper::{PostgresFilter, TokioPostgresFilter};
+
+#[derive(Default, PostgresFilter)]
+pub struct OrderFilter {
+    pub status: Option<String>,
+    #[pg_filter(column = "order", op = ">=")]
+    pub position_from: Option<i32>,
+    #[pg_filter(column = "Customer Name", op = "not ilike")]
+    pub customer_not_like: Option<String>,
+}
+
+#[test]
+fn empty_filter_has_no_clause() {
+    let filter = OrderFilter::default();
+    let (clause, params) = filter.where_clause(1);
+
+    assert_eq!(clause, "");
+    assert!(params.is_empty());
+}
+
+#[test]
+fn columns_are_quoted_and_placeholders_continue_from_the_offset() {
+    let filter = OrderFilter {
+        status: None,
+        position_from: Some(3),
+        customer_not_like: Some("%acme%".to_string()),
+    };
+    let (clause, params) = filter.where_clause(4);
+
+    assert_eq!(
+        clause,
+        " WHERE \"order\" >= $4 AND \"Customer Name\" NOT ILIKE $5 "
+    );
+    assert_eq!(params.len(), 2);
+}
@@ -0,0 +1,151 @@
+//! Checks that batch conversions report the rows which fail to be mapped by
+//! their index in the batch, and that indexed conversions check the order of
+//! the columns.
+
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::types::Type;
+use tokio_postgres::Row;
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "user")]
+pub struct User {
+    pub id: i64,
+    pub email: Option<String>,
+}
+
+fn good(id: i64) -> Row {
+    MockRowBuilder::new()
+        .column("id", Type::INT8, id)
+        .column("email", Type::TEXT, format!("{}@example.com", id))
+        .build()
+}
+
+fn null_id() -> Row {
+    MockRowBuilder::new()
+        .column("id", Type::INT8, None::<i64>)
+        .column("email", Type::TEXT, None::<String>)
+        .build()
+}
+
+fn wrong_type() -> Row {
+    MockRowBuilder::new()
+        .column("id", Type::TEXT, "1")
+        .column("email", Type::TEXT, None::<String>)
+        .build()
+}
+
+fn missing_column() -> Row {
+    MockRowBuilder::new().column("id", Type::INT8, 1i64).build()
+}
+
+fn swapped_columns() -> Row {
+    MockRowBuilder::new()
+        .column("email", Type::TEXT, "1@example.com")
+        .column("id", Type::INT8, 1i64)
+        .build()
+}
+
+fn user(id: i64) -> User {
+    User {
+        id,
+        email: Some(format!("{}@example.com", id)),
+    }
+}
+
+#[test]
+fn from_rows_filtered_sets_interleaved_bad_rows_aside() {
+    let rows = vec![
+        null_id(),
+        good(1),
+        wrong_type(),
+        good(2),
+        good(3),
+        missing_column(),
+        good(4),
+        null_id(),
+    ];
+
+    let (users, errors) = User::from_rows_filtered(rows);
+
+    assert_eq!(users, [user(1), user(2), user(3), user(4)]);
+
+    let indices = errors.iter().map(|(idx, _)| *idx).collect::<Vec<_>>();
+    assert_eq!(indices, [0, 2, 5, 7]);
+
+    assert!(errors[0].1.is_unexpected_null());
+    assert!(errors[1].1.is_wrong_type());
+    assert!(errors[2].1.is_column_not_found());
+    assert!(errors[3].1.is_unexpected_null());
+}
+
+#[test]
+fn from_rows_filtered_without_bad_rows() {
+    let (users, errors) = User::from_rows_filtered(vec![good(1), good(2)]);
+
+    assert_eq!(users, [user(1), user(2)]);
+    assert!(errors.is_empty());
+
+    let (users, errors) = User::from_rows_filtered(Vec::new());
+    assert!(users.is_empty());
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn from_rows_filtered_with_only_bad_rows() {
+    let (users, errors) = User::from_rows_filtered(vec![wrong_type(), null_id()]);
+
+    assert!(users.is_empty());
+    assert_eq!(
+        errors.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(),
+        [0, 1]
+    );
+}
+
+#[test]
+fn from_rows_stops_at_the_first_bad_row() {
+    let err =
+        User::from_rows(vec![good(1), good(2), null_id(), wrong_type()]).unwrap_err();
+
+    assert!(err.is_unexpected_null(), "{}", err);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn from_rows_par_names_the_first_bad_row() {
+    use tokio_pg_mapper::Error;
+
+    let rows = vec![good(1), good(2), wrong_type(), good(3), null_id()];
+    let err = User::from_rows_par(rows).unwrap_err();
+
+    match err {
+        Error::Row { index, ref source } => {
+            assert_eq!(index, 2);
+            assert!(source.is_wrong_type(), "{}", source);
+        }
+        ref err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn from_row_indexed_fetches_the_columns_by_position() {
+    assert_eq!(User::from_row_indexed(&good(1)).unwrap(), user(1));
+
+    let err = User::from_row_indexed(&missing_column()).unwrap_err();
+    assert!(err.is_column_not_found(), "{}", err);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(
+    expected = "column 0 of the row mapped to User is \"email\" instead of \"id\""
+)]
+fn from_row_indexed_panics_on_swapped_columns() {
+    let _ = User::from_row_indexed(&swapped_columns());
+}
+
+#[cfg(not(debug_assertions))]
+#[test]
+fn from_row_indexed_skips_the_check_in_release_builds() {
+    assert!(User::from_row_indexed(&swapped_columns()).is_err());
+}
@@ -0,0 +1,145 @@
+//! Checks the binary encodings of the geometric types against the bytes
+//! sent by Postgres.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::{PgBox, PgPath, PgPoint};
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+use std::fmt::Debug;
+
+mod common;
+
+use common::connect;
+
+/// `point_send('(1.5,-2)'::point)`
+const POINT: &[u8] = b"\
+    \x3f\xf8\x00\x00\x00\x00\x00\x00\
+    \xc0\x00\x00\x00\x00\x00\x00\x00";
+
+/// `box_send('((3,4),(1,2))'::box)`, the upper right corner first.
+const BOX: &[u8] = b"\
+    \x40\x08\x00\x00\x00\x00\x00\x00\x40\x10\x00\x00\x00\x00\x00\x00\
+    \x3f\xf0\x00\x00\x00\x00\x00\x00\x40\x00\x00\x00\x00\x00\x00\x00";
+
+/// `path_send('[(0,0),(1,1)]'::path)`
+const OPEN_PATH: &[u8] = b"\
+    \x00\x00\x00\x00\x02\
+    \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\
+    \x3f\xf0\x00\x00\x00\x00\x00\x00\x3f\xf0\x00\x00\x00\x00\x00\x00";
+
+/// `path_send('((0,0),(1,1),(1,0))'::path)`
+const CLOSED_PATH: &[u8] = b"\
+    \x01\x00\x00\x00\x03\
+    \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\
+    \x3f\xf0\x00\x00\x00\x00\x00\x00\x3f\xf0\x00\x00\x00\x00\x00\x00\
+    \x3f\xf0\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+
+fn point() -> PgPoint {
+    PgPoint::new(1.5, -2.0)
+}
+
+fn pg_box() -> PgBox {
+    PgBox::new(PgPoint::new(3.0, 4.0), PgPoint::new(1.0, 2.0))
+}
+
+fn open_path() -> PgPath {
+    PgPath {
+        closed: false,
+        points: vec![PgPoint::new(0.0, 0.0), PgPoint::new(1.0, 1.0)],
+    }
+}
+
+fn closed_path() -> PgPath {
+    PgPath {
+        closed: true,
+        points: vec![
+            PgPoint::new(0.0, 0.0),
+            PgPoint::new(1.0, 1.0),
+            PgPoint::new(1.0, 0.0),
+        ],
+    }
+}
+
+/// Checks that the value decodes from and encodes to the given bytes, and
+/// that any truncation or extra byte is rejected.
+fn check<T>(ty: &Type, value: T, bytes: &[u8])
+where
+    T: for<'a> FromSql<'a> + ToSql + Debug + PartialEq,
+{
+    assert_eq!(T::from_sql(ty, bytes).unwrap(), value);
+
+    let mut buf = BytesMut::new();
+    value.to_sql_checked(ty, &mut buf).unwrap();
+    assert_eq!(&buf[..], bytes);
+
+    for len in 0..bytes.len() {
+        assert!(T::from_sql(ty, &bytes[..len]).is_err(), "{} bytes", len);
+    }
+
+    let mut trailing = bytes.to_vec();
+    trailing.push(0);
+    assert!(T::from_sql(ty, &trailing).is_err());
+}
+
+#[test]
+fn point_bytes() {
+    check(&Type::POINT, point(), POINT);
+}
+
+#[test]
+fn box_bytes() {
+    check(&Type::BOX, pg_box(), BOX);
+
+    // The corners are swapped like Postgres does on input.
+    let swapped = PgBox {
+        high: PgPoint::new(1.0, 2.0),
+        low: PgPoint::new(3.0, 4.0),
+    };
+    let mut buf = BytesMut::new();
+    swapped.to_sql_checked(&Type::BOX, &mut buf).unwrap();
+    assert_eq!(&buf[..], BOX);
+}
+
+#[test]
+fn path_bytes() {
+    check(&Type::PATH, open_path(), OPEN_PATH);
+    check(&Type::PATH, closed_path(), CLOSED_PATH);
+    check(&Type::PATH, PgPath::default(), b"\x00\x00\x00\x00\x00");
+
+    // Negative point counts are rejected.
+    assert!(PgPath::from_sql(&Type::PATH, b"\x00\xff\xff\xff\xff").is_err());
+}
+
+#[test]
+fn types_are_not_interchangeable() {
+    assert!(!<PgPoint as FromSql>::accepts(&Type::BOX));
+    assert!(!<PgBox as FromSql>::accepts(&Type::POINT));
+    assert!(!<PgPath as FromSql>::accepts(&Type::POLYGON));
+
+    let mut buf = BytesMut::new();
+    assert!(point().to_sql_checked(&Type::BOX, &mut buf).is_err());
+}
+
+#[tokio::test]
+async fn round_trips_through_postgres() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+
+    let row = client
+        .query_one(
+            "SELECT $1::point, $2::box, $3::path, $4::path, \
+             '(1.5,-2)'::point, '((1,2),(3,4))'::box",
+            &[&point(), &pg_box(), &open_path(), &closed_path()],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(row.get::<_, PgPoint>(0), point());
+    assert_eq!(row.get::<_, PgBox>(1), pg_box());
+    assert_eq!(row.get::<_, PgPath>(2), open_path());
+    assert_eq!(row.get::<_, PgPath>(3), closed_path());
+    assert_eq!(row.get::<_, PgPoint>(4), point());
+    assert_eq!(row.get::<_, PgBox>(5), pg_box());
+}
@@ -0,0 +1,113 @@
+//! Checks the binary encoding of `PgHstore` against the bytes sent by
+//! Postgres.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::PgHstore;
+use tokio_postgres::types::{FromSql, Kind, ToSql, Type};
+
+use std::collections::HashMap;
+
+mod common;
+
+use common::connect;
+
+/// `hstore_send('a=>1, b=>NULL, "k e"=>"é"'::hstore)`, as captured from
+/// Postgres 15.
+const CAPTURED: &[u8] = b"\
+    \x00\x00\x00\x03\
+    \x00\x00\x00\x01a\x00\x00\x00\x011\
+    \x00\x00\x00\x01b\xff\xff\xff\xff\
+    \x00\x00\x00\x03k e\x00\x00\x00\x02\xc3\xa9";
+
+fn hstore_type() -> Type {
+    Type::new(
+        "hstore".to_string(),
+        16385,
+        Kind::Simple,
+        "public".to_string(),
+    )
+}
+
+fn expected() -> PgHstore {
+    let mut map = HashMap::new();
+    map.insert("a".to_string(), Some("1".to_string()));
+    map.insert("b".to_string(), None);
+    map.insert("k e".to_string(), Some("é".to_string()));
+
+    PgHstore(map)
+}
+
+#[test]
+fn decodes_captured_bytes() {
+    let hstore = PgHstore::from_sql(&hstore_type(), CAPTURED).unwrap();
+
+    assert_eq!(hstore, expected());
+}
+
+#[test]
+fn round_trips() {
+    let ty = hstore_type();
+    let mut buf = BytesMut::new();
+    expected().to_sql_checked(&ty, &mut buf).unwrap();
+
+    // Entries are written in the order of the map, so only the length of
+    // the encoding can be compared with the captured one.
+    assert_eq!(buf.len(), CAPTURED.len());
+    assert_eq!(PgHstore::from_sql(&ty, &buf).unwrap(), expected());
+
+    let mut buf = BytesMut::new();
+    PgHstore::default().to_sql_checked(&ty, &mut buf).unwrap();
+    assert_eq!(&buf[..], b"\x00\x00\x00\x00");
+    assert!(PgHstore::from_sql(&ty, &buf).unwrap().is_empty());
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    let ty = hstore_type();
+
+    for len in [0, 3, 8, 13, CAPTURED.len() - 1] {
+        assert!(
+            PgHstore::from_sql(&ty, &CAPTURED[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    let mut trailing = CAPTURED.to_vec();
+    trailing.push(0);
+    assert!(PgHstore::from_sql(&ty, &trailing).is_err());
+
+    assert!(PgHstore::from_sql(&ty, b"\xff\xff\xff\xff").is_err());
+    assert!(PgHstore::from_sql(&ty, b"\x00\x00\x00\x01\x00\x00\x00\x01\xff").is_err());
+}
+
+#[test]
+fn accepts_hstore_by_name() {
+    assert!(<PgHstore as FromSql>::accepts(&hstore_type()));
+    assert!(<PgHstore as ToSql>::accepts(&hstore_type()));
+    assert!(!<PgHstore as FromSql>::accepts(&Type::TEXT));
+    assert!(!<PgHstore as ToSql>::accepts(&Type::JSONB));
+}
+
+#[tokio::test]
+async fn round_trips_through_postgres() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS hstore")
+        .await
+        .unwrap();
+
+    let row = client
+        .query_one(
+            "SELECT $1::hstore, 'a=>1, b=>NULL, \"k e\"=>\"é\"'::hstore",
+            &[&expected()],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(row.get::<_, PgHstore>(0), expected());
+    assert_eq!(row.get::<_, PgHstore>(1), expected());
+}
@@ -0,0 +1,224 @@
+//! Checks the mapping of integer columns to `PgU32`, `PgU64` and
+//! `PgNonZero` fields.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{
+    Error, FromTokioPostgresRow, IntegerOutOfRange, PgNonZero, PgU32, PgU64,
+    PostgresMapper,
+};
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+use std::error::Error as StdError;
+use std::num::{NonZeroI16, NonZeroI32, NonZeroI64};
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "counters")]
+pub struct Counter {
+    pub hits: PgU32,
+    pub bytes: Option<PgU64>,
+}
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "users")]
+pub struct User {
+    pub id: PgNonZero<NonZeroI64>,
+    pub team_id: Option<PgNonZero<NonZeroI32>>,
+}
+
+/// Get the column named by a conversion error, and the value of its
+/// `IntegerOutOfRange` source.
+fn out_of_range(err: &Error) -> (&str, i128) {
+    let column = match *err {
+        Error::ColumnConversion { ref column, .. } => column,
+        ref err => panic!("expected a conversion error, got {:?}", err),
+    };
+    let source = err
+        .source()
+        .unwrap()
+        .downcast_ref::<IntegerOutOfRange>()
+        .unwrap();
+
+    (column, source.value())
+}
+
+#[test]
+fn unsigned_values() {
+    let row = MockRowBuilder::new()
+        .column("hits", Type::INT8, i64::from(u32::MAX))
+        .column("bytes", Type::INT8, i64::MAX)
+        .build();
+
+    assert_eq!(
+        Counter::from_row(row).unwrap(),
+        Counter {
+            hits: PgU32(u32::MAX),
+            bytes: Some(PgU64(i64::MAX as u64)),
+        }
+    );
+
+    let row = MockRowBuilder::new()
+        .column("hits", Type::INT2, 0i16)
+        .column("bytes", Type::INT4, 0i32)
+        .build();
+    assert_eq!(Counter::from_row(row).unwrap().bytes, Some(PgU64(0)));
+}
+
+#[test]
+fn null_is_none_rather_than_zero() {
+    let row = MockRowBuilder::new()
+        .column("hits", Type::INT4, 1i32)
+        .column("bytes", Type::INT8, None::<i64>)
+        .build();
+
+    assert_eq!(Counter::from_row(row).unwrap().bytes, None);
+
+    let row = MockRowBuilder::new()
+        .column("hits", Type::INT4, None::<i32>)
+        .column("bytes", Type::INT8, None::<i64>)
+        .build();
+    assert!(Counter::from_row(row).unwrap_err().is_unexpected_null());
+}
+
+#[test]
+fn negative_values_name_the_column() {
+    let row = MockRowBuilder::new()
+        .column("hits", Type::INT4, -1i32)
+        .column("bytes", Type::INT8, None::<i64>)
+        .build();
+    let err = Counter::from_row(row).unwrap_err();
+    assert_eq!(out_of_range(&err), ("hits", -1));
+    assert_eq!(
+        err.source().unwrap().to_string(),
+        "-1 is out of range for u32"
+    );
+
+    let row = MockRowBuilder::new()
+        .column("hits", Type::INT4, 1i32)
+        .column("bytes", Type::INT8, i64::MIN)
+        .build();
+    let err = Counter::from_row(row).unwrap_err();
+    assert_eq!(out_of_range(&err), ("bytes", i128::from(i64::MIN)));
+
+    // Too large for a `u32`.
+    let row = MockRowBuilder::new()
+        .column("hits", Type::INT8, i64::from(u32::MAX) + 1)
+        .column("bytes", Type::INT8, None::<i64>)
+        .build();
+    let err = Counter::from_row(row).unwrap_err();
+    assert_eq!(out_of_range(&err), ("hits", i128::from(u32::MAX) + 1));
+}
+
+#[test]
+fn writes_values_fitting_the_parameter_type() {
+    let mut buf = BytesMut::new();
+    PgU32(u32::MAX)
+        .to_sql_checked(&Type::INT8, &mut buf)
+        .unwrap();
+    assert_eq!(PgU32::from_sql(&Type::INT8, &buf).unwrap(), PgU32(u32::MAX));
+
+    let err = PgU32(u32::MAX)
+        .to_sql_checked(&Type::INT4, &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "4294967295 is out of range for int4");
+
+    let err = PgU64(u64::MAX)
+        .to_sql_checked(&Type::INT8, &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "18446744073709551615 is out of range for int8"
+    );
+}
+
+#[test]
+fn accepts_integer_types() {
+    for ty in [Type::INT2, Type::INT4, Type::INT8] {
+        assert!(<PgU32 as FromSql>::accepts(&ty), "{}", ty);
+        assert!(<PgU64 as ToSql>::accepts(&ty), "{}", ty);
+    }
+    assert!(!<PgU32 as FromSql>::accepts(&Type::OID));
+    assert!(!<PgU64 as ToSql>::accepts(&Type::NUMERIC));
+}
+
+#[test]
+fn non_zero_values() {
+    let row = MockRowBuilder::new()
+        .column("id", Type::INT8, 7i64)
+        .column("team_id", Type::INT2, -3i16)
+        .build();
+
+    let user = User::from_row(row).unwrap();
+    assert_eq!(user.id.into_inner().get(), 7);
+    assert_eq!(user.team_id.unwrap().get(), -3);
+}
+
+#[test]
+fn null_is_none_rather_than_zero_for_non_zero() {
+    let row = MockRowBuilder::new()
+        .column("id", Type::INT8, 7i64)
+        .column("team_id", Type::INT4, None::<i32>)
+        .build();
+
+    assert_eq!(User::from_row(row).unwrap().team_id, None);
+
+    let row = MockRowBuilder::new()
+        .column("id", Type::INT8, None::<i64>)
+        .column("team_id", Type::INT4, None::<i32>)
+        .build();
+    assert!(User::from_row(row).unwrap_err().is_unexpected_null());
+}
+
+#[test]
+fn zero_names_the_column() {
+    let row = MockRowBuilder::new()
+        .column("id", Type::INT8, 0i64)
+        .column("team_id", Type::INT4, None::<i32>)
+        .build();
+    let err = User::from_row(row).unwrap_err();
+    assert_eq!(out_of_range(&err), ("id", 0));
+    assert_eq!(
+        err.source().unwrap().to_string(),
+        "0 is out of range for NonZeroI64"
+    );
+
+    // `Option` fields still fail on zero.
+    let row = MockRowBuilder::new()
+        .column("id", Type::INT8, 1i64)
+        .column("team_id", Type::INT4, 0i32)
+        .build();
+    let err = User::from_row(row).unwrap_err();
+    assert_eq!(out_of_range(&err), ("team_id", 0));
+
+    // Too large for a `NonZeroI16`.
+    let err = PgNonZero::<NonZeroI16>::from_sql(&Type::INT4, &70_000i32.to_be_bytes())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "70000 is out of range for NonZeroI16");
+}
+
+#[test]
+fn non_zero_round_trips() {
+    let value = PgNonZero(NonZeroI32::new(-5).unwrap());
+
+    let mut buf = BytesMut::new();
+    value.to_sql_checked(&Type::INT2, &mut buf).unwrap();
+    assert_eq!(&buf[..], b"\xff\xfb");
+    assert_eq!(
+        PgNonZero::<NonZeroI32>::from_sql(&Type::INT2, &buf).unwrap(),
+        value
+    );
+
+    let err = PgNonZero(NonZeroI64::new(i64::MAX).unwrap())
+        .to_sql_checked(&Type::INT4, &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert!(err.is::<IntegerOutOfRange>());
+}
+
+#[test]
+fn option_of_non_zero_is_the_size_of_the_integer() {
+    assert_eq!(std::mem::size_of::<Option<PgNonZero<NonZeroI32>>>(), 4);
+    assert_eq!(std::mem::size_of::<Option<PgNonZero<NonZeroI64>>>(), 8);
+}
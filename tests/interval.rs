@@ -0,0 +1,197 @@
+//! Checks the binary encoding of `PgInterval` and `PgDuration` against the
+//! bytes sent by Postgres, and their conversions to durations.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::{IntervalError, InvalidDuration, PgDuration, PgInterval};
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::time::Duration;
+
+/// `interval_send('1 year 2 mons 3 days 04:05:06.5')`, as captured from
+/// Postgres 15 like the other intervals.
+const CAPTURED: &[u8] = b"\
+    \x00\x00\x00\x03\x6c\x93\x61\xa0\
+    \x00\x00\x00\x03\
+    \x00\x00\x00\x0e";
+
+/// `'-1 day'`.
+const NEGATIVE_DAY: &[u8] = b"\
+    \x00\x00\x00\x00\x00\x00\x00\x00\
+    \xff\xff\xff\xff\
+    \x00\x00\x00\x00";
+
+/// `'-00:00:01.5'`.
+const NEGATIVE_TIME: &[u8] = b"\
+    \xff\xff\xff\xff\xff\xe9\x1c\xa0\
+    \x00\x00\x00\x00\
+    \x00\x00\x00\x00";
+
+/// `'1 day -02:00:00'`, which is positive in total.
+const MIXED_SIGNS: &[u8] = b"\
+    \xff\xff\xff\xfe\x52\xd8\xb8\x00\
+    \x00\x00\x00\x01\
+    \x00\x00\x00\x00";
+
+fn expected() -> PgInterval {
+    PgInterval {
+        months: 14,
+        days: 3,
+        microseconds: 14_706_500_000,
+    }
+}
+
+fn decode(raw: &[u8]) -> PgInterval {
+    PgInterval::from_sql(&Type::INTERVAL, raw).unwrap()
+}
+
+#[test]
+fn decodes_captured_bytes() {
+    assert_eq!(decode(CAPTURED), expected());
+    assert_eq!(decode(CAPTURED).to_string(), "P1Y2M3DT4H5M6.5S");
+
+    assert_eq!(decode(NEGATIVE_DAY).to_string(), "P-1D");
+    assert_eq!(decode(NEGATIVE_TIME).to_string(), "PT-1.5S");
+    assert_eq!(decode(MIXED_SIGNS).to_string(), "P1DT-2H");
+    assert_eq!(PgInterval::default().to_string(), "PT0S");
+}
+
+#[test]
+fn round_trips() {
+    for raw in [CAPTURED, NEGATIVE_DAY, NEGATIVE_TIME, MIXED_SIGNS] {
+        let mut buf = BytesMut::new();
+        decode(raw)
+            .to_sql_checked(&Type::INTERVAL, &mut buf)
+            .unwrap();
+
+        assert_eq!(&buf[..], raw);
+    }
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    for len in [0, 8, 15] {
+        assert!(
+            PgInterval::from_sql(&Type::INTERVAL, &CAPTURED[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    let mut trailing = CAPTURED.to_vec();
+    trailing.push(0);
+    assert!(PgInterval::from_sql(&Type::INTERVAL, &trailing).is_err());
+}
+
+#[test]
+fn converts_to_durations() {
+    assert_eq!(
+        Duration::try_from(decode(MIXED_SIGNS)),
+        Ok(Duration::from_secs(22 * 3600))
+    );
+    assert_eq!(
+        PgInterval::try_from(Duration::from_millis(1500)),
+        Ok(PgInterval {
+            months: 0,
+            days: 0,
+            microseconds: 1_500_000,
+        })
+    );
+
+    assert_eq!(
+        Duration::try_from(decode(CAPTURED)),
+        Err(IntervalError::Months)
+    );
+    assert_eq!(
+        PgInterval::try_from(Duration::MAX),
+        Err(IntervalError::OutOfRange)
+    );
+}
+
+#[test]
+fn negative_intervals_fail_to_convert() {
+    assert_eq!(
+        Duration::try_from(decode(NEGATIVE_DAY)),
+        Err(IntervalError::Negative)
+    );
+    assert_eq!(
+        Duration::try_from(decode(NEGATIVE_TIME)),
+        Err(IntervalError::Negative)
+    );
+
+    let err = PgDuration::from_sql(&Type::INTERVAL, NEGATIVE_TIME).unwrap_err();
+    let err = err.downcast_ref::<InvalidDuration>().unwrap();
+    assert_eq!(err.interval(), decode(NEGATIVE_TIME));
+    assert_eq!(err.error(), IntervalError::Negative);
+    assert_eq!(
+        err.to_string(),
+        "interval PT-1.5S can't be converted to a Duration: interval is negative"
+    );
+    assert!(err.source().unwrap().is::<IntervalError>());
+}
+
+#[test]
+fn duration_round_trips() {
+    let duration = PgDuration::from_sql(&Type::INTERVAL, MIXED_SIGNS).unwrap();
+    assert_eq!(duration.into_inner(), Duration::from_secs(22 * 3600));
+
+    let mut buf = BytesMut::new();
+    duration.to_sql_checked(&Type::INTERVAL, &mut buf).unwrap();
+    assert_eq!(
+        PgInterval::from_sql(&Type::INTERVAL, &buf).unwrap(),
+        PgInterval {
+            months: 0,
+            days: 0,
+            microseconds: 22 * 3_600_000_000,
+        }
+    );
+
+    let mut buf = BytesMut::new();
+    assert!(PgDuration(Duration::MAX)
+        .to_sql_checked(&Type::INTERVAL, &mut buf)
+        .is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn converts_to_chrono_durations() {
+    assert_eq!(
+        chrono::Duration::try_from(decode(NEGATIVE_TIME)),
+        Ok(chrono::Duration::microseconds(-1_500_000))
+    );
+    assert_eq!(
+        chrono::Duration::try_from(decode(CAPTURED)),
+        Err(IntervalError::Months)
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn deserializes_postgres_style_text() {
+    let interval: PgInterval =
+        serde_json::from_str("\"1 year 2 mons 3 days 04:05:06.5\"").unwrap();
+    assert_eq!(interval, expected());
+
+    let interval: PgInterval = serde_json::from_str("\"-1 days -00:00:01.5\"").unwrap();
+    assert_eq!(
+        interval,
+        PgInterval {
+            months: 0,
+            days: -1,
+            microseconds: -1_500_000,
+        }
+    );
+
+    let err = serde_json::from_str::<PgDuration>("\"-00:00:01.5\"").unwrap_err();
+    assert!(err.to_string().contains("interval is negative"), "{}", err);
+    assert!(serde_json::from_str::<PgInterval>("\"1 fortnight\"").is_err());
+}
+
+#[test]
+fn accepts_interval() {
+    assert!(<PgInterval as FromSql>::accepts(&Type::INTERVAL));
+    assert!(<PgDuration as ToSql>::accepts(&Type::INTERVAL));
+    assert!(!<PgInterval as FromSql>::accepts(&Type::TIME));
+    assert!(!<PgDuration as FromSql>::accepts(&Type::INT8));
+}
@@ -0,0 +1,98 @@
+//! Checks the binary encoding of `PgLtree` and `PgLquery` against the bytes
+//! sent by Postgres.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::{PgLquery, PgLtree};
+use tokio_postgres::types::{FromSql, Kind, ToSql, Type};
+
+/// `ltree_send('Top.Science.Astronomy')`, as captured from Postgres 15.
+const CAPTURED: &[u8] = b"\x01Top.Science.Astronomy";
+
+/// `lquery_send('*.Science.*')`.
+const CAPTURED_LQUERY: &[u8] = b"\x01*.Science.*";
+
+fn extension_type(name: &str, oid: u32) -> Type {
+    Type::new(name.to_string(), oid, Kind::Simple, "public".to_string())
+}
+
+fn ltree_type() -> Type {
+    extension_type("ltree", 18991)
+}
+
+fn lquery_type() -> Type {
+    extension_type("lquery", 19045)
+}
+
+fn expected() -> PgLtree {
+    "Top.Science.Astronomy".parse().unwrap()
+}
+
+#[test]
+fn decodes_captured_bytes() {
+    let ltree = PgLtree::from_sql(&ltree_type(), CAPTURED).unwrap();
+
+    assert_eq!(ltree.labels(), ["Top", "Science", "Astronomy"]);
+    assert_eq!(ltree.depth(), 3);
+    assert_eq!(ltree.to_string(), "Top.Science.Astronomy");
+
+    // `ltree_send('')`.
+    let empty = PgLtree::from_sql(&ltree_type(), b"\x01").unwrap();
+    assert_eq!(empty, PgLtree::default());
+    assert_eq!(empty.parent(), None);
+
+    let lquery = PgLquery::from_sql(&lquery_type(), CAPTURED_LQUERY).unwrap();
+    assert_eq!(lquery, PgLquery("*.Science.*".to_string()));
+}
+
+#[test]
+fn round_trips() {
+    let mut buf = BytesMut::new();
+    expected().to_sql_checked(&ltree_type(), &mut buf).unwrap();
+    assert_eq!(&buf[..], CAPTURED);
+
+    let mut buf = BytesMut::new();
+    PgLquery::from("*.Science.*".to_string())
+        .to_sql_checked(&lquery_type(), &mut buf)
+        .unwrap();
+    assert_eq!(&buf[..], CAPTURED_LQUERY);
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    // No version, another version, and invalid UTF-8.
+    assert!(PgLtree::from_sql(&ltree_type(), b"").is_err());
+    assert!(PgLtree::from_sql(&ltree_type(), b"\x02Top").is_err());
+    assert!(PgLtree::from_sql(&ltree_type(), b"\x01\xff").is_err());
+    assert!(PgLquery::from_sql(&lquery_type(), b"\x00*").is_err());
+
+    // Empty labels.
+    assert!(PgLtree::from_sql(&ltree_type(), b"\x01Top..Astronomy").is_err());
+    assert!(PgLtree::from_sql(&ltree_type(), b"\x01Top.").is_err());
+}
+
+#[test]
+fn compares_paths() {
+    let science = "Top.Science".parse::<PgLtree>().unwrap();
+
+    assert_eq!(expected().parent(), Some(science.clone()));
+    assert!(science.is_ancestor_of(&expected()));
+    assert!(expected().is_descendant_of(&science));
+    assert!(science.is_ancestor_of(&science));
+    assert!(!expected().is_ancestor_of(&science));
+    assert!(PgLtree::default().is_ancestor_of(&science));
+
+    // Labels are compared whole, unlike their text.
+    let sci = "Top.Sci".parse::<PgLtree>().unwrap();
+    assert!(!sci.is_ancestor_of(&science));
+}
+
+#[test]
+fn accepts_types_by_name() {
+    assert!(<PgLtree as FromSql>::accepts(&ltree_type()));
+    assert!(<PgLtree as ToSql>::accepts(&extension_type("ltree", 1)));
+    assert!(!<PgLtree as FromSql>::accepts(&lquery_type()));
+    assert!(!<PgLtree as FromSql>::accepts(&Type::TEXT));
+
+    assert!(<PgLquery as FromSql>::accepts(&lquery_type()));
+    assert!(!<PgLquery as ToSql>::accepts(&ltree_type()));
+}
@@ -0,0 +1,100 @@
+//! Checks the binary encoding of `PgMacAddr` and `PgMacAddr8` against the
+//! bytes sent by Postgres.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::{PgMacAddr, PgMacAddr8};
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+/// `macaddr_send('08:00:2b:01:02:03')`, as captured from Postgres 15.
+const CAPTURED: &[u8] = b"\x08\x00\x2b\x01\x02\x03";
+
+/// `macaddr8_send('08:00:2b:01:02:03:04:05')`.
+const CAPTURED_8: &[u8] = b"\x08\x00\x2b\x01\x02\x03\x04\x05";
+
+/// `macaddr8_send('08:00:2b:01:02:03')`: Postgres stores 6 byte addresses
+/// as EUI-64, inserting `ff:fe`.
+const CAPTURED_8_FROM_6: &[u8] = b"\x08\x00\x2b\xff\xfe\x01\x02\x03";
+
+#[test]
+fn decodes_captured_bytes() {
+    let addr = PgMacAddr::from_sql(&Type::MACADDR, CAPTURED).unwrap();
+    assert_eq!(addr.octets(), [0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]);
+    assert_eq!(addr.to_string(), "08:00:2b:01:02:03");
+
+    let addr = PgMacAddr8::from_sql(&Type::MACADDR8, CAPTURED_8).unwrap();
+    assert_eq!(addr.to_string(), "08:00:2b:01:02:03:04:05");
+
+    let addr = PgMacAddr8::from_sql(&Type::MACADDR8, CAPTURED_8_FROM_6).unwrap();
+    assert_eq!(addr.to_string(), "08:00:2b:ff:fe:01:02:03");
+}
+
+#[test]
+fn round_trips() {
+    let mut buf = BytesMut::new();
+    PgMacAddr::from([0x08, 0x00, 0x2b, 0x01, 0x02, 0x03])
+        .to_sql_checked(&Type::MACADDR, &mut buf)
+        .unwrap();
+    assert_eq!(&buf[..], CAPTURED);
+
+    let mut buf = BytesMut::new();
+    "08:00:2b:01:02:03:04:05"
+        .parse::<PgMacAddr8>()
+        .unwrap()
+        .to_sql_checked(&Type::MACADDR8, &mut buf)
+        .unwrap();
+    assert_eq!(&buf[..], CAPTURED_8);
+}
+
+#[test]
+fn parses_colons_and_hyphens() {
+    let addr = PgMacAddr::from_sql(&Type::MACADDR, CAPTURED).unwrap();
+
+    assert_eq!("08:00:2b:01:02:03".parse(), Ok(addr));
+    assert_eq!("08-00-2B-01-02-03".parse(), Ok(addr));
+
+    for text in [
+        "",
+        "08:00:2b:01:02",
+        "08:00:2b:01:02:03:04",
+        "8:00:2b:01:02:03",
+        "08:00:2b:01:02:0g",
+    ] {
+        assert_eq!(
+            text.parse::<PgMacAddr>(),
+            Err(format!("invalid macaddr value \"{}\"", text))
+        );
+    }
+    assert_eq!(
+        "08:00:2b:01:02:03".parse::<PgMacAddr8>(),
+        Err("invalid macaddr8 value \"08:00:2b:01:02:03\"".to_string())
+    );
+}
+
+#[test]
+fn rejects_invalid_lengths() {
+    let err = PgMacAddr::from_sql(&Type::MACADDR, CAPTURED_8).unwrap_err();
+    assert_eq!(err.to_string(), "invalid macaddr length");
+
+    let err = PgMacAddr8::from_sql(&Type::MACADDR8, CAPTURED).unwrap_err();
+    assert_eq!(err.to_string(), "invalid macaddr8 length");
+
+    assert!(PgMacAddr::from_sql(&Type::MACADDR, b"").is_err());
+}
+
+#[test]
+fn accepts_its_own_type() {
+    assert!(<PgMacAddr as FromSql>::accepts(&Type::MACADDR));
+    assert!(<PgMacAddr8 as ToSql>::accepts(&Type::MACADDR8));
+    assert!(!<PgMacAddr as FromSql>::accepts(&Type::MACADDR8));
+    assert!(!<PgMacAddr8 as ToSql>::accepts(&Type::MACADDR));
+}
+
+#[cfg(feature = "eui48")]
+#[test]
+fn converts_eui48_addresses() {
+    let addr = PgMacAddr::from_sql(&Type::MACADDR, CAPTURED).unwrap();
+    let eui48 = eui48::MacAddress::from(addr);
+
+    assert_eq!(eui48.to_array(), addr.octets());
+    assert_eq!(PgMacAddr::from(eui48), addr);
+}
@@ -0,0 +1,73 @@
+use bytes::Bytes;
+use serde::Deserialize;
+use tokio_pg_mapper::{FromNotificationPayload, PostgresMapper};
+
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Settings {
+    pub theme: String,
+}
+
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "events", notification)]
+pub struct Event {
+    pub id: i64,
+    #[pg_mapper(rename = "Event Name", trim = "both")]
+    pub name: Cow<'static, str>,
+    pub payload: Option<Bytes>,
+    pub elapsed: Duration,
+    #[pg_mapper(epoch = "millis")]
+    pub created_at: SystemTime,
+    #[pg_mapper(default_on_null)]
+    pub attempts: i32,
+    #[pg_mapper(json_text)]
+    pub settings: Settings,
+    #[pg_mapper(json_text)]
+    pub embedded: Settings,
+}
+
+#[test]
+fn conversion_attributes_are_applied_to_payloads() {
+    let payload = r#"{
+        "id": 1,
+        "Event Name": "  signup  ",
+        "payload": "\\x00ff",
+        "elapsed": "1 day 02:03:04.5",
+        "created_at": 1500,
+        "attempts": null,
+        "settings": "{\"theme\": \"dark\"}",
+        "embedded": {"theme": "light"}
+    }"#;
+
+    let event = Event::from_notification_payload(payload).unwrap();
+
+    assert_eq!(event.id, 1);
+    assert_eq!(event.name, "signup");
+    assert_eq!(event.payload, Some(Bytes::from_static(&[0x00, 0xff])));
+    assert_eq!(event.elapsed, Duration::from_micros(93_784_500_000));
+    assert_eq!(event.created_at, UNIX_EPOCH + Duration::from_millis(1500));
+    assert_eq!(event.attempts, 0);
+    assert_eq!(event.settings.theme, "dark");
+    assert_eq!(event.embedded.theme, "light");
+}
+
+#[test]
+fn intervals_with_months_are_rejected() {
+    let payload = r#"{
+        "id": 1,
+        "Event Name": "signup",
+        "payload": null,
+        "elapsed": "1 mon",
+        "created_at": 0,
+        "attempts": 3,
+        "settings": "{\"theme\": \"dark\"}",
+        "embedded": {"theme": "light"}
+    }"#;
+
+    let err = Event::from_notification_payload(payload).err().unwrap();
+
+    assert!(err.is_conversion(), "{:?}", err);
+    assert!(err.to_string().contains("elapsed"), "{}", err);
+}
@@ -0,0 +1,186 @@
+//! Checks the binary encoding of `PgNumeric` against the bytes sent by
+//! Postgres.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::{NumericError, PgNumeric};
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+/// Values and their `numeric_send` bytes, as captured from Postgres 15.
+const CAPTURED: &[(&str, &[u8])] = &[
+    // A weight of -2: the first digit is 1234 * 10000^-2.
+    ("0.00001234", b"\x00\x01\xff\xfe\x00\x00\x00\x08\x04\xd2"),
+    // `12.50::numeric(10, 2)`: the scale keeps the trailing zero.
+    ("12.50", b"\x00\x02\x00\x00\x00\x00\x00\x02\x00\x0c\x13\x88"),
+    (
+        "-1234.5678",
+        b"\x00\x02\x00\x00\x40\x00\x00\x04\x04\xd2\x16\x2e",
+    ),
+    (
+        "123456789.000",
+        b"\x00\x03\x00\x02\x00\x00\x00\x03\x00\x01\x09\x29\x1a\x85",
+    ),
+    ("0", b"\x00\x00\x00\x00\x00\x00\x00\x00"),
+];
+
+const NAN: &[u8] = b"\x00\x00\x00\x00\xc0\x00\x00\x00";
+const INFINITY: &[u8] = b"\x00\x00\x00\x00\xd0\x00\x00\x20";
+const NEGATIVE_INFINITY: &[u8] = b"\x00\x00\x00\x00\xf0\x00\x00\x20";
+
+/// `1e-30::numeric`, with a scale of 30.
+const TINY: &[u8] = b"\x00\x01\xff\xf8\x00\x00\x00\x1e\x00\x64";
+
+fn decode(raw: &[u8]) -> PgNumeric {
+    PgNumeric::from_sql(&Type::NUMERIC, raw).unwrap()
+}
+
+fn decode_error(raw: &[u8]) -> NumericError {
+    let err = PgNumeric::from_sql(&Type::NUMERIC, raw).unwrap_err();
+
+    err.downcast_ref::<NumericError>().unwrap().clone()
+}
+
+#[test]
+fn decodes_captured_bytes() {
+    for &(text, raw) in CAPTURED {
+        let numeric = decode(raw);
+
+        assert_eq!(numeric.to_string(), text);
+        assert_eq!(numeric, text.parse().unwrap(), "{}", text);
+    }
+
+    assert_eq!(decode(CAPTURED[1].1).scale(), 2);
+    assert!(decode(CAPTURED[2].1).is_negative());
+    assert!(decode(CAPTURED[4].1).is_zero());
+}
+
+#[test]
+fn round_trips() {
+    for &(text, raw) in CAPTURED {
+        let numeric = text.parse::<PgNumeric>().unwrap();
+        let mut buf = BytesMut::new();
+        numeric.to_sql_checked(&Type::NUMERIC, &mut buf).unwrap();
+
+        assert_eq!(&buf[..], raw, "{}", text);
+        assert_eq!(decode(&buf).to_string(), text);
+    }
+
+    assert_eq!(decode(TINY).to_string(), format!("0.{:0>30}", 1));
+}
+
+#[test]
+fn parses_signs_and_leading_zeros() {
+    let numeric = "+0012.500".parse::<PgNumeric>().unwrap();
+    assert_eq!(numeric.to_string(), "12.500");
+
+    let numeric = "-.5".parse::<PgNumeric>().unwrap();
+    assert_eq!(numeric.to_string(), "-0.5");
+
+    let numeric = "-0.00".parse::<PgNumeric>().unwrap();
+    assert_eq!(numeric.to_string(), "0.00");
+    assert!(!numeric.is_negative());
+
+    assert_eq!(format!("{:>8}", decode(CAPTURED[1].1)), "   12.50");
+}
+
+#[test]
+fn rejects_invalid_text() {
+    for text in ["", "-", ".", "1e5", "1.2.3", "NaN", " 1", "1,5"] {
+        assert_eq!(
+            text.parse::<PgNumeric>(),
+            Err(NumericError::InvalidText(text.to_string())),
+            "{:?}",
+            text
+        );
+    }
+}
+
+#[test]
+fn special_values_fail_to_decode() {
+    assert_eq!(decode_error(NAN), NumericError::NaN);
+    assert_eq!(decode_error(INFINITY), NumericError::Infinite);
+    assert_eq!(decode_error(NEGATIVE_INFINITY), NumericError::Infinite);
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    let raw = CAPTURED[2].1;
+
+    for len in [0, 7, 9, raw.len() - 1] {
+        assert!(
+            PgNumeric::from_sql(&Type::NUMERIC, &raw[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    // An unknown sign, and a digit of 10000.
+    assert!(
+        PgNumeric::from_sql(&Type::NUMERIC, b"\x00\x00\x00\x00\x80\x00\x00\x00")
+            .is_err()
+    );
+    assert!(PgNumeric::from_sql(
+        &Type::NUMERIC,
+        b"\x00\x01\x00\x00\x00\x00\x00\x00\x27\x10"
+    )
+    .is_err());
+}
+
+#[test]
+fn accepts_numeric() {
+    assert!(<PgNumeric as FromSql>::accepts(&Type::NUMERIC));
+    assert!(<PgNumeric as ToSql>::accepts(&Type::NUMERIC));
+    assert!(!<PgNumeric as FromSql>::accepts(&Type::FLOAT8));
+    assert!(!<PgNumeric as ToSql>::accepts(&Type::TEXT));
+}
+
+#[cfg(feature = "rust_decimal")]
+mod to_rust_decimal {
+    use super::*;
+
+    use rust_decimal::Decimal;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn converts_captured_values() {
+        for &(text, raw) in CAPTURED {
+            let decimal = Decimal::try_from(decode(raw)).unwrap();
+
+            assert_eq!(decimal.to_string(), text);
+            assert_eq!(PgNumeric::from(decimal), decode(raw));
+        }
+    }
+
+    #[test]
+    fn scale_over_28_is_out_of_range() {
+        assert_eq!(
+            Decimal::try_from(decode(TINY)),
+            Err(NumericError::OutOfRange)
+        );
+
+        let too_large = "79228162514264337593543950336"
+            .parse::<PgNumeric>()
+            .unwrap();
+        assert_eq!(Decimal::try_from(too_large), Err(NumericError::OutOfRange));
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+mod to_bigdecimal {
+    use super::*;
+
+    use bigdecimal::BigDecimal;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn converts_captured_values() {
+        for &(text, raw) in CAPTURED {
+            let decimal = BigDecimal::from(decode(raw));
+
+            assert_eq!(decimal.to_plain_string(), text);
+            assert_eq!(PgNumeric::try_from(decimal).unwrap(), decode(raw));
+        }
+
+        let decimal = BigDecimal::from(decode(TINY));
+        assert_eq!(PgNumeric::try_from(decimal).unwrap(), decode(TINY));
+    }
+}
@@ -0,0 +1,40 @@
+use tokio_pg_mapper::{PostgresMapper, PostgresPatch, TokioPostgresPatch};
+
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "users")]
+pub struct User {
+    pub id: i32,
+    pub name: String,
+    #[pg_mapper(rename = "E-mail")]
+    pub email: Option<String>,
+}
+
+#[derive(Default, PostgresPatch)]
+#[pg_patch(for = "User")]
+pub struct UserPatch {
+    pub name: Option<String>,
+    #[pg_mapper(rename = "E-mail")]
+    pub email: Option<Option<String>>,
+}
+
+#[test]
+fn empty_patch_has_no_clause() {
+    let patch = UserPatch::default();
+    let (clause, params) = patch.set_clause(1);
+
+    assert_eq!(clause, "");
+    assert!(params.is_empty());
+}
+
+#[test]
+fn renamed_fields_assign_their_column() {
+    let patch = UserPatch {
+        name: Some("Ferris".to_string()),
+        email: Some(None),
+    };
+    let (clause, params) = patch.set_clause(2);
+
+    assert_eq!(clause, "name = $2, \"E-mail\" = $3");
+    assert_eq!(params.len(), 2);
+    assert_eq!(UserPatch::sql_table(), "users");
+}
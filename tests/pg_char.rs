@@ -0,0 +1,109 @@
+//! Checks the mapping of single-character columns to `PgChar` fields.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{Error, FromTokioPostgresRow, InvalidChar, PgChar, PostgresMapper};
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+use std::error::Error as StdError;
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "orders")]
+pub struct Order {
+    pub status: PgChar,
+    pub grade: Option<PgChar>,
+}
+
+/// Get the column named by a conversion error, and the text of its
+/// `InvalidChar` source.
+fn invalid_char(err: &Error) -> (&str, &str) {
+    let column = match *err {
+        Error::ColumnConversion { ref column, .. } => column,
+        ref err => panic!("expected a conversion error, got {:?}", err),
+    };
+    let source = err.source().unwrap().downcast_ref::<InvalidChar>().unwrap();
+
+    (column, source.text())
+}
+
+#[test]
+fn single_characters() {
+    let row = MockRowBuilder::new()
+        .column("status", Type::CHAR, 'a' as i8)
+        .column("grade", Type::BPCHAR, "é")
+        .build();
+
+    assert_eq!(
+        Order::from_row(row).unwrap(),
+        Order {
+            status: PgChar('a'),
+            grade: Some(PgChar('é')),
+        }
+    );
+}
+
+#[test]
+fn null_is_none() {
+    let row = MockRowBuilder::new()
+        .column("status", Type::TEXT, "a")
+        .column("grade", Type::BPCHAR, None::<&str>)
+        .build();
+
+    assert_eq!(Order::from_row(row).unwrap().grade, None);
+
+    let row = MockRowBuilder::new()
+        .column("status", Type::TEXT, None::<&str>)
+        .column("grade", Type::BPCHAR, None::<&str>)
+        .build();
+    assert!(Order::from_row(row).unwrap_err().is_unexpected_null());
+}
+
+#[test]
+fn multiple_characters_name_the_column() {
+    let row = MockRowBuilder::new()
+        .column("status", Type::TEXT, "a")
+        .column("grade", Type::VARCHAR, "ab")
+        .build();
+
+    let err = Order::from_row(row).unwrap_err();
+    assert_eq!(invalid_char(&err), ("grade", "ab"));
+    assert_eq!(
+        err.source().unwrap().to_string(),
+        "expected a single character, got \"ab\""
+    );
+
+    let row = MockRowBuilder::new()
+        .column("status", Type::TEXT, "")
+        .column("grade", Type::VARCHAR, None::<&str>)
+        .build();
+    assert_eq!(
+        invalid_char(&Order::from_row(row).unwrap_err()),
+        ("status", "")
+    );
+}
+
+#[test]
+fn only_ascii_fits_in_a_char() {
+    let mut buf = BytesMut::new();
+    PgChar('a').to_sql_checked(&Type::CHAR, &mut buf).unwrap();
+    assert_eq!(&buf[..], b"a");
+
+    let err = PgChar('é')
+        .to_sql_checked(&Type::CHAR, &mut BytesMut::new())
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "\"é\" doesn't fit in a \"char\"");
+
+    let mut buf = BytesMut::new();
+    PgChar('é').to_sql_checked(&Type::TEXT, &mut buf).unwrap();
+    assert_eq!(PgChar::from_sql(&Type::TEXT, &buf).unwrap(), PgChar('é'));
+}
+
+#[test]
+fn accepts_text_types() {
+    for ty in [Type::CHAR, Type::BPCHAR, Type::VARCHAR, Type::TEXT] {
+        assert!(<PgChar as FromSql>::accepts(&ty), "{}", ty);
+        assert!(<PgChar as ToSql>::accepts(&ty), "{}", ty);
+    }
+    assert!(!<PgChar as FromSql>::accepts(&Type::INT4));
+}
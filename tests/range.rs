@@ -0,0 +1,133 @@
+//! Checks the binary encoding of `PgRange` against the bytes sent by
+//! Postgres.
+
+use bytes::BytesMut;
+use tokio_pg_mapper::PgRange;
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+use std::ops::Bound;
+
+/// `range_send('[1,3]'::int4range)`, normalized to `[1,4)`, as captured from
+/// Postgres 15 like the other ranges.
+const BOUNDED: &[u8] = b"\x02\
+    \x00\x00\x00\x04\x00\x00\x00\x01\
+    \x00\x00\x00\x04\x00\x00\x00\x04";
+
+/// `'empty'::int4range`.
+const EMPTY: &[u8] = b"\x01";
+
+/// `'(,5)'::int4range`.
+const LOWER_INFINITE: &[u8] = b"\x08\x00\x00\x00\x04\x00\x00\x00\x05";
+
+/// `'[2,)'::int8range`.
+const UPPER_INFINITE: &[u8] = b"\x12\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00\x00\x02";
+
+/// `'(,)'::int4range`.
+const UNBOUNDED: &[u8] = b"\x18";
+
+fn decode<T: for<'a> FromSql<'a>>(ty: &Type, raw: &[u8]) -> PgRange<T> {
+    PgRange::from_sql(ty, raw).unwrap()
+}
+
+fn encode<T: ToSql>(ty: &Type, range: &PgRange<T>) -> BytesMut {
+    let mut buf = BytesMut::new();
+    range.to_sql_checked(ty, &mut buf).unwrap();
+
+    buf
+}
+
+#[test]
+fn decodes_captured_bytes() {
+    let range = decode::<i32>(&Type::INT4_RANGE, BOUNDED);
+    assert_eq!(range, PgRange::from(1..4));
+    assert_eq!(range.lower(), Some(Bound::Included(&1)));
+    assert_eq!(range.upper(), Some(Bound::Excluded(&4)));
+
+    let range = decode::<i32>(&Type::INT4_RANGE, EMPTY);
+    assert!(range.is_empty());
+    assert_eq!(range.lower(), None);
+    assert_eq!(range.upper(), None);
+
+    assert_eq!(
+        decode::<i32>(&Type::INT4_RANGE, LOWER_INFINITE),
+        PgRange::new(Bound::Unbounded, Bound::Excluded(5))
+    );
+    assert_eq!(
+        decode::<i64>(&Type::INT8_RANGE, UPPER_INFINITE),
+        PgRange::from(2..)
+    );
+    assert_eq!(
+        decode::<i32>(&Type::INT4_RANGE, UNBOUNDED),
+        PgRange::new(Bound::Unbounded, Bound::Unbounded)
+    );
+}
+
+#[test]
+fn round_trips() {
+    assert_eq!(
+        &encode(&Type::INT4_RANGE, &PgRange::from(1..4))[..],
+        BOUNDED
+    );
+    assert_eq!(
+        &encode(&Type::INT4_RANGE, &PgRange::<i32>::Empty)[..],
+        EMPTY
+    );
+    assert_eq!(
+        &encode(
+            &Type::INT4_RANGE,
+            &PgRange::new(Bound::Unbounded, Bound::Excluded(5))
+        )[..],
+        LOWER_INFINITE
+    );
+    assert_eq!(
+        &encode(&Type::INT8_RANGE, &PgRange::from(2i64..))[..],
+        UPPER_INFINITE
+    );
+    assert_eq!(
+        &encode(
+            &Type::INT4_RANGE,
+            &PgRange::<i32>::new(Bound::Unbounded, Bound::Unbounded)
+        )[..],
+        UNBOUNDED
+    );
+
+    // Both bounds inclusive, as sent for continuous ranges.
+    let range = PgRange::from(1..=3);
+    let buf = encode(&Type::INT4_RANGE, &range);
+    assert_eq!(buf[0], 0x06);
+    assert_eq!(decode::<i32>(&Type::INT4_RANGE, &buf), range);
+}
+
+#[test]
+fn empty_flag_ignores_the_other_flags() {
+    assert!(decode::<i32>(&Type::INT4_RANGE, b"\x1f").is_empty());
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    for len in [0, 1, 4, 8, BOUNDED.len() - 1] {
+        assert!(
+            PgRange::<i32>::from_sql(&Type::INT4_RANGE, &BOUNDED[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    let mut trailing = BOUNDED.to_vec();
+    trailing.push(0);
+    assert!(PgRange::<i32>::from_sql(&Type::INT4_RANGE, &trailing).is_err());
+
+    // A NULL lower bound.
+    assert!(
+        PgRange::<i32>::from_sql(&Type::INT4_RANGE, b"\x10\xff\xff\xff\xff").is_err()
+    );
+}
+
+#[test]
+fn accepts_ranges_of_the_bound_type() {
+    assert!(<PgRange<i32> as FromSql>::accepts(&Type::INT4_RANGE));
+    assert!(<PgRange<i64> as ToSql>::accepts(&Type::INT8_RANGE));
+    assert!(!<PgRange<i32> as FromSql>::accepts(&Type::INT8_RANGE));
+    assert!(!<PgRange<i32> as FromSql>::accepts(&Type::INT4));
+    assert!(!<PgRange<i32> as ToSql>::accepts(&Type::INT4_ARRAY));
+}
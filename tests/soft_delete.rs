@@ -0,0 +1,155 @@
+//! Checks that the derived `SELECT` statements leave out the rows whose
+//! `soft_delete` column is set.
+
+use tokio_pg_mapper::{
+    quote_identifier, FromTokioPostgresRow, PostgresMapper, TokioPostgresPrimaryKey,
+};
+
+#[cfg(feature = "client")]
+mod common;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "posts")]
+#[cfg_attr(feature = "client", pg_mapper(crud))]
+pub struct Post {
+    #[pg_mapper(primary_key)]
+    pub id: i64,
+    #[cfg_attr(feature = "client", pg_mapper(unique))]
+    pub slug: String,
+    #[pg_mapper(soft_delete, rename = "Deleted At")]
+    pub deleted_at: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "tags")]
+pub struct Tag {
+    #[pg_mapper(primary_key)]
+    pub id: i64,
+}
+
+const FIELDS: &str = "SELECT  id ,  slug ,  \"Deleted At\"  FROM posts";
+
+#[test]
+fn soft_delete_column_is_the_annotated_one() {
+    assert_eq!(Post::soft_delete_column(), Some("Deleted At"));
+    assert_eq!(Tag::soft_delete_column(), None);
+}
+
+#[test]
+fn pagination_leaves_out_deleted_rows() {
+    assert_eq!(
+        Post::select_page_stmt(),
+        format!(
+            "{} WHERE \"Deleted At\" IS NULL ORDER BY id LIMIT $1 OFFSET $2",
+            FIELDS
+        )
+    );
+    assert_eq!(
+        Post::select_after_stmt(),
+        format!(
+            "{} WHERE id > $1 AND \"Deleted At\" IS NULL ORDER BY id LIMIT $2",
+            FIELDS
+        )
+    );
+}
+
+#[test]
+fn selects_leave_out_deleted_rows() {
+    assert_eq!(
+        Post::select_all_stmt(),
+        format!("{} WHERE \"Deleted At\" IS NULL ORDER BY id", FIELDS)
+    );
+    assert_eq!(
+        Post::select_by_primary_key_stmt(),
+        format!("{} WHERE id = $1 AND \"Deleted At\" IS NULL", FIELDS)
+    );
+}
+
+#[test]
+fn select_constant_leaves_out_deleted_rows() {
+    assert_eq!(
+        Post::SELECT,
+        format!(
+            "SELECT {} FROM {} WHERE {}.{} IS NULL",
+            Post::sql_table_fields_trimmed(),
+            Post::sql_table(),
+            Post::sql_table(),
+            quote_identifier(Post::soft_delete_column().unwrap()),
+        )
+    );
+    assert_eq!(
+        Post::SELECT,
+        "SELECT posts.id, posts.slug, posts.\"Deleted At\" FROM posts \
+         WHERE posts.\"Deleted At\" IS NULL"
+    );
+    assert_eq!(Tag::SELECT, "SELECT tags.id FROM tags");
+}
+
+#[test]
+fn statements_without_soft_delete_column_are_unfiltered() {
+    assert_eq!(
+        Tag::select_page_stmt(),
+        "SELECT  id  FROM tags ORDER BY id LIMIT $1 OFFSET $2"
+    );
+    assert_eq!(
+        Tag::select_after_stmt(),
+        "SELECT  id  FROM tags WHERE id > $1 ORDER BY id LIMIT $2"
+    );
+}
+
+#[cfg(feature = "client")]
+mod client {
+    use super::common::connect;
+    use super::Post;
+
+    use tokio_pg_mapper::{ClientExt, TokioPostgresCrud, TokioPostgresPrimaryKey};
+
+    fn slugs(posts: &[Post]) -> Vec<&str> {
+        posts.iter().map(|post| post.slug.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn queries_leave_out_deleted_rows() {
+        let client = match connect().await {
+            Some(client) => client,
+            None => return,
+        };
+        client
+            .batch_execute(
+                "CREATE TEMPORARY TABLE posts (
+                     id INT8 PRIMARY KEY,
+                     slug TEXT NOT NULL,
+                     \"Deleted At\" TEXT
+                 );
+                 INSERT INTO posts VALUES
+                     (1, 'first', NULL), (2, 'second', 'today'), (3, 'third', NULL);",
+            )
+            .await
+            .unwrap();
+
+        let page: Vec<Post> = client
+            .query_as(Post::select_page_stmt().as_str(), &[&10i64, &0i64])
+            .await
+            .unwrap();
+        assert_eq!(slugs(&page), ["first", "third"]);
+
+        let after: Vec<Post> = client
+            .query_as(Post::select_after_stmt().as_str(), &[&1i64, &10i64])
+            .await
+            .unwrap();
+        assert_eq!(slugs(&after), ["third"]);
+
+        assert_eq!(
+            slugs(&Post::list_all(&client).await.unwrap()),
+            ["first", "third"]
+        );
+        assert_eq!(Post::find(&client, &2).await.unwrap(), None);
+        assert_eq!(Post::find_by_slug(&client, "second").await.unwrap(), None);
+
+        let loaded = Post::load_many(&client, &[2, 3]).await.unwrap();
+        assert_eq!(loaded[0], None);
+        assert_eq!(loaded[1].as_ref().map(|post| post.id), Some(3));
+    }
+}
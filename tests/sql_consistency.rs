@@ -0,0 +1,156 @@
+//! Checks that every generated SQL string spells the mapped columns and the
+//! table the same way, whatever the quoting they need.
+
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper, TokioPostgresInsert};
+
+#[cfg(feature = "client")]
+use tokio_pg_mapper::TokioPostgresCrud;
+
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "user", insert)]
+#[cfg_attr(feature = "client", pg_mapper(crud))]
+pub struct User {
+    #[pg_mapper(primary_key, readonly)]
+    pub id: i64,
+    #[pg_mapper(rename = "Display Name")]
+    pub display_name: String,
+    #[pg_mapper(rename = "order")]
+    pub position: i32,
+    #[pg_mapper(rename = "say \"hi\"")]
+    pub greeting: Option<String>,
+    pub email: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "auth.Accounts")]
+pub struct Account {
+    pub id: i64,
+}
+
+const QUOTED: &[&str] = &[
+    "id",
+    "\"Display Name\"",
+    "\"order\"",
+    "\"say \"\"hi\"\"\"",
+    "email",
+];
+
+fn split(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn sql_fields_quote_every_column() {
+    assert_eq!(split(&User::sql_fields()), QUOTED);
+    assert_eq!(split(&User::sql_fields_trimmed()), QUOTED);
+}
+
+#[test]
+fn sql_table_fields_qualify_the_spelling_of_sql_fields() {
+    let qualified = QUOTED
+        .iter()
+        .map(|column| format!("\"user\".{}", column))
+        .collect::<Vec<_>>();
+
+    assert_eq!(split(&User::sql_table_fields()), qualified);
+    assert_eq!(split(&User::sql_table_fields_trimmed()), qualified);
+}
+
+#[test]
+fn table_names_are_quoted_by_part() {
+    assert_eq!(User::sql_table(), "\"user\"");
+    assert_eq!(User::sql_table_trimmed(), "\"user\"");
+    assert_eq!(Account::sql_table(), "auth.\"Accounts\"");
+}
+
+#[test]
+fn select_constant_uses_the_same_spelling() {
+    assert_eq!(
+        User::SELECT,
+        format!(
+            "SELECT {} FROM {}",
+            User::sql_table_fields_trimmed(),
+            User::sql_table()
+        )
+    );
+    assert_eq!(
+        Account::SELECT,
+        format!(
+            "SELECT {} FROM {}",
+            Account::sql_table_fields_trimmed(),
+            Account::sql_table()
+        )
+    );
+    assert_eq!(
+        Account::SELECT,
+        "SELECT auth.\"Accounts\".id FROM auth.\"Accounts\""
+    );
+}
+
+#[test]
+fn insert_columns_are_a_subset_with_the_same_spelling() {
+    for column in User::insert_columns() {
+        assert!(User::columns().contains(column), "{}", column);
+    }
+    assert!(!User::insert_columns().contains(&"id"));
+
+    assert_eq!(
+        User::insert_stmt(1),
+        format!(
+            "INSERT INTO \"user\" ({}) VALUES ($1, $2, $3, $4)",
+            QUOTED[1..].join(", ")
+        )
+    );
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn update_columns_are_a_subset_with_the_same_spelling() {
+    for column in User::update_columns() {
+        assert!(User::columns().contains(column), "{}", column);
+    }
+    assert!(!User::update_columns().contains(&"id"));
+
+    let assignments = QUOTED[1..]
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| format!("{} = ${}", column, idx + 1))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        User::update_stmt(),
+        format!(
+            "UPDATE \"user\" SET {} WHERE id = $5",
+            assignments.join(", ")
+        )
+    );
+}
+
+#[cfg(feature = "client")]
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "documents", crud)]
+pub struct Document {
+    #[pg_mapper(primary_key)]
+    pub id: i64,
+    #[pg_mapper(rename = "Body")]
+    pub body: String,
+    #[pg_mapper(version, rename = "order")]
+    pub version: i32,
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn update_stmt_checks_and_increments_the_version_column() {
+    assert_eq!(Document::version_column(), Some("order"));
+    assert_eq!(Document::update_columns(), &["Body"]);
+    assert_eq!(
+        Document::update_stmt(),
+        "UPDATE documents SET \"Body\" = $1, \"order\" = \"order\" + 1 \
+         WHERE id = $2 AND \"order\" = $3"
+    );
+}
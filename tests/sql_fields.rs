@@ -0,0 +1,117 @@
+//! Pins the exact strings, padding included, of the column and table lists
+//! of `FromTokioPostgresRow`.
+
+use tokio_pg_mapper::{Error, FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::Row;
+
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "user")]
+pub struct User {
+    pub id: i64,
+    pub email: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "Profiles")]
+pub struct Profile {
+    pub user_id: i64,
+    #[pg_mapper(rename = "displayName")]
+    pub display_name: String,
+    #[pg_mapper(rename = "select")]
+    pub choice: Option<String>,
+    #[pg_mapper(rename = "a \"b\"")]
+    pub quoted: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "audit.events")]
+pub struct Event {
+    pub id: i64,
+}
+
+/// Implemented by hand with only the methods which used to be required.
+pub struct Legacy;
+
+impl FromTokioPostgresRow for Legacy {
+    fn from_row(_row: Row) -> Result<Self, Error> {
+        Ok(Legacy)
+    }
+
+    fn from_row_ref(_row: &Row) -> Result<Self, Error> {
+        Ok(Legacy)
+    }
+
+    fn sql_table() -> String {
+        " legacy ".to_string()
+    }
+
+    fn sql_fields() -> String {
+        " id ".to_string()
+    }
+
+    fn sql_table_fields() -> String {
+        " legacy.id ".to_string()
+    }
+}
+
+#[test]
+fn plain_columns_of_a_reserved_table() {
+    assert_eq!(User::sql_table(), "\"user\"");
+    assert_eq!(User::sql_fields(), " id ,  email ");
+    assert_eq!(User::sql_table_fields(), " \"user\".id ,  \"user\".email ");
+
+    assert_eq!(User::sql_table_trimmed(), "\"user\"");
+    assert_eq!(User::sql_fields_trimmed(), "id, email");
+    assert_eq!(
+        User::sql_table_fields_trimmed(),
+        "\"user\".id, \"user\".email"
+    );
+}
+
+#[test]
+fn renamed_and_quoted_columns() {
+    assert_eq!(Profile::sql_table(), "\"Profiles\"");
+    assert_eq!(
+        Profile::sql_fields(),
+        " user_id ,  \"displayName\" ,  \"select\" ,  \"a \"\"b\"\"\" "
+    );
+    assert_eq!(
+        Profile::sql_table_fields(),
+        " \"Profiles\".user_id ,  \"Profiles\".\"displayName\" ,  \
+         \"Profiles\".\"select\" ,  \"Profiles\".\"a \"\"b\"\"\" "
+    );
+
+    assert_eq!(Profile::sql_table_trimmed(), "\"Profiles\"");
+    assert_eq!(
+        Profile::sql_fields_trimmed(),
+        "user_id, \"displayName\", \"select\", \"a \"\"b\"\"\""
+    );
+    assert_eq!(
+        Profile::sql_table_fields_trimmed(),
+        "\"Profiles\".user_id, \"Profiles\".\"displayName\", \
+         \"Profiles\".\"select\", \"Profiles\".\"a \"\"b\"\"\""
+    );
+}
+
+#[test]
+fn schema_qualified_table() {
+    assert_eq!(Event::sql_table(), "audit.events");
+    assert_eq!(Event::sql_fields(), " id ");
+    assert_eq!(Event::sql_table_fields(), " audit.events.id ");
+
+    assert_eq!(Event::sql_table_trimmed(), "audit.events");
+    assert_eq!(Event::sql_fields_trimmed(), "id");
+    assert_eq!(Event::sql_table_fields_trimmed(), "audit.events.id");
+}
+
+#[test]
+fn hand_written_mapping_without_columns() {
+    assert_eq!(Legacy::sql_fields(), " id ");
+    assert_eq!(Legacy::sql_table_fields(), " legacy.id ");
+
+    assert!(Legacy::columns().is_empty());
+    assert!(!Legacy::has_column("id"));
+}
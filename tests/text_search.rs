@@ -0,0 +1,132 @@
+//! Checks the decoding of `PgTsVector` and `PgTsQuery` against the bytes
+//! sent by Postgres.
+
+use tokio_pg_mapper::{PgTsQuery, PgTsVector, TsLexeme, TsWeight};
+use tokio_postgres::types::{FromSql, Type};
+
+/// `tsvectorsend('fat:2A rat:3 cat')`, as captured from Postgres 15 like
+/// the other values.
+const CAPTURED: &[u8] = b"\
+    \x00\x00\x00\x03\
+    cat\x00\x00\x00\
+    fat\x00\x00\x01\xc0\x02\
+    rat\x00\x00\x01\x00\x03";
+
+/// `$$'it''s':1 'a\\b':2$$::tsvector`.
+const CAPTURED_QUOTED: &[u8] = b"\
+    \x00\x00\x00\x02\
+    a\\b\x00\x00\x01\x00\x02\
+    it's\x00\x00\x01\x00\x01";
+
+/// `'fat & (rat:*B | !cat) <2> dog'::tsquery`, in prefix order with the
+/// right operand first.
+const CAPTURED_QUERY: &[u8] = b"\
+    \x00\x00\x00\x08\
+    \x02\x02\
+    \x02\x04\x00\x02\
+    \x01\x00\x00dog\x00\
+    \x02\x03\
+    \x02\x01\
+    \x01\x00\x00cat\x00\
+    \x01\x04\x01rat\x00\
+    \x01\x00\x00fat\x00";
+
+/// `''::tsvector` and `''::tsquery`.
+const EMPTY: &[u8] = b"\x00\x00\x00\x00";
+
+fn lexeme(word: &str, positions: &[(u16, TsWeight)]) -> TsLexeme {
+    TsLexeme {
+        word: word.to_string(),
+        positions: positions.to_vec(),
+    }
+}
+
+#[test]
+fn decodes_captured_vectors() {
+    let vector = PgTsVector::from_sql(&Type::TS_VECTOR, CAPTURED).unwrap();
+
+    assert_eq!(
+        vector.lexemes,
+        [
+            lexeme("cat", &[]),
+            lexeme("fat", &[(2, TsWeight::A)]),
+            lexeme("rat", &[(3, TsWeight::D)]),
+        ]
+    );
+    assert_eq!(vector.to_string(), "'cat' 'fat':2A 'rat':3");
+
+    let vector = PgTsVector::from_sql(&Type::TS_VECTOR, CAPTURED_QUOTED).unwrap();
+    assert_eq!(vector.to_string(), "'a\\\\b':2 'it''s':1");
+
+    let vector = PgTsVector::from_sql(&Type::TS_VECTOR, EMPTY).unwrap();
+    assert_eq!(vector, PgTsVector::default());
+    assert_eq!(vector.to_string(), "");
+}
+
+#[test]
+fn decodes_captured_queries() {
+    let query = PgTsQuery::from_sql(&Type::TSQUERY, CAPTURED_QUERY).unwrap();
+    assert_eq!(query.to_string(), "'fat' & (('rat':*B | !'cat') <2> 'dog')");
+
+    let query = PgTsQuery::from_sql(&Type::TSQUERY, EMPTY).unwrap();
+    assert_eq!(query, PgTsQuery::default());
+}
+
+#[test]
+fn rejects_invalid_vectors() {
+    for len in [0, 3, 5, 7, 9, CAPTURED.len() - 1] {
+        assert!(
+            PgTsVector::from_sql(&Type::TS_VECTOR, &CAPTURED[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    let mut trailing = CAPTURED.to_vec();
+    trailing.push(0);
+    assert!(PgTsVector::from_sql(&Type::TS_VECTOR, &trailing).is_err());
+
+    assert!(PgTsVector::from_sql(&Type::TS_VECTOR, b"\xff\xff\xff\xff").is_err());
+    assert!(
+        PgTsVector::from_sql(&Type::TS_VECTOR, b"\x00\x00\x00\x01\xff\x00\x00\x00")
+            .is_err()
+    );
+}
+
+#[test]
+fn rejects_invalid_queries() {
+    for len in [0, 3, 5, 9, CAPTURED_QUERY.len() - 1] {
+        assert!(
+            PgTsQuery::from_sql(&Type::TSQUERY, &CAPTURED_QUERY[..len]).is_err(),
+            "{} bytes",
+            len
+        );
+    }
+
+    // An unknown item type, an unknown operator, a missing operand and a
+    // value left over.
+    assert!(PgTsQuery::from_sql(&Type::TSQUERY, b"\x00\x00\x00\x01\x03").is_err());
+    assert!(PgTsQuery::from_sql(
+        &Type::TSQUERY,
+        b"\x00\x00\x00\x03\x02\x09\x01\x00\x00a\x00\x01\x00\x00b\x00"
+    )
+    .is_err());
+    assert!(PgTsQuery::from_sql(
+        &Type::TSQUERY,
+        b"\x00\x00\x00\x02\x02\x02\x01\x00\x00a\x00"
+    )
+    .is_err());
+    assert!(PgTsQuery::from_sql(
+        &Type::TSQUERY,
+        b"\x00\x00\x00\x02\x01\x00\x00a\x00\x01\x00\x00b\x00"
+    )
+    .is_err());
+}
+
+#[test]
+fn accepts_types_by_name() {
+    assert!(<PgTsVector as FromSql>::accepts(&Type::TS_VECTOR));
+    assert!(<PgTsQuery as FromSql>::accepts(&Type::TSQUERY));
+    assert!(!<PgTsVector as FromSql>::accepts(&Type::TSQUERY));
+    assert!(!<PgTsQuery as FromSql>::accepts(&Type::TEXT));
+}
@@ -0,0 +1,121 @@
+//! Checks the trimming of the padding of `CHAR(n)` columns, sent by Postgres
+//! as `bpchar` values.
+
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{FromTokioPostgresRow, PostgresMapper};
+use tokio_postgres::types::Type;
+use tokio_postgres::Row;
+
+mod common;
+
+use common::connect;
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "codes")]
+pub struct Code {
+    pub raw: String,
+    #[pg_mapper(trim)]
+    pub code: String,
+    #[pg_mapper(trim = "both")]
+    pub label: String,
+    #[pg_mapper(trim)]
+    pub note: Option<String>,
+    #[pg_mapper(trim, empty_as_none)]
+    pub blank: Option<String>,
+}
+
+#[derive(Debug, PartialEq, PostgresMapper)]
+#[pg_mapper(table = "codes", trim)]
+pub struct TrimmedCode {
+    pub raw: String,
+    pub code: String,
+    pub label: String,
+    pub note: Option<String>,
+    pub blank: Option<String>,
+}
+
+fn row(note: Option<&str>) -> Row {
+    MockRowBuilder::new()
+        .column("raw", Type::BPCHAR, "ab   ")
+        .column("code", Type::BPCHAR, "ab   ")
+        .column("label", Type::BPCHAR, "  a b  ")
+        .column("note", Type::BPCHAR, note)
+        .column("blank", Type::BPCHAR, "     ")
+        .build()
+}
+
+#[test]
+fn field_attributes() {
+    assert_eq!(
+        Code::from_row(row(Some("x\t\n "))).unwrap(),
+        Code {
+            raw: "ab   ".to_string(),
+            code: "ab".to_string(),
+            label: "a b".to_string(),
+            note: Some("x".to_string()),
+            blank: None,
+        }
+    );
+
+    assert_eq!(Code::from_row(row(None)).unwrap().note, None);
+}
+
+#[test]
+fn container_attribute_trims_the_end_of_every_string() {
+    assert_eq!(
+        TrimmedCode::from_row(row(Some("  x  "))).unwrap(),
+        TrimmedCode {
+            raw: "ab".to_string(),
+            code: "ab".to_string(),
+            label: "  a b".to_string(),
+            note: Some("  x".to_string()),
+            blank: Some(String::new()),
+        }
+    );
+}
+
+#[test]
+fn unicode_whitespace() {
+    let row = MockRowBuilder::new()
+        .column("raw", Type::BPCHAR, "é\u{a0}")
+        .column("code", Type::BPCHAR, "é\u{a0}")
+        .column("label", Type::BPCHAR, "\u{3000}é\u{3000}")
+        .column("note", Type::BPCHAR, None::<&str>)
+        .column("blank", Type::BPCHAR, "\u{2003}")
+        .build();
+
+    let code = Code::from_row(row).unwrap();
+    assert_eq!(code.code, "é");
+    assert_eq!(code.label, "é");
+    assert_eq!(code.blank, None);
+}
+
+#[tokio::test]
+async fn char_columns_are_padded_by_postgres() {
+    let client = match connect().await {
+        Some(client) => client,
+        None => return,
+    };
+
+    let row = client
+        .query_one(
+            "SELECT 'ab'::CHAR(5) AS raw, 'ab'::CHAR(5) AS code,
+                 '  a b'::CHAR(7) AS label, NULL::CHAR(3) AS note,
+                 ''::CHAR(4) AS blank",
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(row.columns()[0].type_(), &Type::BPCHAR);
+    assert_eq!(
+        Code::from_row_ref(&row).unwrap(),
+        Code {
+            raw: "ab   ".to_string(),
+            code: "ab".to_string(),
+            label: "a b".to_string(),
+            note: None,
+            blank: None,
+        }
+    );
+}
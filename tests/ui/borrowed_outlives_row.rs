@@ -0,0 +1,17 @@
+use tokio_pg_mapper::test_util::MockRowBuilder;
+use tokio_pg_mapper::{FromTokioPostgresRowBorrowed, PostgresMapper};
+use tokio_postgres::types::Type;
+
+#[derive(PostgresMapper)]
+#[pg_mapper(borrowed)]
+pub struct LogLine<'a> {
+    pub host: &'a str,
+}
+
+fn main() {
+    let row = MockRowBuilder::new().column("host", Type::TEXT, "db-1").build();
+    let line = LogLine::from_row_borrowed(&row).unwrap();
+
+    drop(row);
+    println!("{}", line.host);
+}
@@ -0,0 +1,13 @@
+use std::collections::HashMap;
+
+use tokio_pg_mapper::PostgresMapper;
+
+#[derive(PostgresMapper)]
+#[pg_mapper(composite = "item")]
+pub struct Item {
+    pub id: i64,
+    #[pg_mapper(capture_rest)]
+    pub rest: HashMap<String, String>,
+}
+
+fn main() {}
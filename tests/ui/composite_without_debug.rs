@@ -0,0 +1,9 @@
+use tokio_pg_mapper::PostgresMapper;
+
+#[derive(PostgresMapper)]
+#[pg_mapper(composite = "item")]
+pub struct Item {
+    pub id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use tokio_pg_mapper::{PostgresMapper, TokioPostgresCrud};
+
+#[derive(Clone, PostgresMapper)]
+#[pg_mapper(table = "memberships", crud)]
+pub struct Membership {
+    #[pg_mapper(primary_key)]
+    pub team_id: i64,
+    #[pg_mapper(primary_key)]
+    pub user_id: i64,
+}
+
+async fn load(client: &tokio_postgres::Client) {
+    let _ = Membership::load_many(client, &[(1, 2)]).await;
+}
+
+fn main() {
+    let _ = load;
+}